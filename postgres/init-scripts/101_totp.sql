@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id TEXT PRIMARY KEY REFERENCES "user" (id) ON DELETE CASCADE,
+    secret BYTEA NOT NULL,
+    confirmed BOOLEAN NOT NULL DEFAULT FALSE,
+    last_used_step BIGINT NULL,
+    created TIMESTAMPTZ NOT NULL DEFAULT now(),
+    confirmed_at TIMESTAMPTZ NULL
+);
+
+CREATE TABLE IF NOT EXISTS user_recovery_code (
+    id BIGSERIAL PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES "user" (id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    used TIMESTAMPTZ NULL
+);
+
+CREATE INDEX IF NOT EXISTS user_recovery_code_user_id_idx ON user_recovery_code (user_id);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS audit_event (
+    id TEXT PRIMARY KEY,
+    actor_id TEXT NULL,
+    method TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    before JSONB NULL,
+    after JSONB NULL,
+    created TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS audit_event_entity_idx ON audit_event (entity_type, entity_id, created);
+CREATE INDEX IF NOT EXISTS audit_event_actor_idx ON audit_event (actor_id, created);
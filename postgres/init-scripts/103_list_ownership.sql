@@ -0,0 +1,45 @@
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS owner_id TEXT NULL;
+
+-- list items created before ownership existed would not be visible to any user, so they are given
+-- to the only user, or to the user set with e.g. PGOPTIONS='-c webserver.list_owner_id=<user id>'
+DO $$
+DECLARE
+    unowned BIGINT;
+    configured_owner TEXT := nullif(current_setting('webserver.list_owner_id', TRUE), '');
+    new_owner TEXT;
+BEGIN
+    SELECT count(*) INTO unowned FROM list_item WHERE owner_id IS NULL;
+    IF unowned = 0 THEN
+        RETURN;
+    END IF;
+
+    IF configured_owner IS NOT NULL THEN
+        SELECT id INTO new_owner FROM "user" WHERE id = configured_owner;
+        IF new_owner IS NULL THEN
+            RAISE EXCEPTION 'webserver.list_owner_id is ''%'', which is not the id of a user', configured_owner;
+        END IF;
+    ELSIF (SELECT count(*) FROM "user") = 1 THEN
+        SELECT id INTO new_owner FROM "user";
+    ELSE
+        RAISE EXCEPTION '% list items have no owner and there isn''t exactly one user to give them to', unowned
+            USING HINT = 'set webserver.list_owner_id to the id of their owner, '
+                'e.g. with PGOPTIONS=''-c webserver.list_owner_id=<user id>''';
+    END IF;
+
+    UPDATE list_item SET owner_id = new_owner WHERE owner_id IS NULL;
+    RAISE NOTICE 'gave % list items without an owner to user %', unowned, new_owner;
+END
+$$;
+
+CREATE INDEX IF NOT EXISTS list_item_owner_idx ON list_item (owner_id, list_type);
+
+CREATE TABLE IF NOT EXISTS list_share (
+    owner_id TEXT NOT NULL,
+    list_type TEXT NOT NULL,
+    user_id TEXT NOT NULL REFERENCES "user" (id) ON DELETE CASCADE,
+    permission TEXT NOT NULL CHECK (permission IN ('read', 'write')),
+    created TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (owner_id, list_type, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS list_share_user_idx ON list_share (user_id);
@@ -0,0 +1,6 @@
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS quantity DOUBLE PRECISION NULL CHECK (quantity > 0);
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS unit TEXT NULL;
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS notes TEXT NULL;
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS due_date DATE NULL;
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS completed BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS completed_at TIMESTAMPTZ NULL;
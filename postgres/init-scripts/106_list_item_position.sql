@@ -0,0 +1,11 @@
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS position BIGINT NOT NULL DEFAULT 0;
+
+-- keep the current order (creation order) for existing items
+UPDATE list_item SET position = ordered.position
+FROM (
+    SELECT id, row_number() OVER (PARTITION BY owner_id, list_type ORDER BY created, id) AS position
+    FROM list_item
+) AS ordered
+WHERE list_item.id = ordered.id AND list_item.position = 0;
+
+CREATE INDEX IF NOT EXISTS list_item_position_idx ON list_item (owner_id, list_type, position);
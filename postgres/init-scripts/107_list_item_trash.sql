@@ -0,0 +1,3 @@
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS list_item_deleted_at_idx ON list_item (deleted_at) WHERE deleted_at IS NOT NULL;
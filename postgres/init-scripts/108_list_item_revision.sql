@@ -0,0 +1,48 @@
+CREATE SEQUENCE IF NOT EXISTS list_item_revision_seq;
+
+-- every mutation of a list item sets `revision` to the next value of the sequence
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS revision BIGINT NOT NULL DEFAULT nextval('list_item_revision_seq');
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS created_revision BIGINT NULL;
+UPDATE list_item SET created_revision = revision WHERE created_revision IS NULL;
+
+CREATE INDEX IF NOT EXISTS list_item_revision_idx ON list_item (owner_id, revision);
+
+-- items purged from the trash, so that clients that were offline still see them as deleted
+CREATE TABLE IF NOT EXISTS list_item_tombstone (
+    id TEXT PRIMARY KEY,
+    owner_id TEXT NULL,
+    list_type TEXT NOT NULL,
+    revision BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS list_item_tombstone_revision_idx ON list_item_tombstone (owner_id, revision);
+
+-- revisions are taken in order but can be committed out of order, so the transaction is given an
+-- id before it takes a revision, and the change feed only returns the revisions taken before a
+-- snapshot once every transaction that was running at the time of the snapshot has ended
+CREATE OR REPLACE FUNCTION assign_list_item_revision() RETURNS TRIGGER AS $$
+BEGIN
+    -- updates that keep the revision, like the items shifted by a move, don't get a new one
+    IF TG_OP = 'UPDATE' AND NEW.revision = OLD.revision THEN
+        RETURN NEW;
+    END IF;
+
+    PERFORM pg_current_xact_id();
+    NEW.revision := nextval('list_item_revision_seq');
+    IF TG_TABLE_NAME = 'list_item' AND TG_OP = 'INSERT' THEN
+        NEW.created_revision := NEW.revision;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS list_item_revision ON list_item;
+CREATE TRIGGER list_item_revision
+    BEFORE INSERT OR UPDATE ON list_item
+    FOR EACH ROW EXECUTE FUNCTION assign_list_item_revision();
+
+DROP TRIGGER IF EXISTS list_item_tombstone_revision ON list_item_tombstone;
+CREATE TRIGGER list_item_tombstone_revision
+    BEFORE INSERT OR UPDATE ON list_item_tombstone
+    FOR EACH ROW EXECUTE FUNCTION assign_list_item_revision();
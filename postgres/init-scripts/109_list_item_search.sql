@@ -0,0 +1,8 @@
+-- the 'simple' configuration doesn't stem, since list items are written in more than one language
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', item_name), 'A') ||
+        setweight(to_tsvector('simple', coalesce(notes, '')), 'B')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS list_item_search_vector_idx ON list_item USING GIN (search_vector);
@@ -0,0 +1,41 @@
+-- notifications are only delivered when the transaction commits, and to every listening server
+CREATE OR REPLACE FUNCTION notify_list_item_change() RETURNS TRIGGER AS $$
+DECLARE
+    item list_item;
+BEGIN
+    -- updates that keep the revision, like the items shifted by a move, aren't changes for clients
+    IF TG_OP = 'UPDATE' AND OLD.revision = NEW.revision THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP = 'DELETE' THEN
+        item := OLD;
+    ELSE
+        item := NEW;
+    END IF;
+
+    PERFORM pg_notify('list_item_change', json_build_object(
+        'id', item.id,
+        'owner_id', item.owner_id,
+        'list_type', item.list_type,
+        'revision', item.revision
+    )::text);
+
+    -- a renamed or merged list type also changes for those listening to the old name
+    IF TG_OP = 'UPDATE' AND OLD.list_type IS DISTINCT FROM NEW.list_type THEN
+        PERFORM pg_notify('list_item_change', json_build_object(
+            'id', OLD.id,
+            'owner_id', OLD.owner_id,
+            'list_type', OLD.list_type,
+            'revision', NEW.revision
+        )::text);
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS list_item_notify ON list_item;
+CREATE TRIGGER list_item_notify
+    AFTER INSERT OR UPDATE OR DELETE ON list_item
+    FOR EACH ROW EXECUTE FUNCTION notify_list_item_change();
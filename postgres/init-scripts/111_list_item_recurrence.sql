@@ -0,0 +1,6 @@
+-- how a completed item comes back, e.g. {"frequency": "weekly", "weekdays": ["Mon", "Thu"]}
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS recurrence JSONB NULL;
+
+-- the recurrence scheduler only looks at completed items that recur
+CREATE INDEX IF NOT EXISTS list_item_recurring_completed_idx ON list_item (completed_at)
+    WHERE recurrence IS NOT NULL AND completed AND deleted_at IS NULL;
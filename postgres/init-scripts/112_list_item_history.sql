@@ -0,0 +1,60 @@
+-- the user that made the latest change of the item, NULL for changes made by the server itself
+ALTER TABLE list_item ADD COLUMN IF NOT EXISTS updated_by TEXT NULL;
+
+-- every version of every item, purged together with the item
+CREATE TABLE IF NOT EXISTS list_item_history (
+    list_item_id TEXT NOT NULL REFERENCES list_item (id) ON DELETE CASCADE,
+    version BIGINT NOT NULL,
+    actor_id TEXT NULL,
+    changed_at TIMESTAMPTZ NOT NULL,
+    list_type TEXT NOT NULL,
+    item_name TEXT NOT NULL,
+    quantity DOUBLE PRECISION NULL,
+    unit TEXT NULL,
+    notes TEXT NULL,
+    due_date DATE NULL,
+    completed BOOLEAN NOT NULL,
+    completed_at TIMESTAMPTZ NULL,
+    recurrence JSONB NULL,
+    deleted_at TIMESTAMPTZ NULL,
+    PRIMARY KEY (list_item_id, version)
+);
+
+-- items that existed before the history start with their current version
+INSERT INTO list_item_history (
+    list_item_id, version, actor_id, changed_at, list_type, item_name, quantity, unit, notes,
+    due_date, completed, completed_at, recurrence, deleted_at
+)
+SELECT id, version, NULL, updated, list_type, item_name, quantity, unit, notes,
+    due_date, completed, completed_at, recurrence, deleted_at
+FROM list_item
+ON CONFLICT DO NOTHING;
+
+-- every change of an item increments its version, moving an item only changes its position
+CREATE OR REPLACE FUNCTION record_list_item_version() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO list_item_history (
+        list_item_id, version, actor_id, changed_at, list_type, item_name, quantity, unit, notes,
+        due_date, completed, completed_at, recurrence, deleted_at
+    )
+    VALUES (
+        NEW.id, NEW.version, NEW.updated_by, now(), NEW.list_type, NEW.item_name, NEW.quantity,
+        NEW.unit, NEW.notes, NEW.due_date, NEW.completed, NEW.completed_at, NEW.recurrence,
+        NEW.deleted_at
+    )
+    ON CONFLICT (list_item_id, version) DO NOTHING;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS list_item_history_insert ON list_item;
+CREATE TRIGGER list_item_history_insert
+    AFTER INSERT ON list_item
+    FOR EACH ROW EXECUTE FUNCTION record_list_item_version();
+
+DROP TRIGGER IF EXISTS list_item_history_update ON list_item;
+CREATE TRIGGER list_item_history_update
+    AFTER UPDATE ON list_item
+    FOR EACH ROW WHEN (OLD.version IS DISTINCT FROM NEW.version)
+    EXECUTE FUNCTION record_list_item_version();
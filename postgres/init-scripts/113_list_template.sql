@@ -0,0 +1,21 @@
+-- named snapshots of list types, e.g. a packing list, that new list types can be created from
+CREATE TABLE IF NOT EXISTS list_template (
+    owner_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    created TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (owner_id, name)
+);
+
+-- items are copied without their due dates and completion
+CREATE TABLE IF NOT EXISTS list_template_item (
+    owner_id TEXT NOT NULL,
+    template_name TEXT NOT NULL,
+    position BIGINT NOT NULL,
+    item_name TEXT NOT NULL,
+    quantity DOUBLE PRECISION NULL,
+    unit TEXT NULL,
+    notes TEXT NULL,
+    PRIMARY KEY (owner_id, template_name, position),
+    FOREIGN KEY (owner_id, template_name) REFERENCES list_template (owner_id, name) ON DELETE CASCADE
+);
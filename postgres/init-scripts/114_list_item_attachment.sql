@@ -0,0 +1,12 @@
+-- files attached to list items, e.g. receipts and photos, the files themselves are stored on disk
+CREATE TABLE IF NOT EXISTS list_item_attachment (
+    id TEXT PRIMARY KEY,
+    list_item_id TEXT NOT NULL REFERENCES list_item (id) ON DELETE CASCADE,
+    file_name TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    uploaded_by TEXT NULL,
+    created TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS list_item_attachment_list_item_idx ON list_item_attachment (list_item_id, created);
@@ -0,0 +1,6 @@
+-- the secret token of the calendar feed of each user, only a hash of it is stored
+CREATE TABLE IF NOT EXISTS calendar_feed (
+    user_id TEXT PRIMARY KEY,
+    token_hash TEXT NOT NULL UNIQUE,
+    created TIMESTAMPTZ NOT NULL DEFAULT now()
+);
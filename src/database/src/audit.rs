@@ -0,0 +1,94 @@
+use crate::{Database, DatabaseResult, InsertionResult};
+use sqlx::{types::time::OffsetDateTime, FromRow};
+
+pub type AuditDb = Database<AuditEvent>;
+
+/// A change made by a mutating method. `before` and `after` are JSON documents.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+#[non_exhaustive]
+pub struct AuditEvent {
+    pub id: String,
+    pub actor_id: Option<String>,
+    pub method: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub created: OffsetDateTime,
+}
+
+/// Optional filters for `AuditDb::get_audit_events`, `None` matches everything.
+#[derive(Debug, Clone, Default)]
+pub struct AuditEventFilter<'a> {
+    pub actor_id: Option<&'a str>,
+    pub method: Option<&'a str>,
+    pub entity_type: Option<&'a str>,
+    pub entity_id: Option<&'a str>,
+}
+
+impl AuditDb {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_audit_event(
+        &self,
+        id: &str,
+        actor_id: Option<&str>,
+        method: &str,
+        entity_type: &str,
+        entity_id: &str,
+        before: Option<&str>,
+        after: Option<&str>,
+    ) -> DatabaseResult<InsertionResult> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query(
+            "
+        INSERT INTO audit_event (id, actor_id, method, entity_type, entity_id, before, after)
+        VALUES ($1, $2, $3, $4, $5, $6::jsonb, $7::jsonb)",
+        )
+        .bind(id)
+        .bind(actor_id)
+        .bind(method)
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(before)
+        .bind(after)
+        .execute(&mut db)
+        .await?;
+
+        Ok(InsertionResult::from_changed_rows(
+            query_result.rows_affected(),
+        ))
+    }
+
+    /// Newest events first.
+    pub async fn get_audit_events(
+        &self,
+        filter: &AuditEventFilter<'_>,
+        limit: i64,
+        offset: i64,
+    ) -> DatabaseResult<Vec<AuditEvent>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, AuditEvent>(
+            "
+        SELECT id, actor_id, method, entity_type, entity_id, before::text, after::text, created
+        FROM audit_event
+        WHERE ($1::text IS NULL OR actor_id = $1)
+            AND ($2::text IS NULL OR method = $2)
+            AND ($3::text IS NULL OR entity_type = $3)
+            AND ($4::text IS NULL OR entity_id = $4)
+        ORDER BY created DESC
+        LIMIT $5 OFFSET $6",
+        )
+        .bind(filter.actor_id)
+        .bind(filter.method)
+        .bind(filter.entity_type)
+        .bind(filter.entity_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+}
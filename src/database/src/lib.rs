@@ -9,12 +9,18 @@ use std::{fmt::Display, marker::PhantomData};
 #[macro_use]
 extern crate log;
 
+mod audit;
 mod list;
+mod login;
 mod server;
+mod totp;
 mod user;
 
+pub use audit::*;
 pub use list::*;
+pub use login::*;
 pub use server::*;
+pub use totp::*;
 pub use user::*;
 
 pub type DatabaseResult<T> = Result<T, DatabaseError>;
@@ -22,6 +28,7 @@ pub type DatabaseResult<T> = Result<T, DatabaseError>;
 pub struct Database<T> {
     path: String,
     pool: sqlx::PgPool,
+    revision_watermark: std::sync::Mutex<list::RevisionWatermark>,
     _phantom: PhantomData<T>,
 }
 
@@ -30,6 +37,7 @@ impl<T> Database<T> {
         Ok(Self {
             pool: sqlx::PgPool::connect(&path).await?,
             path,
+            revision_watermark: Default::default(),
             _phantom: PhantomData,
         })
     }
@@ -47,6 +55,39 @@ impl<T> Database<T> {
     }
 }
 
+/// The database that tests run against, set with `TEST_DATABASE_URL`.
+///
+/// It needs the schema of `postgres/init-scripts`, and is shared by the tests, so they only touch
+/// rows with ids from `unique_id`. Tests that need it are skipped if it isn't set.
+#[cfg(test)]
+pub(crate) async fn test_database<T>() -> Option<Database<T>> {
+    match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => Some(
+            Database::new(url)
+                .await
+                .expect("failed to connect to TEST_DATABASE_URL"),
+        ),
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL is not set, skipping");
+            None
+        }
+    }
+}
+
+/// An id that no other test uses.
+#[cfg(test)]
+pub(crate) fn unique_id(prefix: &str) -> String {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let nanos = time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{}-{}-{}", prefix, std::process::id(), nanos, count)
+}
+
 #[derive(Debug)]
 pub enum DatabaseError {
     SqlxError(sqlx::Error),
@@ -70,7 +111,7 @@ impl Display for DatabaseError {
 
 impl std::error::Error for DatabaseError {}
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum InsertionResult {
     Inserted,
     AlreadyExists,
@@ -1,54 +1,385 @@
 use crate::{Database, DatabaseResult, InsertionResult};
-use sqlx::{postgres::PgRow, types::time::OffsetDateTime, Row};
+use sqlx::{
+    postgres::{PgListener, PgRow},
+    types::time::OffsetDateTime,
+    FromRow, PgConnection, Row,
+};
+use std::{
+    cmp::{self, Ordering},
+    collections::HashMap,
+};
 
-#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+/// The channel that the `list_item_notify` trigger notifies on every change of a list item.
+const LIST_ITEM_CHANGE_CHANNEL: &str = "list_item_change";
+
+/// `due_date` is selected as an ISO 8601 date string (`YYYY-MM-DD`).
+const LIST_ITEM_COLUMNS: &str = "id, list_type, item_name, created, owner_id, version, updated, \
+    quantity, unit, notes, due_date::text AS due_date, completed, completed_at, deleted_at, \
+    revision, created_revision, recurrence::text AS recurrence";
+
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
 #[non_exhaustive]
 pub struct ListItem {
     pub id: String,
     pub list_type: String,
     pub item_name: String,
     pub created: OffsetDateTime,
+    pub owner_id: Option<String>,
+    pub version: i64,
+    pub updated: OffsetDateTime,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub notes: Option<String>,
+    pub due_date: Option<String>,
+    pub completed: bool,
+    pub completed_at: Option<OffsetDateTime>,
+    /// Set when the item is in the trash.
+    pub deleted_at: Option<OffsetDateTime>,
+    /// Set from `list_item_revision_seq` on every change of the item.
+    pub revision: i64,
+    /// The revision at which the item was inserted.
+    pub created_revision: Option<i64>,
+    /// How the item comes back after it has been completed, as JSON.
+    pub recurrence: Option<String>,
 }
 
-impl ListItem {
-    fn new(id: String, list_type: String, item_name: String, created: OffsetDateTime) -> Self {
-        Self {
-            id,
-            list_type,
-            item_name,
-            created,
+/// A version of a list item, one is recorded by the database on every change of the item.
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+#[non_exhaustive]
+pub struct ListItemVersion {
+    pub list_item_id: String,
+    pub version: i64,
+    /// The user that made the change, `None` for changes made by the server itself.
+    pub actor_id: Option<String>,
+    pub changed_at: OffsetDateTime,
+    pub list_type: String,
+    pub item_name: String,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub notes: Option<String>,
+    pub due_date: Option<String>,
+    pub completed: bool,
+    pub completed_at: Option<OffsetDateTime>,
+    pub recurrence: Option<String>,
+    pub deleted_at: Option<OffsetDateTime>,
+}
+
+/// A list item matching a search, with the matching words surrounded by `<mark>` and `</mark>`.
+///
+/// `item_name_highlight` is the whole item name, `notes_highlight` only the matching fragments.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ListItemSearchHit {
+    pub list_item: ListItem,
+    pub rank: f32,
+    pub item_name_highlight: String,
+    pub notes_highlight: Option<String>,
+}
+
+impl<'r> FromRow<'r, PgRow> for ListItemSearchHit {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            list_item: ListItem::from_row(row)?,
+            rank: row.try_get("rank")?,
+            item_name_highlight: row.try_get("item_name_highlight")?,
+            notes_highlight: row.try_get("notes_highlight")?,
+        })
+    }
+}
+
+/// Sent when a change of a list item is committed, by any server sharing the database.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[non_exhaustive]
+pub struct ListItemNotification {
+    pub id: String,
+    pub owner_id: Option<String>,
+    pub list_type: String,
+    pub revision: i64,
+}
+
+/// Receives the notifications of list item changes, see `Database::<ListItem>::listen`.
+pub struct ListItemListener {
+    listener: PgListener,
+}
+
+impl ListItemListener {
+    /// Waits for the next notification.
+    ///
+    /// Returns `None` if the connection was lost, in which case notifications may have been missed.
+    /// The listener reconnects on the next call.
+    pub async fn recv(&mut self) -> DatabaseResult<Option<ListItemNotification>> {
+        loop {
+            let notification = match self.listener.try_recv().await? {
+                Some(notification) => notification,
+                None => return Ok(None),
+            };
+
+            match serde_json::from_str(notification.payload()) {
+                Ok(notification) => return Ok(Some(notification)),
+                Err(e) => error!(
+                    "invalid list item notification '{}' with error: '{}'",
+                    notification.payload(),
+                    e
+                ),
+            }
+        }
+    }
+}
+
+/// The revisions that `get_committed_revision` knows to be committed.
+///
+/// The `list_item_revision` trigger gives transactions an id before they take a revision, so the
+/// revisions taken before a snapshot are committed (or rolled back) once no transaction older
+/// than the xmax of the snapshot is running.
+#[derive(Debug, Default)]
+pub(crate) struct RevisionWatermark {
+    committed: i64,
+    /// The xmax of a snapshot and the last revision taken before it, not yet known to be committed.
+    pending: Option<(i64, i64)>,
+}
+
+impl RevisionWatermark {
+    /// Takes `last_revision` and the snapshot after it, where `xmin` is the oldest transaction
+    /// that is still running, and returns the committed revision.
+    fn advance(&mut self, last_revision: i64, xmin: i64, xmax: i64) -> i64 {
+        let samples = self.pending.take().into_iter();
+        for (sample_xmax, revision) in samples.chain(Some((xmax, last_revision))) {
+            if xmin >= sample_xmax {
+                self.committed = cmp::max(self.committed, revision);
+            } else if self.pending.is_none() {
+                self.pending = Some((sample_xmax, revision));
+            }
+        }
+
+        self.committed
+    }
+}
+
+/// A list item that has been purged from the trash.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+#[non_exhaustive]
+pub struct ListItemTombstone {
+    pub id: String,
+    pub owner_id: Option<String>,
+    pub list_type: String,
+    pub revision: i64,
+}
+
+/// A change of a list item, as returned by `get_list_item_changes`.
+/// Items that are in the trash are returned as `Changed` with `deleted_at` set.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListItemChange {
+    Changed(Box<ListItem>),
+    Purged(ListItemTombstone),
+}
+
+impl ListItemChange {
+    pub fn revision(&self) -> i64 {
+        match self {
+            ListItemChange::Changed(list_item) => list_item.revision,
+            ListItemChange::Purged(tombstone) => tombstone.revision,
+        }
+    }
+}
+
+/// The optional checklist fields of a list item, `due_date` is an ISO 8601 date (`YYYY-MM-DD`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListItemDetails<'a> {
+    pub quantity: Option<f64>,
+    pub unit: Option<&'a str>,
+    pub notes: Option<&'a str>,
+    pub due_date: Option<&'a str>,
+}
+
+/// Where to put a moved list item relative to the target item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListItemPlacement {
+    Before,
+    After,
+}
+
+/// What to do with items of the merged list types that have the same name (ignoring case).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateItemStrategy {
+    KeepBoth,
+    /// Duplicates from the source list type are moved to the trash.
+    KeepTarget,
+    /// Duplicates in the target list type are moved to the trash.
+    KeepSource,
+}
+
+impl DuplicateItemStrategy {
+    /// The list type to move the duplicates out of and the list type that keeps its items,
+    /// `None` if both keep their items.
+    fn trashed_and_kept<'a>(self, source: &'a str, target: &'a str) -> Option<(&'a str, &'a str)> {
+        match self {
+            DuplicateItemStrategy::KeepBoth => None,
+            DuplicateItemStrategy::KeepTarget => Some((source, target)),
+            DuplicateItemStrategy::KeepSource => Some((target, source)),
         }
     }
 }
 
+/// The outcome of `merge_list_types`, items in the trash are not counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListTypeMerge {
+    pub moved: u64,
+    pub discarded: u64,
+}
+
+/// The outcome of `purge_deleted_list_items`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgedListItems {
+    pub purged: u64,
+    /// The attachments that were deleted along with the items, their files are to be removed.
+    pub attachment_ids: Vec<String>,
+}
+
+/// A list item to insert with `insert_list_items`.
+#[derive(Debug, Clone, Copy)]
+pub struct NewListItem<'a> {
+    pub id: &'a str,
+    pub item_name: &'a str,
+    pub details: ListItemDetails<'a>,
+    pub completed: bool,
+}
+
+/// One of the moves of `move_list_items`, see `move_list_item`.
+#[derive(Debug, Clone, Copy)]
+pub struct ListItemMove<'a> {
+    pub id: &'a str,
+    pub target_id: &'a str,
+    pub placement: ListItemPlacement,
+}
+
+/// Access to a list type given by its owner to another user.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+#[non_exhaustive]
+pub struct ListShare {
+    pub owner_id: String,
+    pub list_type: String,
+    pub user_id: String,
+    pub permission: String,
+}
+
+/// An attachment to insert with `insert_list_item_attachment`.
+#[derive(Debug, Clone, Copy)]
+pub struct NewListItemAttachment<'a> {
+    pub id: &'a str,
+    pub list_item_id: &'a str,
+    pub file_name: &'a str,
+    pub content_type: &'a str,
+    pub size_bytes: i64,
+    pub uploaded_by: Option<&'a str>,
+}
+
+/// A named copy of the items of a list type, see `save_list_template`.
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+#[non_exhaustive]
+pub struct ListTemplate {
+    pub owner_id: String,
+    pub name: String,
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+/// An item of a list template, without due date and completion.
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+#[non_exhaustive]
+pub struct ListTemplateItem {
+    pub template_name: String,
+    pub position: i64,
+    pub item_name: String,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// A file attached to a list item, the file itself is stored outside of the database.
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+#[non_exhaustive]
+pub struct ListItemAttachment {
+    pub id: String,
+    pub list_item_id: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub uploaded_by: Option<String>,
+    pub created: OffsetDateTime,
+}
+
+/// The outcome of `instantiate_list_template`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateInstantiation {
+    /// The items of the new list type, in order.
+    Created(Vec<ListItem>),
+    MissingTemplate,
+    /// The list type already has items.
+    ListTypeExists,
+}
+
 impl Database<ListItem> {
+    /// Listens for the notifications of all list item changes, on a connection of its own.
+    pub async fn listen(&self) -> DatabaseResult<ListItemListener> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(LIST_ITEM_CHANGE_CHANNEL).await?;
+
+        Ok(ListItemListener { listener })
+    }
+
     pub async fn insert_list_item(
         &self,
         id: &str,
+        owner_id: &str,
         list_type: &str,
         item_name: &str,
+        details: ListItemDetails<'_>,
+        actor_id: Option<&str>,
     ) -> DatabaseResult<InsertionResult> {
         let mut db = self.get_connection().await?;
 
-        let query_result =
-            sqlx::query("INSERT INTO list_item (id, list_type, item_name) VALUES ($1, $2, $3)")
-                .bind(id)
-                .bind(list_type)
-                .bind(item_name)
-                .execute(&mut db)
-                .await?;
+        let item = NewListItem {
+            id,
+            item_name,
+            details,
+            completed: false,
+        };
+        insert_item(&mut db, owner_id, list_type, &item, actor_id).await
+    }
+
+    /// Inserts either all of `items` or none of them, nothing is inserted if any of them already exists.
+    ///
+    /// The results are in the same order as `items`.
+    pub async fn insert_list_items(
+        &self,
+        owner_id: &str,
+        list_type: &str,
+        items: &[NewListItem<'_>],
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<Vec<InsertionResult>> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut results = Vec::with_capacity(items.len());
+        for item in items {
+            let result = insert_item(&mut tx, owner_id, list_type, item, actor_id).await?;
+            results.push(result);
+        }
 
-        Ok(InsertionResult::from_changed_rows(
-            query_result.rows_affected(),
-        ))
+        if results.iter().all(|r| *r == InsertionResult::Inserted) {
+            tx.commit().await?;
+        }
+
+        Ok(results)
     }
 
+    /// Items in the trash are not included.
     pub async fn get_list_item(&self, id: &str) -> DatabaseResult<Option<ListItem>> {
         let mut db = self.get_connection().await?;
 
-        let mut query_result = sqlx::query_as::<_, ListItem>(
-            "SELECT id, list_type, item_name, created FROM list_item WHERE id = $1",
-        )
+        let mut query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "SELECT {} FROM list_item WHERE id = $1 AND deleted_at IS NULL",
+            LIST_ITEM_COLUMNS
+        ))
         .bind(id)
         .fetch_all(&mut db)
         .await?;
@@ -63,65 +394,2062 @@ impl Database<ListItem> {
         }
     }
 
-    pub async fn get_list_types(&self) -> DatabaseResult<Vec<String>> {
+    /// The list types owned by `owner_id`.
+    pub async fn get_list_types(&self, owner_id: &str) -> DatabaseResult<Vec<String>> {
         let mut db = self.get_connection().await?;
 
-        let query_result = sqlx::query("SELECT DISTINCT list_type FROM list_item")
-            .map(|row: PgRow| {
-                let list_type: &str = row.get("list_type");
-                list_type.to_owned()
-            })
-            .fetch_all(&mut db)
-            .await?;
+        let query_result = sqlx::query(
+            "SELECT DISTINCT list_type FROM list_item WHERE owner_id = $1 AND deleted_at IS NULL",
+        )
+        .bind(owner_id)
+        .map(|row: PgRow| {
+            let list_type: &str = row.get("list_type");
+            list_type.to_owned()
+        })
+        .fetch_all(&mut db)
+        .await?;
 
         Ok(query_result)
     }
 
-    pub async fn update_list_item(&self, id: &str, item_name: &str) -> DatabaseResult<u64> {
+    /// The list types that other users have shared with `user_id`.
+    pub async fn get_shared_list_types(&self, user_id: &str) -> DatabaseResult<Vec<ListShare>> {
         let mut db = self.get_connection().await?;
 
-        let query_result = sqlx::query("UPDATE list_item SET item_name = $1 WHERE id = $2")
-            .bind(item_name)
-            .bind(id)
-            .execute(&mut db)
-            .await?;
+        let query_result = sqlx::query_as::<_, ListShare>(
+            "
+        SELECT owner_id, list_type, user_id, permission
+        FROM list_share
+        WHERE user_id = $1
+        ORDER BY owner_id, list_type",
+        )
+        .bind(user_id)
+        .fetch_all(&mut db)
+        .await?;
 
-        Ok(query_result.rows_affected())
+        Ok(query_result)
+    }
+
+    pub async fn get_list_share(
+        &self,
+        owner_id: &str,
+        list_type: &str,
+        user_id: &str,
+    ) -> DatabaseResult<Option<ListShare>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListShare>(
+            "
+        SELECT owner_id, list_type, user_id, permission
+        FROM list_share
+        WHERE owner_id = $1 AND list_type = $2 AND user_id = $3",
+        )
+        .bind(owner_id)
+        .bind(list_type)
+        .bind(user_id)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Inserts the share, or replaces the permission if the list type is already shared with `user_id`.
+    ///
+    /// Returns false if there is no user with `user_id`.
+    pub async fn share_list_type(
+        &self,
+        owner_id: &str,
+        list_type: &str,
+        user_id: &str,
+        permission: &str,
+    ) -> DatabaseResult<bool> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query(
+            r#"
+        INSERT INTO list_share (owner_id, list_type, user_id, permission)
+        SELECT $1, $2, $3, $4
+        WHERE EXISTS (SELECT 1 FROM "user" WHERE id = $3)
+        ON CONFLICT (owner_id, list_type, user_id) DO UPDATE SET permission = EXCLUDED.permission"#,
+        )
+        .bind(owner_id)
+        .bind(list_type)
+        .bind(user_id)
+        .bind(permission)
+        .execute(&mut db)
+        .await?;
+
+        Ok(query_result.rows_affected() > 0)
     }
 
-    pub async fn get_list_items(&self, list_type: &str) -> DatabaseResult<Vec<ListItem>> {
+    pub async fn unshare_list_type(
+        &self,
+        owner_id: &str,
+        list_type: &str,
+        user_id: &str,
+    ) -> DatabaseResult<bool> {
         let mut db = self.get_connection().await?;
 
-        let query_result = sqlx::query_as::<_, ListItem>(
-            "SELECT id, list_type, item_name, created FROM list_item WHERE list_type = $1",
+        let query_result = sqlx::query(
+            "DELETE FROM list_share WHERE owner_id = $1 AND list_type = $2 AND user_id = $3",
         )
+        .bind(owner_id)
         .bind(list_type)
+        .bind(user_id)
+        .execute(&mut db)
+        .await?;
+
+        Ok(query_result.rows_affected() == 1)
+    }
+
+    /// Updates the item only if it is still at `expected_version`, and returns the updated item.
+    ///
+    /// Returns `None` if the item doesn't exist or has been changed since `expected_version`.
+    pub async fn update_list_item(
+        &self,
+        id: &str,
+        expected_version: i64,
+        item_name: &str,
+        details: ListItemDetails<'_>,
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<Option<ListItem>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        UPDATE list_item
+        SET item_name = $1, quantity = $2, unit = $3, notes = $4, due_date = $5::date,
+            version = version + 1, updated = now(), updated_by = $8,
+            revision = nextval('list_item_revision_seq')
+        WHERE id = $6 AND version = $7 AND deleted_at IS NULL
+        RETURNING {}",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(item_name)
+        .bind(details.quantity)
+        .bind(details.unit)
+        .bind(details.notes)
+        .bind(details.due_date)
+        .bind(id)
+        .bind(expected_version)
+        .bind(actor_id)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Marks the item as completed or not, and returns the updated item.
+    /// An item that is already completed keeps its original `completed_at`.
+    pub async fn set_list_item_completed(
+        &self,
+        id: &str,
+        completed: bool,
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<Option<ListItem>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        UPDATE list_item
+        SET completed = $1,
+            completed_at = CASE WHEN $1 THEN COALESCE(completed_at, now()) ELSE NULL END,
+            version = version + 1, updated = now(), updated_by = $3,
+            revision = nextval('list_item_revision_seq')
+        WHERE id = $2 AND deleted_at IS NULL
+        RETURNING {}",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(completed)
+        .bind(id)
+        .bind(actor_id)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Sets or clears (with `None`) the JSON recurrence of the item, and returns the updated item.
+    pub async fn set_list_item_recurrence(
+        &self,
+        id: &str,
+        recurrence: Option<&str>,
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<Option<ListItem>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        UPDATE list_item
+        SET recurrence = $1::jsonb,
+            version = version + 1, updated = now(), updated_by = $3,
+            revision = nextval('list_item_revision_seq')
+        WHERE id = $2 AND deleted_at IS NULL
+        RETURNING {}",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(recurrence)
+        .bind(id)
+        .bind(actor_id)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// The versions of the item, newest first.
+    pub async fn get_list_item_history(&self, id: &str) -> DatabaseResult<Vec<ListItemVersion>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItemVersion>(
+            "
+        SELECT list_item_id, version, actor_id, changed_at, list_type, item_name, quantity, unit,
+            notes, due_date::text AS due_date, completed, completed_at,
+            recurrence::text AS recurrence, deleted_at
+        FROM list_item_history
+        WHERE list_item_id = $1
+        ORDER BY version DESC",
+        )
+        .bind(id)
         .fetch_all(&mut db)
         .await?;
 
         Ok(query_result)
     }
 
-    pub async fn delete_list_item(&self, id: &str) -> DatabaseResult<bool> {
+    /// Sets the name, details, completion and recurrence of the item back to those of `version`,
+    /// as a new version of the item, and returns the reverted item.
+    ///
+    /// The list type of the item is kept, and items in the trash have to be restored first.
+    /// Returns `None` if the item or the version doesn't exist.
+    pub async fn revert_list_item(
+        &self,
+        id: &str,
+        version: i64,
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<Option<ListItem>> {
         let mut db = self.get_connection().await?;
 
-        let query_result = sqlx::query("DELETE FROM list_item WHERE id = $1")
-            .bind(id)
-            .execute(&mut db)
-            .await?;
+        let query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        UPDATE list_item
+        SET (item_name, quantity, unit, notes, due_date, completed, completed_at, recurrence) = (
+                SELECT item_name, quantity, unit, notes, due_date, completed, completed_at, recurrence
+                FROM list_item_history
+                WHERE list_item_id = $1 AND version = $2
+            ),
+            version = version + 1, updated = now(), updated_by = $3,
+            revision = nextval('list_item_revision_seq')
+        WHERE id = $1 AND deleted_at IS NULL AND EXISTS (
+            SELECT 1 FROM list_item_history WHERE list_item_id = $1 AND version = $2
+        )
+        RETURNING {}",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(id)
+        .bind(version)
+        .bind(actor_id)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Completed items with a recurrence, of all owners.
+    pub async fn get_completed_recurring_list_items(&self) -> DatabaseResult<Vec<ListItem>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        SELECT {}
+        FROM list_item
+        WHERE recurrence IS NOT NULL AND completed AND deleted_at IS NULL
+        ORDER BY completed_at",
+            LIST_ITEM_COLUMNS
+        ))
+        .fetch_all(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Marks a recurring item as not completed, if it is still the same completion as `completed_at`.
+    ///
+    /// Returns `false` if the item has been uncompleted, completed again or deleted in the meantime.
+    pub async fn reopen_recurring_list_item(
+        &self,
+        id: &str,
+        completed_at: OffsetDateTime,
+    ) -> DatabaseResult<bool> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query(
+            "
+        UPDATE list_item
+        SET completed = FALSE, completed_at = NULL,
+            version = version + 1, updated = now(), updated_by = NULL,
+            revision = nextval('list_item_revision_seq')
+        WHERE id = $1 AND completed AND completed_at = $2
+            AND recurrence IS NOT NULL AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(completed_at)
+        .execute(&mut db)
+        .await?;
 
         Ok(query_result.rows_affected() == 1)
     }
 
-    pub async fn rename_list_type(&self, old_name: &str, new_name: &str) -> DatabaseResult<u64> {
+    pub async fn get_list_items(
+        &self,
+        owner_id: &str,
+        list_type: &str,
+    ) -> DatabaseResult<Vec<ListItem>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        SELECT {}
+        FROM list_item
+        WHERE owner_id = $1 AND list_type = $2 AND deleted_at IS NULL
+        ORDER BY position, created, id",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(owner_id)
+        .bind(list_type)
+        .fetch_all(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// The items of all list types of `owner_id` that have a due date, by due date.
+    pub async fn get_due_list_items(&self, owner_id: &str) -> DatabaseResult<Vec<ListItem>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        SELECT {}
+        FROM list_item
+        WHERE owner_id = $1 AND due_date IS NOT NULL AND deleted_at IS NULL
+        ORDER BY due_date, created, id",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(owner_id)
+        .fetch_all(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Replaces the calendar feed token of `user_id`, so that only the new token is valid.
+    pub async fn set_calendar_feed_token(
+        &self,
+        user_id: &str,
+        token_hash: &str,
+    ) -> DatabaseResult<()> {
+        let mut db = self.get_connection().await?;
+
+        sqlx::query(
+            "
+        INSERT INTO calendar_feed (user_id, token_hash) VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE SET token_hash = EXCLUDED.token_hash, created = now()",
+        )
+        .bind(user_id)
+        .bind(token_hash)
+        .execute(&mut db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The user whose calendar feed token has the hash `token_hash`.
+    pub async fn get_calendar_feed_user(&self, token_hash: &str) -> DatabaseResult<Option<String>> {
         let mut db = self.get_connection().await?;
 
-        let query_result = sqlx::query("UPDATE list_item SET list_type = $1 WHERE list_type = $2")
-            .bind(new_name)
-            .bind(old_name)
-            .execute(&mut db)
+        let query_result = sqlx::query("SELECT user_id FROM calendar_feed WHERE token_hash = $1")
+            .bind(token_hash)
+            .fetch_optional(&mut db)
             .await?;
 
-        Ok(query_result.rows_affected())
+        Ok(query_result.map(|row| row.get("user_id")))
+    }
+
+    /// Moves item `id` directly before or after `target_id`, shifting the items in between by one.
+    ///
+    /// Returns `false` if either item doesn't exist or they are not in the same list type.
+    pub async fn move_list_item(
+        &self,
+        id: &str,
+        target_id: &str,
+        placement: ListItemPlacement,
+    ) -> DatabaseResult<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let moved = move_item(&mut tx, id, target_id, placement).await?;
+        if moved {
+            tx.commit().await?;
+        }
+
+        Ok(moved)
+    }
+
+    /// Applies `moves` in order, either all of them or none of them if any of the moves fails.
+    ///
+    /// The results are in the same order as `moves`.
+    pub async fn move_list_items(&self, moves: &[ListItemMove<'_>]) -> DatabaseResult<Vec<bool>> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut results = Vec::with_capacity(moves.len());
+        for list_item_move in moves {
+            let moved = move_item(
+                &mut tx,
+                list_item_move.id,
+                list_item_move.target_id,
+                list_item_move.placement,
+            )
+            .await?;
+            results.push(moved);
+        }
+
+        if results.iter().all(|moved| *moved) {
+            tx.commit().await?;
+        }
+
+        Ok(results)
+    }
+
+    /// Moves the item to the trash, from where it can be restored until it is purged.
+    pub async fn delete_list_item(&self, id: &str, actor_id: Option<&str>) -> DatabaseResult<bool> {
+        let mut db = self.get_connection().await?;
+
+        delete_item(&mut db, id, actor_id).await
+    }
+
+    /// Moves either all of the items to the trash or none of them if any of them doesn't exist.
+    ///
+    /// The results are in the same order as `ids`.
+    pub async fn delete_list_items(
+        &self,
+        ids: &[String],
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<Vec<bool>> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            results.push(delete_item(&mut tx, id, actor_id).await?);
+        }
+
+        if results.iter().all(|deleted| *deleted) {
+            tx.commit().await?;
+        }
+
+        Ok(results)
+    }
+
+    pub async fn get_deleted_list_item(&self, id: &str) -> DatabaseResult<Option<ListItem>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "SELECT {} FROM list_item WHERE id = $1 AND deleted_at IS NOT NULL",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// The items of the list type that are in the trash, most recently deleted first.
+    pub async fn get_deleted_list_items(
+        &self,
+        owner_id: &str,
+        list_type: &str,
+    ) -> DatabaseResult<Vec<ListItem>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        SELECT {}
+        FROM list_item
+        WHERE owner_id = $1 AND list_type = $2 AND deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(owner_id)
+        .bind(list_type)
+        .fetch_all(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Moves the item out of the trash, back to its previous position, and returns the restored item.
+    pub async fn restore_list_item(
+        &self,
+        id: &str,
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<Option<ListItem>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        UPDATE list_item
+        SET deleted_at = NULL, version = version + 1, updated = now(), updated_by = $2,
+            revision = nextval('list_item_revision_seq')
+        WHERE id = $1 AND deleted_at IS NOT NULL
+        RETURNING {}",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(id)
+        .bind(actor_id)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Permanently deletes items that have been in the trash for more than `retention_days`,
+    /// leaving a tombstone for each of them, along with their attachments.
+    pub async fn purge_deleted_list_items(
+        &self,
+        retention_days: u32,
+    ) -> DatabaseResult<PurgedListItems> {
+        let mut tx = self.pool.begin().await?;
+
+        // locking the items keeps them from being restored and given new attachments until they
+        // are deleted, so that the attachments returned are all that are deleted with them
+        let ids: Vec<String> = sqlx::query(
+            "
+        SELECT id FROM list_item
+        WHERE deleted_at < now() - $1 * INTERVAL '1 day'
+        FOR UPDATE",
+        )
+        .bind(retention_days as i32)
+        .map(|row: PgRow| row.get("id"))
+        .fetch_all(&mut tx)
+        .await?;
+        if ids.is_empty() {
+            return Ok(PurgedListItems {
+                purged: 0,
+                attachment_ids: Vec::new(),
+            });
+        }
+
+        let attachment_ids = sqlx::query(
+            "
+        WITH purged AS (
+            DELETE FROM list_item WHERE id = ANY($1)
+            RETURNING id, owner_id, list_type
+        ), tombstones AS (
+            INSERT INTO list_item_tombstone (id, owner_id, list_type, revision)
+            SELECT id, owner_id, list_type, nextval('list_item_revision_seq') FROM purged
+            ON CONFLICT (id) DO UPDATE
+            SET owner_id = EXCLUDED.owner_id, list_type = EXCLUDED.list_type,
+                revision = EXCLUDED.revision
+        )
+        SELECT a.id
+        FROM list_item_attachment a
+        JOIN purged ON purged.id = a.list_item_id",
+        )
+        .bind(&ids)
+        .map(|row: PgRow| row.get("id"))
+        .fetch_all(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(PurgedListItems {
+            purged: ids.len() as u64,
+            attachment_ids,
+        })
+    }
+
+    /// Changes of the items of `owner_id` with a revision after `since_revision`, oldest first.
+    ///
+    /// If `list_types` is set, only changes of those list types are included.
+    ///
+    /// Only committed revisions are returned, and never one that is followed by a revision
+    /// that is still to be committed, so that the last returned revision can be used as
+    /// `since_revision` without missing any changes.
+    pub async fn get_list_item_changes(
+        &self,
+        owner_id: &str,
+        list_types: Option<&[String]>,
+        since_revision: i64,
+        limit: i64,
+    ) -> DatabaseResult<Vec<ListItemChange>> {
+        let committed_revision = self.get_committed_revision().await?;
+
+        let mut db = self.get_connection().await?;
+
+        let list_items = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        SELECT {}
+        FROM list_item
+        WHERE owner_id = $1 AND revision > $2 AND revision <= $5
+            AND ($3::text[] IS NULL OR list_type = ANY($3))
+        ORDER BY revision
+        LIMIT $4",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(owner_id)
+        .bind(since_revision)
+        .bind(list_types)
+        .bind(limit)
+        .bind(committed_revision)
+        .fetch_all(&mut db)
+        .await?;
+
+        let tombstones = sqlx::query_as::<_, ListItemTombstone>(
+            "
+        SELECT id, owner_id, list_type, revision
+        FROM list_item_tombstone
+        WHERE owner_id = $1 AND revision > $2 AND revision <= $5
+            AND ($3::text[] IS NULL OR list_type = ANY($3))
+        ORDER BY revision
+        LIMIT $4",
+        )
+        .bind(owner_id)
+        .bind(since_revision)
+        .bind(list_types)
+        .bind(limit)
+        .bind(committed_revision)
+        .fetch_all(&mut db)
+        .await?;
+
+        // both are ordered by revision, so the first `limit` of the merged changes are complete
+        let mut changes: Vec<_> = list_items
+            .into_iter()
+            .map(|list_item| ListItemChange::Changed(Box::new(list_item)))
+            .chain(tombstones.into_iter().map(ListItemChange::Purged))
+            .collect();
+        changes.sort_by_key(ListItemChange::revision);
+        changes.truncate(limit as usize);
+
+        Ok(changes)
+    }
+
+    /// The latest revision that every revision before it has been committed or rolled back,
+    /// as far as is known without waiting for the transactions that are still running.
+    async fn get_committed_revision(&self) -> DatabaseResult<i64> {
+        let mut db = self.get_connection().await?;
+
+        // the snapshot is taken after the revision is read, so the transactions that took the
+        // revisions up to it are older than its xmax
+        let last_revision: i64 = sqlx::query("SELECT last_value FROM list_item_revision_seq")
+            .map(|row: PgRow| row.get("last_value"))
+            .fetch_one(&mut db)
+            .await?;
+        let (xmin, xmax): (i64, i64) = sqlx::query(
+            "
+        SELECT pg_snapshot_xmin(snapshot)::text::bigint AS xmin,
+            pg_snapshot_xmax(snapshot)::text::bigint AS xmax
+        FROM pg_current_snapshot() AS snapshot",
+        )
+        .map(|row: PgRow| (row.get("xmin"), row.get("xmax")))
+        .fetch_one(&mut db)
+        .await?;
+
+        let mut watermark = self
+            .revision_watermark
+            .lock()
+            .expect("revision watermark lock poisoned");
+        Ok(watermark.advance(last_revision, xmin, xmax))
+    }
+
+    /// Full-text search of the items of `owner_id` that are not in the trash, best match first.
+    ///
+    /// `query` uses the web search syntax, e.g. `milk -oat "whole grain"`.
+    pub async fn search_list_items(
+        &self,
+        owner_id: &str,
+        list_types: Option<&[String]>,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> DatabaseResult<Vec<ListItemSearchHit>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItemSearchHit>(&format!(
+            "
+        SELECT {},
+            ts_rank(search_vector, query) AS rank,
+            ts_headline('simple', item_name, query, $6) AS item_name_highlight,
+            ts_headline('simple', notes, query, $7) AS notes_highlight
+        FROM list_item, websearch_to_tsquery('simple', $1) query
+        WHERE search_vector @@ query AND owner_id = $2 AND deleted_at IS NULL
+            AND ($3::text[] IS NULL OR list_type = ANY($3))
+        ORDER BY rank DESC, created, id
+        LIMIT $4 OFFSET $5",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(query)
+        .bind(owner_id)
+        .bind(list_types)
+        .bind(limit)
+        .bind(offset)
+        .bind("StartSel=<mark>, StopSel=</mark>, HighlightAll=true")
+        .bind("StartSel=<mark>, StopSel=</mark>, MaxFragments=3, FragmentDelimiter=\" ... \"")
+        .fetch_all(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Renames the list type of `owner_id`, keeping it shared with the same users.
+    pub async fn rename_list_type(
+        &self,
+        owner_id: &str,
+        old_name: &str,
+        new_name: &str,
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let query_result = sqlx::query(
+            "
+        UPDATE list_item
+        SET list_type = $1, version = version + 1, updated = now(), updated_by = $4,
+            revision = nextval('list_item_revision_seq')
+        WHERE owner_id = $2 AND list_type = $3",
+        )
+        .bind(new_name)
+        .bind(owner_id)
+        .bind(old_name)
+        .bind(actor_id)
+        .execute(&mut tx)
+        .await?;
+
+        move_list_shares(&mut tx, owner_id, old_name, new_name).await?;
+
+        tx.commit().await?;
+
+        Ok(query_result.rows_affected())
+    }
+
+    /// Moves all items of the list type of `owner_id` to the trash and stops sharing it.
+    ///
+    /// Returns the number of items that were moved to the trash.
+    pub async fn delete_list_type(
+        &self,
+        owner_id: &str,
+        list_type: &str,
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let query_result = sqlx::query(
+            "
+        UPDATE list_item
+        SET deleted_at = now(), version = version + 1, updated = now(), updated_by = $3,
+            revision = nextval('list_item_revision_seq')
+        WHERE owner_id = $1 AND list_type = $2 AND deleted_at IS NULL",
+        )
+        .bind(owner_id)
+        .bind(list_type)
+        .bind(actor_id)
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query("DELETE FROM list_share WHERE owner_id = $1 AND list_type = $2")
+            .bind(owner_id)
+            .bind(list_type)
+            .execute(&mut tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(query_result.rows_affected())
+    }
+
+    /// Moves all items of `source` to the end of `target`, both owned by `owner_id`.
+    ///
+    /// `target` keeps its shares, and the shares of `source` are removed rather than moved,
+    /// since they would give its users access to the items that already were in `target`.
+    pub async fn merge_list_types(
+        &self,
+        owner_id: &str,
+        source: &str,
+        target: &str,
+        strategy: DuplicateItemStrategy,
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<ListTypeMerge> {
+        let mut tx = self.pool.begin().await?;
+
+        // serializes the merge with moves and other merges of either list type, so that the
+        // positions the items are given in `target` stay distinct, in a fixed order so that
+        // merging the other way concurrently doesn't deadlock
+        let mut list_types = [source, target];
+        list_types.sort_unstable();
+        for list_type in &list_types {
+            lock_list_type(&mut tx, Some(owner_id), list_type).await?;
+        }
+
+        let discarded = match strategy.trashed_and_kept(source, target) {
+            Some((from, other)) => {
+                trash_duplicate_items(&mut tx, owner_id, from, other, actor_id).await?
+            }
+            None => 0,
+        };
+
+        let moved = sqlx::query(
+            "
+        WITH moved AS (
+            UPDATE list_item
+            SET list_type = $3, version = version + 1, updated = now(), updated_by = $4,
+                revision = nextval('list_item_revision_seq'),
+                position = position + (
+                    SELECT COALESCE(MAX(position), 0)
+                    FROM list_item
+                    WHERE owner_id = $1 AND list_type = $3
+                )
+            WHERE owner_id = $1 AND list_type = $2
+            RETURNING deleted_at
+        )
+        SELECT COUNT(*) FILTER (WHERE deleted_at IS NULL) AS moved FROM moved",
+        )
+        .bind(owner_id)
+        .bind(source)
+        .bind(target)
+        .bind(actor_id)
+        .map(|row: PgRow| {
+            let moved: i64 = row.get("moved");
+            moved as u64
+        })
+        .fetch_one(&mut tx)
+        .await?;
+
+        sqlx::query("DELETE FROM list_share WHERE owner_id = $1 AND list_type = $2")
+            .bind(owner_id)
+            .bind(source)
+            .execute(&mut tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(ListTypeMerge { moved, discarded })
+    }
+
+    /// Saves the items of `list_type` of `list_owner_id` as the template `name` of `owner_id`,
+    /// replacing the items of an existing template with that name if `overwrite` is set.
+    ///
+    /// Returns `None` if the template already exists and `overwrite` isn't set.
+    pub async fn save_list_template(
+        &self,
+        owner_id: &str,
+        name: &str,
+        list_owner_id: &str,
+        list_type: &str,
+        overwrite: bool,
+    ) -> DatabaseResult<Option<(ListTemplate, Vec<ListTemplateItem>)>> {
+        let mut tx = self.pool.begin().await?;
+
+        let on_conflict = if overwrite {
+            "DO UPDATE SET updated = now()"
+        } else {
+            "DO NOTHING"
+        };
+        let template = sqlx::query_as::<_, ListTemplate>(&format!(
+            "
+        INSERT INTO list_template (owner_id, name) VALUES ($1, $2)
+        ON CONFLICT (owner_id, name) {}
+        RETURNING owner_id, name, created, updated",
+            on_conflict
+        ))
+        .bind(owner_id)
+        .bind(name)
+        .fetch_optional(&mut tx)
+        .await?;
+        let template = match template {
+            Some(template) => template,
+            None => return Ok(None),
+        };
+
+        sqlx::query("DELETE FROM list_template_item WHERE owner_id = $1 AND template_name = $2")
+            .bind(owner_id)
+            .bind(name)
+            .execute(&mut tx)
+            .await?;
+
+        let mut items = sqlx::query_as::<_, ListTemplateItem>(
+            "
+        INSERT INTO list_template_item (
+            owner_id, template_name, position, item_name, quantity, unit, notes
+        )
+        SELECT $1, $2, row_number() OVER (ORDER BY position, created, id),
+            item_name, quantity, unit, notes
+        FROM list_item
+        WHERE owner_id = $3 AND list_type = $4 AND deleted_at IS NULL
+        RETURNING template_name, position, item_name, quantity, unit, notes",
+        )
+        .bind(owner_id)
+        .bind(name)
+        .bind(list_owner_id)
+        .bind(list_type)
+        .fetch_all(&mut tx)
+        .await?;
+        items.sort_by_key(|item| item.position);
+
+        tx.commit().await?;
+
+        Ok(Some((template, items)))
+    }
+
+    /// The templates of `owner_id`, by name.
+    pub async fn get_list_templates(&self, owner_id: &str) -> DatabaseResult<Vec<ListTemplate>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListTemplate>(
+            "
+        SELECT owner_id, name, created, updated
+        FROM list_template
+        WHERE owner_id = $1
+        ORDER BY name",
+        )
+        .bind(owner_id)
+        .fetch_all(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// The items of all templates of `owner_id`, ordered by template name and position.
+    pub async fn get_list_template_items(
+        &self,
+        owner_id: &str,
+    ) -> DatabaseResult<Vec<ListTemplateItem>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListTemplateItem>(
+            "
+        SELECT template_name, position, item_name, quantity, unit, notes
+        FROM list_template_item
+        WHERE owner_id = $1
+        ORDER BY template_name, position",
+        )
+        .bind(owner_id)
+        .fetch_all(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Creates `list_type` of `owner_id` with the items of the template `name`, in one transaction.
+    ///
+    /// Items in the trash don't count as existing items of the list type.
+    pub async fn instantiate_list_template(
+        &self,
+        owner_id: &str,
+        name: &str,
+        list_type: &str,
+        actor_id: Option<&str>,
+    ) -> DatabaseResult<TemplateInstantiation> {
+        let mut tx = self.pool.begin().await?;
+
+        // keeps the template from being overwritten while it is copied
+        let template_exists = sqlx::query(
+            "SELECT name FROM list_template WHERE owner_id = $1 AND name = $2 FOR SHARE",
+        )
+        .bind(owner_id)
+        .bind(name)
+        .fetch_optional(&mut tx)
+        .await?
+        .is_some();
+        if !template_exists {
+            return Ok(TemplateInstantiation::MissingTemplate);
+        }
+
+        let list_type_exists = sqlx::query(
+            "SELECT id FROM list_item WHERE owner_id = $1 AND list_type = $2 AND deleted_at IS NULL LIMIT 1",
+        )
+        .bind(owner_id)
+        .bind(list_type)
+        .fetch_optional(&mut tx)
+        .await?
+        .is_some();
+        if list_type_exists {
+            return Ok(TemplateInstantiation::ListTypeExists);
+        }
+
+        sqlx::query(
+            "
+        INSERT INTO list_item (
+            id, owner_id, list_type, item_name, quantity, unit, notes, completed, position,
+            revision, created_revision, updated_by
+        )
+        SELECT gen_random_uuid()::text, $1, $3, item_name, quantity, unit, notes, FALSE,
+            position, revision, revision, $4
+        FROM (
+            SELECT item_name, quantity, unit, notes, position,
+                nextval('list_item_revision_seq') AS revision
+            FROM list_template_item
+            WHERE owner_id = $1 AND template_name = $2
+            ORDER BY position
+        ) template_item",
+        )
+        .bind(owner_id)
+        .bind(name)
+        .bind(list_type)
+        .bind(actor_id)
+        .execute(&mut tx)
+        .await?;
+
+        let list_items = sqlx::query_as::<_, ListItem>(&format!(
+            "
+        SELECT {}
+        FROM list_item
+        WHERE owner_id = $1 AND list_type = $2 AND deleted_at IS NULL
+        ORDER BY position, created, id",
+            LIST_ITEM_COLUMNS
+        ))
+        .bind(owner_id)
+        .bind(list_type)
+        .fetch_all(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(TemplateInstantiation::Created(list_items))
+    }
+
+    /// Returns `None` if the list item doesn't exist or is in the trash.
+    pub async fn insert_list_item_attachment(
+        &self,
+        attachment: &NewListItemAttachment<'_>,
+    ) -> DatabaseResult<Option<ListItemAttachment>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItemAttachment>(
+            "
+        INSERT INTO list_item_attachment (id, list_item_id, file_name, content_type, size_bytes, uploaded_by)
+        SELECT $1, id, $3, $4, $5, $6
+        FROM list_item
+        WHERE id = $2 AND deleted_at IS NULL
+        RETURNING id, list_item_id, file_name, content_type, size_bytes, uploaded_by, created",
+        )
+        .bind(attachment.id)
+        .bind(attachment.list_item_id)
+        .bind(attachment.file_name)
+        .bind(attachment.content_type)
+        .bind(attachment.size_bytes)
+        .bind(attachment.uploaded_by)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Returns `None` if the attachment doesn't exist or its list item is in the trash.
+    pub async fn get_list_item_attachment(
+        &self,
+        id: &str,
+    ) -> DatabaseResult<Option<ListItemAttachment>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItemAttachment>(
+            "
+        SELECT attachment.id, attachment.list_item_id, attachment.file_name,
+            attachment.content_type, attachment.size_bytes, attachment.uploaded_by,
+            attachment.created
+        FROM list_item_attachment attachment
+        JOIN list_item ON list_item.id = attachment.list_item_id
+        WHERE attachment.id = $1 AND list_item.deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// The attachments of a list item, oldest first.
+    pub async fn get_list_item_attachments(
+        &self,
+        list_item_id: &str,
+    ) -> DatabaseResult<Vec<ListItemAttachment>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, ListItemAttachment>(
+            "
+        SELECT id, list_item_id, file_name, content_type, size_bytes, uploaded_by, created
+        FROM list_item_attachment
+        WHERE list_item_id = $1
+        ORDER BY created, id",
+        )
+        .bind(list_item_id)
+        .fetch_all(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    pub async fn delete_list_item_attachment(&self, id: &str) -> DatabaseResult<bool> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query(
+            "
+        DELETE FROM list_item_attachment
+        WHERE id = $1",
+        )
+        .bind(id)
+        .execute(&mut db)
+        .await?;
+
+        Ok(query_result.rows_affected() == 1)
+    }
+}
+
+async fn insert_item(
+    conn: &mut PgConnection,
+    owner_id: &str,
+    list_type: &str,
+    item: &NewListItem<'_>,
+    actor_id: Option<&str>,
+) -> DatabaseResult<InsertionResult> {
+    // new items are put last, concurrent inserts can end up with the same position,
+    // which is resolved by also ordering on creation time
+    let query_result = sqlx::query(
+        "
+        WITH next AS (SELECT nextval('list_item_revision_seq') AS revision)
+        INSERT INTO list_item (
+            id, owner_id, list_type, item_name, quantity, unit, notes, due_date,
+            completed, completed_at, position, revision, created_revision, updated_by
+        )
+        SELECT $1, $2, $3, $4, $5, $6, $7, $8::date, $9, CASE WHEN $9 THEN now() END, (
+            SELECT COALESCE(MAX(position), 0) + 1
+            FROM list_item
+            WHERE owner_id = $2 AND list_type = $3
+        ), next.revision, next.revision, $10
+        FROM next
+        ON CONFLICT (id) DO NOTHING",
+    )
+    .bind(item.id)
+    .bind(owner_id)
+    .bind(list_type)
+    .bind(item.item_name)
+    .bind(item.details.quantity)
+    .bind(item.details.unit)
+    .bind(item.details.notes)
+    .bind(item.details.due_date)
+    .bind(item.completed)
+    .bind(actor_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(InsertionResult::from_changed_rows(
+        query_result.rows_affected(),
+    ))
+}
+
+/// Returns `false` if either item doesn't exist or they are not in the same list type.
+async fn move_item(
+    conn: &mut PgConnection,
+    id: &str,
+    target_id: &str,
+    placement: ListItemPlacement,
+) -> DatabaseResult<bool> {
+    let list = sqlx::query(
+        "SELECT owner_id, list_type FROM list_item WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .map(|row: PgRow| {
+        let owner_id: Option<String> = row.get("owner_id");
+        let list_type: String = row.get("list_type");
+        (owner_id, list_type)
+    })
+    .fetch_optional(&mut *conn)
+    .await?;
+    let (owner_id, list_type) = match list {
+        Some(list) => list,
+        None => return Ok(false),
+    };
+
+    if id == target_id {
+        return Ok(false);
+    }
+
+    // serializes concurrent moves within the list type, so that each move sees the order left
+    // by the previous one, without locking the items against other changes
+    lock_list_type(conn, owner_id.as_deref(), &list_type).await?;
+
+    // concurrent inserts can give items the same position, shifting needs them to be distinct
+    sqlx::query(
+        "
+        WITH ordered AS (
+            SELECT id, position,
+                row_number() OVER (ORDER BY position, created, id) AS new_position,
+                count(*) OVER (PARTITION BY position) AS same_position
+            FROM list_item
+            WHERE owner_id = $1 AND list_type = $2 AND deleted_at IS NULL
+        )
+        UPDATE list_item SET position = ordered.new_position
+        FROM ordered
+        WHERE list_item.id = ordered.id AND ordered.position <> ordered.new_position
+            AND EXISTS (SELECT 1 FROM ordered WHERE same_position > 1)",
+    )
+    .bind(&owner_id)
+    .bind(&list_type)
+    .execute(&mut *conn)
+    .await?;
+
+    let positions: HashMap<String, i64> = sqlx::query(
+        "
+        SELECT id, position
+        FROM list_item
+        WHERE id IN ($3, $4) AND owner_id = $1 AND list_type = $2 AND deleted_at IS NULL",
+    )
+    .bind(&owner_id)
+    .bind(&list_type)
+    .bind(id)
+    .bind(target_id)
+    .map(|row: PgRow| (row.get("id"), row.get("position")))
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .collect();
+    let (position, target_position) = match (positions.get(id), positions.get(target_id)) {
+        (Some(position), Some(target_position)) => (*position, *target_position),
+        _ => return Ok(false),
+    };
+
+    let shift = match position_shift(position, target_position, placement) {
+        Some(shift) => shift,
+        None => return Ok(true),
+    };
+
+    // the other items keep their revisions, only the moved item is reported as changed
+    sqlx::query(
+        "
+        UPDATE list_item SET position = position + $3
+        WHERE owner_id = $1 AND list_type = $2 AND deleted_at IS NULL
+            AND position BETWEEN $4 AND $5",
+    )
+    .bind(&owner_id)
+    .bind(&list_type)
+    .bind(shift.step)
+    .bind(shift.first)
+    .bind(shift.last)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        "
+        UPDATE list_item SET position = $2, revision = nextval('list_item_revision_seq')
+        WHERE id = $1",
+    )
+    .bind(id)
+    .bind(shift.position)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(true)
+}
+
+/// Takes the lock on the order of the items of the list type until the end of the transaction.
+async fn lock_list_type(
+    conn: &mut PgConnection,
+    owner_id: Option<&str>,
+    list_type: &str,
+) -> DatabaseResult<()> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended(concat_ws(':', $1, $2), 0))")
+        .bind(owner_id)
+        .bind(list_type)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// How the positions of a list type change when an item is moved.
+#[derive(Debug, PartialEq, Eq)]
+struct PositionShift {
+    /// The items with positions in `first..=last` move one step towards the old position.
+    first: i64,
+    last: i64,
+    step: i64,
+    /// The new position of the moved item.
+    position: i64,
+}
+
+/// The shift that puts the item at `position` before or after the item at `target_position`,
+/// `None` if it already is there.
+///
+/// The positions of the items in the list type must be distinct.
+fn position_shift(
+    position: i64,
+    target_position: i64,
+    placement: ListItemPlacement,
+) -> Option<PositionShift> {
+    let new_position = match placement {
+        ListItemPlacement::Before if target_position > position => target_position - 1,
+        ListItemPlacement::Before => target_position,
+        ListItemPlacement::After if target_position > position => target_position,
+        ListItemPlacement::After => target_position + 1,
+    };
+
+    match new_position.cmp(&position) {
+        Ordering::Equal => None,
+        Ordering::Greater => Some(PositionShift {
+            first: position + 1,
+            last: new_position,
+            step: -1,
+            position: new_position,
+        }),
+        Ordering::Less => Some(PositionShift {
+            first: new_position,
+            last: position - 1,
+            step: 1,
+            position: new_position,
+        }),
+    }
+}
+
+async fn delete_item(
+    conn: &mut PgConnection,
+    id: &str,
+    actor_id: Option<&str>,
+) -> DatabaseResult<bool> {
+    let query_result = sqlx::query(
+        "
+        UPDATE list_item
+        SET deleted_at = now(), version = version + 1, updated = now(), updated_by = $2,
+            revision = nextval('list_item_revision_seq')
+        WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .bind(actor_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(query_result.rows_affected() == 1)
+}
+
+/// Moves the items of `from` that have the same name as an item of `other` to the trash.
+async fn trash_duplicate_items(
+    conn: &mut PgConnection,
+    owner_id: &str,
+    from: &str,
+    other: &str,
+    actor_id: Option<&str>,
+) -> DatabaseResult<u64> {
+    let query_result = sqlx::query(
+        "
+        UPDATE list_item duplicate
+        SET deleted_at = now(), version = version + 1, updated = now(), updated_by = $4,
+            revision = nextval('list_item_revision_seq')
+        WHERE owner_id = $1 AND list_type = $2 AND deleted_at IS NULL AND EXISTS (
+            SELECT 1 FROM list_item kept
+            WHERE kept.owner_id = $1 AND kept.list_type = $3 AND kept.deleted_at IS NULL
+            AND lower(kept.item_name) = lower(duplicate.item_name)
+        )",
+    )
+    .bind(owner_id)
+    .bind(from)
+    .bind(other)
+    .bind(actor_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(query_result.rows_affected())
+}
+
+/// Users that already had access to `new_name` keep that permission.
+async fn move_list_shares(
+    conn: &mut PgConnection,
+    owner_id: &str,
+    old_name: &str,
+    new_name: &str,
+) -> DatabaseResult<()> {
+    sqlx::query(
+        "
+        UPDATE list_share renamed SET list_type = $1
+        WHERE owner_id = $2 AND list_type = $3 AND NOT EXISTS (
+            SELECT 1 FROM list_share existing
+            WHERE existing.owner_id = $2 AND existing.list_type = $1 AND existing.user_id = renamed.user_id
+        )",
+    )
+    .bind(new_name)
+    .bind(owner_id)
+    .bind(old_name)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("DELETE FROM list_share WHERE owner_id = $1 AND list_type = $2")
+        .bind(owner_id)
+        .bind(old_name)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_database, unique_id};
+    use std::{sync::Arc, time::Duration};
+
+    fn shift(first: i64, last: i64, step: i64, position: i64) -> Option<PositionShift> {
+        Some(PositionShift {
+            first,
+            last,
+            step,
+            position,
+        })
+    }
+
+    #[test]
+    fn moving_down_shifts_the_items_in_between_up() {
+        // 1 [2] 3 4 5
+        assert_eq!(
+            position_shift(2, 4, ListItemPlacement::After),
+            shift(3, 4, -1, 4)
+        );
+        assert_eq!(
+            position_shift(2, 4, ListItemPlacement::Before),
+            shift(3, 3, -1, 3)
+        );
+        assert_eq!(position_shift(2, 3, ListItemPlacement::Before), None);
+    }
+
+    #[test]
+    fn moving_up_shifts_the_items_in_between_down() {
+        // 1 2 3 [4] 5
+        assert_eq!(
+            position_shift(4, 1, ListItemPlacement::Before),
+            shift(1, 3, 1, 1)
+        );
+        assert_eq!(
+            position_shift(4, 1, ListItemPlacement::After),
+            shift(2, 3, 1, 2)
+        );
+        assert_eq!(position_shift(4, 3, ListItemPlacement::After), None);
+    }
+
+    #[test]
+    fn duplicates_are_trashed_from_the_list_type_that_isnt_kept() {
+        assert_eq!(
+            DuplicateItemStrategy::KeepBoth.trashed_and_kept("source", "target"),
+            None
+        );
+        assert_eq!(
+            DuplicateItemStrategy::KeepTarget.trashed_and_kept("source", "target"),
+            Some(("source", "target"))
+        );
+        assert_eq!(
+            DuplicateItemStrategy::KeepSource.trashed_and_kept("source", "target"),
+            Some(("target", "source"))
+        );
+    }
+
+    #[test]
+    fn notifications_of_the_trigger() {
+        // as built by `notify_list_item_change()`
+        let payload = r#"{"id" : "b5ab3e2f-5d3c-4b8e-9a1e-2f3c4d5e6f70", "owner_id" : "user", "list_type" : "groceries", "revision" : 42}"#;
+        assert_eq!(
+            serde_json::from_str::<ListItemNotification>(payload).unwrap(),
+            ListItemNotification {
+                id: "b5ab3e2f-5d3c-4b8e-9a1e-2f3c4d5e6f70".to_owned(),
+                owner_id: Some("user".to_owned()),
+                list_type: "groceries".to_owned(),
+                revision: 42,
+            }
+        );
+
+        let payload =
+            r#"{"id" : "1", "owner_id" : null, "list_type" : "groceries", "revision" : 1}"#;
+        let notification = serde_json::from_str::<ListItemNotification>(payload).unwrap();
+        assert_eq!(notification.owner_id, None);
+
+        assert!(serde_json::from_str::<ListItemNotification>(r#"{"id" : "1"}"#).is_err());
+    }
+
+    #[test]
+    fn positions_with_gaps() {
+        // items at 1, [3], 7 and 9
+        assert_eq!(
+            position_shift(3, 9, ListItemPlacement::After),
+            shift(4, 9, -1, 9)
+        );
+        assert_eq!(
+            position_shift(9, 3, ListItemPlacement::Before),
+            shift(3, 8, 1, 3)
+        );
+    }
+
+    /// Inserts items with `names` at the end of the list type, with ids from `unique_id`.
+    async fn insert_named_items(
+        db: &Database<ListItem>,
+        owner_id: &str,
+        list_type: &str,
+        names: &[&str],
+    ) -> Vec<String> {
+        let ids: Vec<_> = names.iter().map(|name| unique_id(name)).collect();
+        let items: Vec<_> = ids
+            .iter()
+            .zip(names)
+            .map(|(id, name)| NewListItem {
+                id,
+                item_name: name,
+                details: ListItemDetails::default(),
+                completed: false,
+            })
+            .collect();
+        let results = db
+            .insert_list_items(owner_id, list_type, &items, None)
+            .await
+            .unwrap();
+        assert!(results.iter().all(|r| *r == InsertionResult::Inserted));
+        ids
+    }
+
+    async fn item_names(db: &Database<ListItem>, owner_id: &str, list_type: &str) -> Vec<String> {
+        db.get_list_items(owner_id, list_type)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|list_item| list_item.item_name)
+            .collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_updates_of_the_current_version_are_applied() {
+        let db = match test_database::<ListItem>().await {
+            Some(db) => Arc::new(db),
+            None => return,
+        };
+        let owner_id = unique_id("owner");
+        let id = insert_named_items(&db, &owner_id, "groceries", &["milk"])
+            .await
+            .remove(0);
+
+        let updates: Vec<_> = (0..5)
+            .map(|i| {
+                let db = db.clone();
+                let id = id.clone();
+                tokio::spawn(async move {
+                    let details = ListItemDetails::default();
+                    let item_name = format!("milk {}", i);
+                    db.update_list_item(&id, 1, &item_name, details, None).await
+                })
+            })
+            .collect();
+        let mut updated = Vec::new();
+        for update in updates {
+            updated.extend(update.await.unwrap().unwrap());
+        }
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].version, 2);
+
+        // based on the version that was just replaced
+        let stale = db
+            .update_list_item(&id, 1, "oat milk", ListItemDetails::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(stale, None);
+        let current = db.get_list_item(&id).await.unwrap().unwrap();
+        assert_eq!(current.item_name, updated[0].item_name);
+
+        let details = ListItemDetails {
+            quantity: Some(2.0),
+            unit: Some("l"),
+            ..ListItemDetails::default()
+        };
+        let update = db
+            .update_list_item(&id, 2, "oat milk", details, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.version, 3);
+        assert_eq!(update.quantity, Some(2.0));
+
+        assert!(db.delete_list_item(&id, None).await.unwrap());
+        let deleted = db
+            .update_list_item(&id, 4, "milk", ListItemDetails::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(deleted, None);
+    }
+
+    #[tokio::test]
+    async fn bulk_changes_are_rolled_back_if_any_of_them_fails() {
+        let db = match test_database::<ListItem>().await {
+            Some(db) => db,
+            None => return,
+        };
+        let owner_id = unique_id("owner");
+        let ids = insert_named_items(&db, &owner_id, "groceries", &["milk", "eggs", "bread"]).await;
+        let (milk, eggs, bread) = (&ids[0], &ids[1], &ids[2]);
+        let missing = unique_id("missing");
+
+        let cheese = unique_id("cheese");
+        let items = [
+            NewListItem {
+                id: &cheese,
+                item_name: "cheese",
+                details: ListItemDetails::default(),
+                completed: false,
+            },
+            NewListItem {
+                id: milk,
+                item_name: "milk",
+                details: ListItemDetails::default(),
+                completed: false,
+            },
+        ];
+        let inserted = db
+            .insert_list_items(&owner_id, "groceries", &items, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            inserted,
+            [InsertionResult::Inserted, InsertionResult::AlreadyExists]
+        );
+
+        let deleted = db
+            .delete_list_items(&[milk.clone(), missing.clone()], None)
+            .await
+            .unwrap();
+        assert_eq!(deleted, [true, false]);
+
+        let moves = [
+            ListItemMove {
+                id: bread,
+                target_id: milk,
+                placement: ListItemPlacement::Before,
+            },
+            ListItemMove {
+                id: eggs,
+                target_id: &missing,
+                placement: ListItemPlacement::Before,
+            },
+        ];
+        assert_eq!(db.move_list_items(&moves).await.unwrap(), [true, false]);
+
+        assert_eq!(
+            item_names(&db, &owner_id, "groceries").await,
+            vec!["milk", "eggs", "bread"]
+        );
+
+        let moves = [
+            ListItemMove {
+                id: bread,
+                target_id: milk,
+                placement: ListItemPlacement::Before,
+            },
+            ListItemMove {
+                id: eggs,
+                target_id: bread,
+                placement: ListItemPlacement::After,
+            },
+        ];
+        assert_eq!(db.move_list_items(&moves).await.unwrap(), [true, true]);
+        assert_eq!(
+            item_names(&db, &owner_id, "groceries").await,
+            vec!["bread", "eggs", "milk"]
+        );
+
+        let deleted = db
+            .delete_list_items(&[milk.clone(), eggs.clone()], None)
+            .await
+            .unwrap();
+        assert_eq!(deleted, [true, true]);
+        assert_eq!(item_names(&db, &owner_id, "groceries").await, vec!["bread"]);
+    }
+
+    #[tokio::test]
+    async fn search_ranks_names_above_notes_and_highlights_the_matches() {
+        let db = match test_database::<ListItem>().await {
+            Some(db) => db,
+            None => return,
+        };
+        let owner_id = unique_id("owner");
+        let ids = insert_named_items(&db, &owner_id, "groceries", &["oat milk", "bread"]).await;
+        insert_named_items(&db, &owner_id, "snacks", &["milk chocolate"]).await;
+        let trashed = insert_named_items(&db, &owner_id, "groceries", &["whole milk"]).await;
+        assert!(db.delete_list_item(&trashed[0], None).await.unwrap());
+        let details = ListItemDetails {
+            notes: Some("the one that goes with milk"),
+            ..ListItemDetails::default()
+        };
+        db.update_list_item(&ids[1], 1, "bread", details, None)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let search = |query: &'static str, list_types: Option<Vec<String>>, limit, offset| {
+            let db = &db;
+            let owner_id = &owner_id;
+            async move {
+                db.search_list_items(owner_id, list_types.as_deref(), query, limit, offset)
+                    .await
+                    .unwrap()
+            }
+        };
+        let names = |hits: &[ListItemSearchHit]| -> Vec<String> {
+            hits.iter()
+                .map(|hit| hit.list_item.item_name.clone())
+                .collect()
+        };
+
+        let hits = search("milk", None, 10, 0).await;
+        assert_eq!(names(&hits), ["oat milk", "milk chocolate", "bread"]);
+        assert!(hits[1].rank > hits[2].rank);
+        assert_eq!(hits[0].item_name_highlight, "oat <mark>milk</mark>");
+        assert_eq!(hits[0].notes_highlight, None);
+        assert_eq!(hits[2].item_name_highlight, "bread");
+        assert_eq!(
+            hits[2].notes_highlight.as_deref(),
+            Some("that goes with <mark>milk</mark>")
+        );
+
+        let hits = search("milk", None, 1, 1).await;
+        assert_eq!(names(&hits), ["milk chocolate"]);
+        let hits = search("milk", Some(vec!["snacks".to_owned()]), 10, 0).await;
+        assert_eq!(names(&hits), ["milk chocolate"]);
+        let mut excluded = names(&search("milk -chocolate", None, 10, 0).await);
+        excluded.sort();
+        assert_eq!(excluded, ["bread", "oat milk"]);
+        let hits = search("\"goes with\" milk", None, 10, 0).await;
+        assert_eq!(names(&hits), ["bread"]);
+    }
+
+    #[tokio::test]
+    async fn every_change_is_recorded_and_can_be_reverted() {
+        let db = match test_database::<ListItem>().await {
+            Some(db) => db,
+            None => return,
+        };
+        let owner_id = unique_id("owner");
+        let editor_id = unique_id("editor");
+        let id = unique_id("milk");
+        db.insert_list_item(
+            &id,
+            &owner_id,
+            "groceries",
+            "milk",
+            ListItemDetails::default(),
+            Some(&owner_id),
+        )
+        .await
+        .unwrap();
+        let details = ListItemDetails {
+            quantity: Some(2.0),
+            due_date: Some("2022-03-01"),
+            ..ListItemDetails::default()
+        };
+        db.update_list_item(&id, 1, "oat milk", details, Some(&editor_id))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let history = db.get_list_item_history(&id).await.unwrap();
+        let versions: Vec<_> = history
+            .iter()
+            .map(|version| {
+                (
+                    version.version,
+                    version.item_name.as_str(),
+                    version.actor_id.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            versions,
+            [
+                (2, "oat milk", Some(editor_id.as_str())),
+                (1, "milk", Some(owner_id.as_str())),
+            ]
+        );
+        assert_eq!(history[0].quantity, Some(2.0));
+        assert_eq!(history[0].due_date.as_deref(), Some("2022-03-01"));
+        assert_eq!(history[1].due_date, None);
+
+        let reverted = db
+            .revert_list_item(&id, 1, Some(&owner_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reverted.version, 3);
+        assert_eq!(reverted.item_name, "milk");
+        assert_eq!(reverted.quantity, None);
+        assert_eq!(reverted.due_date, None);
+
+        let history = db.get_list_item_history(&id).await.unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].item_name, "milk");
+        assert_eq!(history[0].actor_id.as_deref(), Some(owner_id.as_str()));
+
+        assert_eq!(db.revert_list_item(&id, 9, None).await.unwrap(), None);
+        assert!(db.delete_list_item(&id, None).await.unwrap());
+        assert_eq!(db.revert_list_item(&id, 2, None).await.unwrap(), None);
+        let history = db.get_list_item_history(&id).await.unwrap();
+        assert_eq!(history.len(), 4);
+        assert!(history[0].deleted_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn templates_are_copies_of_a_list_type_that_new_list_types_start_from() {
+        let db = match test_database::<ListItem>().await {
+            Some(db) => db,
+            None => return,
+        };
+        let owner_id = unique_id("owner");
+        let ids = insert_named_items(&db, &owner_id, "groceries", &["milk", "eggs", "bread"]).await;
+        let moved = db
+            .move_list_item(&ids[2], &ids[0], ListItemPlacement::Before)
+            .await
+            .unwrap();
+        assert!(moved);
+        assert!(db.delete_list_item(&ids[1], None).await.unwrap());
+
+        let (template, items) = db
+            .save_list_template(&owner_id, "weekly", &owner_id, "groceries", false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(template.name, "weekly");
+        let names: Vec<_> = items.iter().map(|item| item.item_name.as_str()).collect();
+        assert_eq!(names, ["bread", "milk"]);
+        assert_eq!(
+            items.iter().map(|item| item.position).collect::<Vec<_>>(),
+            [1, 2]
+        );
+
+        // an existing template is only replaced with `overwrite`
+        insert_named_items(&db, &owner_id, "party", &["chips"]).await;
+        let saved = db
+            .save_list_template(&owner_id, "weekly", &owner_id, "party", false)
+            .await
+            .unwrap();
+        assert_eq!(saved, None);
+        db.save_list_template(&owner_id, "party", &owner_id, "party", false)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let templates = db.get_list_templates(&owner_id).await.unwrap();
+        let names: Vec<_> = templates.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["party", "weekly"]);
+        let items = db.get_list_template_items(&owner_id).await.unwrap();
+        let items: Vec<_> = items
+            .iter()
+            .map(|item| (item.template_name.as_str(), item.item_name.as_str()))
+            .collect();
+        assert_eq!(
+            items,
+            [("party", "chips"), ("weekly", "bread"), ("weekly", "milk")]
+        );
+
+        let created = db
+            .instantiate_list_template(&owner_id, "weekly", "next week", None)
+            .await
+            .unwrap();
+        let list_items = match created {
+            TemplateInstantiation::Created(list_items) => list_items,
+            other => panic!("expected the list type to be created, got {:?}", other),
+        };
+        let names: Vec<_> = list_items
+            .iter()
+            .map(|item| item.item_name.as_str())
+            .collect();
+        assert_eq!(names, ["bread", "milk"]);
+        assert!(list_items.iter().all(|item| !item.completed));
+        assert_eq!(
+            item_names(&db, &owner_id, "next week").await,
+            vec!["bread", "milk"]
+        );
+
+        let instantiation = db
+            .instantiate_list_template(&owner_id, "weekly", "next week", None)
+            .await
+            .unwrap();
+        assert_eq!(instantiation, TemplateInstantiation::ListTypeExists);
+        let instantiation = db
+            .instantiate_list_template(&owner_id, "monthly", "next month", None)
+            .await
+            .unwrap();
+        assert_eq!(instantiation, TemplateInstantiation::MissingTemplate);
+        assert_eq!(
+            item_names(&db, &owner_id, "next month").await,
+            Vec::<String>::new()
+        );
+    }
+
+    #[tokio::test]
+    async fn merging_moves_the_items_but_not_the_shares_of_the_source() {
+        let db = match test_database::<ListItem>().await {
+            Some(db) => db,
+            None => return,
+        };
+        let users = test_database::<crate::User>().await.unwrap();
+        let owner_id = unique_id("owner");
+        let source_user_id = unique_id("user");
+        let target_user_id = unique_id("user");
+        for user_id in &[&source_user_id, &target_user_id] {
+            users
+                .insert_user(user_id, user_id, "password")
+                .await
+                .unwrap();
+        }
+
+        insert_named_items(&db, &owner_id, "pantry", &["bread", "Milk"]).await;
+        insert_named_items(&db, &owner_id, "groceries", &["milk", "eggs"]).await;
+        assert!(db
+            .share_list_type(&owner_id, "pantry", &source_user_id, "write")
+            .await
+            .unwrap());
+        assert!(db
+            .share_list_type(&owner_id, "groceries", &target_user_id, "read")
+            .await
+            .unwrap());
+
+        let merge = db
+            .merge_list_types(
+                &owner_id,
+                "pantry",
+                "groceries",
+                DuplicateItemStrategy::KeepTarget,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            merge,
+            ListTypeMerge {
+                moved: 1,
+                discarded: 1
+            }
+        );
+        assert_eq!(
+            item_names(&db, &owner_id, "groceries").await,
+            vec!["milk", "eggs", "bread"]
+        );
+        assert_eq!(
+            item_names(&db, &owner_id, "pantry").await,
+            Vec::<String>::new()
+        );
+
+        for list_type in &["pantry", "groceries"] {
+            let share = db
+                .get_list_share(&owner_id, list_type, &source_user_id)
+                .await
+                .unwrap();
+            assert_eq!(share, None);
+        }
+        let share = db
+            .get_list_share(&owner_id, "groceries", &target_user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(share.permission, "read");
+    }
+
+    #[tokio::test]
+    async fn purging_returns_the_attachments_of_the_purged_items() {
+        let db = match test_database::<ListItem>().await {
+            Some(db) => db,
+            None => return,
+        };
+        let owner_id = unique_id("owner");
+        let ids = insert_named_items(&db, &owner_id, "groceries", &["milk", "eggs"]).await;
+        let attachment_ids: Vec<_> = ids.iter().map(|_| unique_id("attachment")).collect();
+        for (id, attachment_id) in ids.iter().zip(&attachment_ids) {
+            let attachment = NewListItemAttachment {
+                id: attachment_id,
+                list_item_id: id,
+                file_name: "receipt.pdf",
+                content_type: "application/pdf",
+                size_bytes: 1,
+                uploaded_by: None,
+            };
+            let inserted = db.insert_list_item_attachment(&attachment).await.unwrap();
+            assert!(inserted.is_some());
+        }
+        assert!(db.delete_list_item(&ids[0], None).await.unwrap());
+        assert!(db.delete_list_item(&ids[1], None).await.unwrap());
+        sqlx::query("UPDATE list_item SET deleted_at = now() - INTERVAL '2 days' WHERE id = $1")
+            .bind(&ids[0])
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let purged = db.purge_deleted_list_items(1).await.unwrap();
+        assert!(purged.purged >= 1);
+        assert!(purged.attachment_ids.contains(&attachment_ids[0]));
+        assert!(!purged.attachment_ids.contains(&attachment_ids[1]));
+
+        assert_eq!(db.get_deleted_list_item(&ids[0]).await.unwrap(), None);
+        assert!(db.restore_list_item(&ids[1], None).await.unwrap().is_some());
+        let attachment = db
+            .get_list_item_attachment(&attachment_ids[1])
+            .await
+            .unwrap();
+        assert!(attachment.is_some());
+    }
+
+    #[tokio::test]
+    async fn changes_are_returned_once_the_revisions_before_them_are_committed() {
+        let db = match test_database::<ListItem>().await {
+            Some(db) => db,
+            None => return,
+        };
+        let owner_id = unique_id("owner");
+        let milk_id = unique_id("milk");
+        let bread_id = unique_id("bread");
+
+        let mut tx = db.pool.begin().await.unwrap();
+        let milk = NewListItem {
+            id: &milk_id,
+            item_name: "milk",
+            details: ListItemDetails::default(),
+            completed: false,
+        };
+        insert_item(&mut tx, &owner_id, "groceries", &milk, None)
+            .await
+            .unwrap();
+        db.insert_list_item(
+            &bread_id,
+            &owner_id,
+            "groceries",
+            "bread",
+            ListItemDetails::default(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        // bread is committed, but its revision follows the one of milk that isn't yet
+        let changes = db
+            .get_list_item_changes(&owner_id, None, 0, 10)
+            .await
+            .unwrap();
+        assert_eq!(changes, vec![]);
+
+        tx.commit().await.unwrap();
+
+        // other tests can have transactions running that the watermark waits for
+        let mut changed_ids = Vec::new();
+        for _ in 0..100 {
+            let changes = db
+                .get_list_item_changes(&owner_id, None, 0, 10)
+                .await
+                .unwrap();
+            if !changes.is_empty() {
+                changed_ids = changes
+                    .into_iter()
+                    .map(|change| match change {
+                        ListItemChange::Changed(list_item) => list_item.id,
+                        ListItemChange::Purged(tombstone) => tombstone.id,
+                    })
+                    .collect();
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(changed_ids, vec![milk_id, bread_id]);
     }
 }
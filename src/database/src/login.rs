@@ -0,0 +1,260 @@
+use crate::{DatabaseResult, InsertionResult, UserDatabase};
+use sqlx::{types::time::OffsetDateTime, FromRow};
+
+/// Consecutive failed logins registered for a throttle key (a username or an ip address).
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+#[non_exhaustive]
+pub struct LoginThrottle {
+    pub throttle_key: String,
+    pub failed_attempts: i32,
+    pub last_failure: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthEventKind {
+    LoginSucceeded,
+    LoginFailed,
+    LoginThrottled,
+    Unlocked,
+}
+
+impl AuthEventKind {
+    fn as_sql_value(&self) -> &'static str {
+        match self {
+            AuthEventKind::LoginSucceeded => "login_succeeded",
+            AuthEventKind::LoginFailed => "login_failed",
+            AuthEventKind::LoginThrottled => "login_throttled",
+            AuthEventKind::Unlocked => "unlocked",
+        }
+    }
+}
+
+impl UserDatabase {
+    pub async fn get_login_throttles(&self, keys: &[String]) -> DatabaseResult<Vec<LoginThrottle>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, LoginThrottle>(
+            "SELECT throttle_key, failed_attempts, last_failure FROM login_throttle WHERE throttle_key = ANY($1)",
+        )
+        .bind(keys)
+        .fetch_all(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Registers a login attempt as failed for all of `throttle_keys` before it is made,
+    /// unless `blocked_until` returns a timestamp after `now_s` for any of their throttles.
+    ///
+    /// The throttles are locked while they are checked and incremented, so concurrent attempts
+    /// (like a batch of logins) are counted one at a time, instead of all of them passing the check
+    /// before any of them has failed. If the previous failure happened before `reset_before_s`,
+    /// the counter starts over from 1.
+    ///
+    /// Returns the latest timestamp that the attempt is blocked until, `None` if it was registered.
+    pub async fn register_login_attempt(
+        &self,
+        throttle_keys: &[String],
+        now_s: i64,
+        reset_before_s: i64,
+        blocked_until: impl Fn(&LoginThrottle) -> Option<i64>,
+    ) -> DatabaseResult<Option<i64>> {
+        // locked in the same order by every attempt, so that attempts can't deadlock
+        let mut throttle_keys = throttle_keys.to_vec();
+        throttle_keys.sort();
+        let now = OffsetDateTime::from_unix_timestamp(now_s);
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "
+        INSERT INTO login_throttle (throttle_key, failed_attempts, last_failure)
+        SELECT throttle_key, 0, $2
+        FROM unnest($1::text[]) AS throttle_key
+        ORDER BY throttle_key
+        ON CONFLICT (throttle_key) DO NOTHING",
+        )
+        .bind(&throttle_keys)
+        .bind(now)
+        .execute(&mut tx)
+        .await?;
+
+        let throttles = sqlx::query_as::<_, LoginThrottle>(
+            "
+        SELECT throttle_key, failed_attempts, last_failure
+        FROM login_throttle
+        WHERE throttle_key = ANY($1)
+        ORDER BY throttle_key
+        FOR UPDATE",
+        )
+        .bind(&throttle_keys)
+        .fetch_all(&mut tx)
+        .await?;
+
+        let blocked = throttles
+            .iter()
+            .filter_map(blocked_until)
+            .filter(|blocked_until| *blocked_until > now_s)
+            .max();
+        if blocked.is_some() {
+            // dropping the transaction rolls it back, blocked attempts aren't failures
+            return Ok(blocked);
+        }
+
+        sqlx::query(
+            "
+        UPDATE login_throttle SET
+            failed_attempts = CASE
+                WHEN last_failure < $3 THEN 1
+                ELSE failed_attempts + 1
+            END,
+            last_failure = $2
+        WHERE throttle_key = ANY($1)",
+        )
+        .bind(&throttle_keys)
+        .bind(now)
+        .bind(OffsetDateTime::from_unix_timestamp(reset_before_s))
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(None)
+    }
+
+    /// Takes back the failure that `register_login_attempt` registered for a login that didn't fail,
+    /// without clearing the failures of other logins with the same keys.
+    pub async fn unregister_login_attempt(&self, throttle_keys: &[String]) -> DatabaseResult<()> {
+        let mut db = self.get_connection().await?;
+
+        sqlx::query(
+            "
+        UPDATE login_throttle SET failed_attempts = greatest(failed_attempts - 1, 0)
+        WHERE throttle_key = ANY($1)",
+        )
+        .bind(throttle_keys)
+        .execute(&mut db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn clear_login_throttle(&self, throttle_key: &str) -> DatabaseResult<bool> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query("DELETE FROM login_throttle WHERE throttle_key = $1")
+            .bind(throttle_key)
+            .execute(&mut db)
+            .await?;
+
+        Ok(query_result.rows_affected() > 0)
+    }
+
+    pub async fn insert_auth_event(
+        &self,
+        id: &str,
+        kind: AuthEventKind,
+        username: &str,
+        user_id: Option<&str>,
+        ip_address: Option<&str>,
+    ) -> DatabaseResult<InsertionResult> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query(
+            "INSERT INTO auth_event (id, kind, username, user_id, ip_address) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(id)
+        .bind(kind.as_sql_value())
+        .bind(username)
+        .bind(user_id)
+        .bind(ip_address)
+        .execute(&mut db)
+        .await?;
+
+        Ok(InsertionResult::from_changed_rows(
+            query_result.rows_affected(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_database, unique_id};
+    use std::sync::Arc;
+
+    const NOW: i64 = 1_600_000_000;
+
+    /// Blocks a key for good after 3 failures.
+    fn blocked_after_3(throttle: &LoginThrottle) -> Option<i64> {
+        if throttle.failed_attempts >= 3 {
+            Some(i64::MAX)
+        } else {
+            None
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_attempts_are_counted_one_at_a_time() {
+        let db = match test_database::<crate::User>().await {
+            Some(db) => Arc::new(db),
+            None => return,
+        };
+        let keys = vec![unique_id("user"), unique_id("ip")];
+
+        let attempts: Vec<_> = (0..10)
+            .map(|_| {
+                let db = db.clone();
+                let keys = keys.clone();
+                tokio::spawn(async move {
+                    db.register_login_attempt(&keys, NOW, NOW - 60, blocked_after_3)
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+        let mut registered = 0;
+        for attempt in attempts {
+            if attempt.await.unwrap().is_none() {
+                registered += 1;
+            }
+        }
+        assert_eq!(registered, 3);
+
+        let throttles = db.get_login_throttles(&keys).await.unwrap();
+        assert_eq!(throttles.len(), 2);
+        assert!(throttles.iter().all(|t| t.failed_attempts == 3));
+    }
+
+    #[tokio::test]
+    async fn attempts_that_dont_fail_are_taken_back() {
+        let db = match test_database::<crate::User>().await {
+            Some(db) => db,
+            None => return,
+        };
+        let ip_key = unique_id("ip");
+        let keys = vec![unique_id("user"), ip_key.clone()];
+
+        for _ in 0..2 {
+            let blocked = db
+                .register_login_attempt(&keys, NOW, NOW - 60, blocked_after_3)
+                .await
+                .unwrap();
+            assert_eq!(blocked, None);
+        }
+        db.unregister_login_attempt(std::slice::from_ref(&ip_key))
+            .await
+            .unwrap();
+
+        let throttles = db.get_login_throttles(&[ip_key]).await.unwrap();
+        assert_eq!(throttles[0].failed_attempts, 1);
+
+        // failures from before the reset start over
+        let later = NOW + 120;
+        db.register_login_attempt(&keys, later, later - 60, blocked_after_3)
+            .await
+            .unwrap();
+        let throttles = db.get_login_throttles(&keys).await.unwrap();
+        assert!(throttles.iter().all(|t| t.failed_attempts == 1));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{DatabaseResult, UserDatabase};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+#[non_exhaustive]
+pub struct UserTotp {
+    pub user_id: String,
+    pub secret: Vec<u8>,
+    pub confirmed: bool,
+    pub last_used_step: Option<i64>,
+}
+
+impl UserDatabase {
+    pub async fn get_totp(&self, user_id: &str) -> DatabaseResult<Option<UserTotp>> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query_as::<_, UserTotp>(
+            "SELECT user_id, secret, confirmed, last_used_step FROM user_totp WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(query_result)
+    }
+
+    /// Store a new, unconfirmed, secret for `user_id`, replacing any earlier unconfirmed secret.
+    ///
+    /// Returns `false` if the user already has a confirmed secret.
+    pub async fn set_pending_totp(&self, user_id: &str, secret: &[u8]) -> DatabaseResult<bool> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query(
+            "
+        INSERT INTO user_totp (user_id, secret, confirmed) VALUES ($1, $2, FALSE)
+        ON CONFLICT (user_id) DO UPDATE SET secret = EXCLUDED.secret, created = now()
+        WHERE user_totp.confirmed = FALSE",
+        )
+        .bind(user_id)
+        .bind(secret)
+        .execute(&mut db)
+        .await?;
+
+        Ok(query_result.rows_affected() == 1)
+    }
+
+    /// Confirm the pending secret of `user_id` and replace its recovery codes, in a single transaction.
+    ///
+    /// `step` is the step of the code that was used to confirm, it can't be used again to log in.
+    pub async fn confirm_totp(
+        &self,
+        user_id: &str,
+        step: i64,
+        recovery_codes: &[String],
+    ) -> DatabaseResult<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let query_result = sqlx::query(
+            "
+        UPDATE user_totp SET confirmed = TRUE, confirmed_at = now(), last_used_step = $2
+        WHERE user_id = $1 AND confirmed = FALSE",
+        )
+        .bind(user_id)
+        .bind(step)
+        .execute(&mut tx)
+        .await?;
+
+        if query_result.rows_affected() != 1 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query("DELETE FROM user_recovery_code WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut tx)
+            .await?;
+
+        for recovery_code in recovery_codes {
+            sqlx::query(
+                "INSERT INTO user_recovery_code (user_id, code_hash) VALUES ($1, crypt($2, gen_salt('bf')))",
+            )
+            .bind(user_id)
+            .bind(recovery_code)
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Mark `step` as used, returns `false` if it (or a later step) has already been used.
+    pub async fn use_totp_step(&self, user_id: &str, step: i64) -> DatabaseResult<bool> {
+        let mut db = self.get_connection().await?;
+
+        let query_result = sqlx::query(
+            "
+        UPDATE user_totp SET last_used_step = $2
+        WHERE user_id = $1 AND confirmed = TRUE AND (last_used_step IS NULL OR last_used_step < $2)",
+        )
+        .bind(user_id)
+        .bind(step)
+        .execute(&mut db)
+        .await?;
+
+        Ok(query_result.rows_affected() == 1)
+    }
+
+    /// Mark a matching unused recovery code as used, returns `false` if there was none.
+    ///
+    /// Of concurrent logins with the same code, only one can use it.
+    pub async fn use_recovery_code(&self, user_id: &str, code: &str) -> DatabaseResult<bool> {
+        let mut db = self.get_connection().await?;
+
+        // `used` is checked again after waiting for a concurrent use of the code to commit
+        let query_result = sqlx::query(
+            "
+        UPDATE user_recovery_code SET used = now()
+        WHERE used IS NULL AND id = (
+            SELECT id FROM user_recovery_code
+            WHERE user_id = $1 AND used IS NULL AND code_hash = crypt($2, code_hash)
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )",
+        )
+        .bind(user_id)
+        .bind(code)
+        .execute(&mut db)
+        .await?;
+
+        Ok(query_result.rows_affected() == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_database, unique_id, InsertionResult};
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn recovery_codes_can_only_be_used_once() {
+        let db = match test_database::<crate::User>().await {
+            Some(db) => Arc::new(db),
+            None => return,
+        };
+        let user_id = unique_id("user");
+        let inserted = db
+            .insert_user(&user_id, &user_id, "password")
+            .await
+            .unwrap();
+        assert_eq!(inserted, InsertionResult::Inserted);
+        assert!(db.set_pending_totp(&user_id, b"secret").await.unwrap());
+        let recovery_codes = ["aaaa-bbbb".to_owned(), "cccc-dddd".to_owned()];
+        assert!(db.confirm_totp(&user_id, 1, &recovery_codes).await.unwrap());
+
+        let logins: Vec<_> = (0..5)
+            .map(|_| {
+                let db = db.clone();
+                let user_id = user_id.clone();
+                tokio::spawn(async move { db.use_recovery_code(&user_id, "aaaa-bbbb").await })
+            })
+            .collect();
+        let mut used = 0;
+        for login in logins {
+            if login.await.unwrap().unwrap() {
+                used += 1;
+            }
+        }
+        assert_eq!(used, 1);
+
+        assert!(!db.use_recovery_code(&user_id, "aaaa-bbbb").await.unwrap());
+        assert!(!db.use_recovery_code(&user_id, "eeee-ffff").await.unwrap());
+        assert!(db.use_recovery_code(&user_id, "cccc-dddd").await.unwrap());
+    }
+}
@@ -55,6 +55,19 @@ impl UserDatabase {
         }
     }
 
+    pub async fn get_user_by_username(&self, username: &str) -> DatabaseResult<Option<User>> {
+        let mut db = self.get_connection().await?;
+
+        let user = sqlx::query_as::<_, User>(
+            r#"SELECT id, username, created FROM "user" WHERE username = $1"#,
+        )
+        .bind(username)
+        .fetch_optional(&mut db)
+        .await?;
+
+        Ok(user)
+    }
+
     pub async fn validate_user(
         &self,
         username: &str,
@@ -7,11 +7,16 @@ pub mod consts {
     pub const AGENCY_REDIS_KEY: &'static str = "agency";
     pub const CALENDAR_REDIS_KEY: &'static str = "calendar";
     pub const STOP_REDIS_KEY: &'static str = "stop";
+    /// A geospatial index of the coordinates of the stations in `STOP_REDIS_KEY`.
+    pub const STOP_GEO_REDIS_KEY: &'static str = "stop_geo";
+    /// The stations in `STOP_REDIS_KEY` as one JSON array, for searching them by name.
+    pub const STATION_REDIS_KEY: &'static str = "station";
     pub const ROUTE_REDIS_KEY: &'static str = "route";
 }
 
 mod model;
 mod modes;
 
+pub use model::{Station, Stop};
 pub use modes::Populate;
 pub use modes::Serve;
@@ -90,6 +90,30 @@ pub struct Stop {
     pub platform_code: String,
 }
 
+impl Stop {
+    /// Stations and stops that aren't part of a station,
+    /// as opposed to the platforms and entrances of stations.
+    pub fn is_station(&self) -> bool {
+        self.parent_station.is_empty()
+    }
+
+    /// The latitude and longitude, `None` if they aren't numbers.
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        let lat = self.stop_lat.parse().ok()?;
+        let lon = self.stop_lon.parse().ok()?;
+        Some((lat, lon))
+    }
+}
+
+/// A station with its coordinates, a compact version of the `Stop` it comes from.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Station {
+    pub stop_id: String,
+    pub stop_name: String,
+    pub lat: f64,
+    pub lon: f64,
+}
+
 impl Id for Stop {
     type Output = String;
 
@@ -2,11 +2,19 @@ use crate::consts::*;
 use crate::model::Agency;
 use crate::model::*;
 use isahc::{AsyncReadResponseExt, HttpClient};
-use redis::{async_pool::mobc_redis::redis::AsyncCommands, async_pool::AsyncRedisPool};
+use redis::{
+    async_pool::mobc_redis::redis::{cmd, AsyncCommands},
+    async_pool::AsyncRedisPool,
+};
 use serde::{de::DeserializeOwned, Serialize};
 use std::{collections::HashSet, error::Error, fs::File};
 
 const GTFS_DOWNLOAD_DIR: &'static str = "gtfs_download";
+/// How many stops are added to the geospatial index per GEOADD.
+const GEOADD_CHUNK_SIZE: usize = 1000;
+/// The coordinates that Redis can index, a GEOADD with any other fails as a whole.
+const MAX_GEO_LATITUDE: f64 = 85.05112878;
+const MAX_GEO_LONGITUDE: f64 = 180.0;
 
 pub struct Populate {
     http_client: HttpClient,
@@ -105,6 +113,10 @@ impl Populate {
         self.update_hash_set_from_csv::<Stop>("stops.txt", STOP_REDIS_KEY)
             .await?;
 
+        let stations = Self::stations_from_csv("stops.txt")?;
+        self.update_station_list(&stations).await?;
+        self.update_stop_geo_index(&stations).await?;
+
         self.update_hash_set_from_csv::<Route>("routes.txt", ROUTE_REDIS_KEY)
             .await?;
 
@@ -131,6 +143,82 @@ impl Populate {
         Ok(())
     }
 
+    /// The stations in `path` that have coordinates which can be indexed.
+    fn stations_from_csv(path: &str) -> Result<Vec<Station>, Box<dyn Error>> {
+        let mut stations = Vec::new();
+        for stop in Self::csv_get_generic::<Stop>(path)? {
+            if !stop.is_station() {
+                continue;
+            }
+            match stop.coordinates() {
+                Some((lat, lon))
+                    if lat.abs() <= MAX_GEO_LATITUDE && lon.abs() <= MAX_GEO_LONGITUDE =>
+                {
+                    stations.push(Station {
+                        stop_id: stop.stop_id,
+                        stop_name: stop.stop_name,
+                        lat,
+                        lon,
+                    })
+                }
+                _ => warn!("stop {} has invalid coordinates", stop.stop_id),
+            }
+        }
+
+        Ok(stations)
+    }
+
+    /// Stores all stations under one key, so that they can be read at once and searched by name.
+    async fn update_station_list(&self, stations: &[Station]) -> Result<(), Box<dyn Error>> {
+        info!("updating contents of {STATION_REDIS_KEY} in Redis...");
+        let mut conn = self.redis_pool.get_connection().await?;
+        cmd("SET")
+            .arg(STATION_REDIS_KEY)
+            .arg(serde_json::to_string(stations)?)
+            .query_async::<_, ()>(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Indexes the coordinates of the stations, so that they can be searched by distance.
+    async fn update_stop_geo_index(&self, stations: &[Station]) -> Result<(), Box<dyn Error>> {
+        info!("updating contents of {STOP_GEO_REDIS_KEY} in Redis...");
+        let mut conn = self.redis_pool.get_connection().await?;
+        if stations.is_empty() {
+            cmd("DEL")
+                .arg(STOP_GEO_REDIS_KEY)
+                .query_async::<_, ()>(&mut *conn)
+                .await?;
+            return Ok(());
+        }
+
+        // the index is built under another key and then renamed, which removes the old stops
+        let new_key = format!("{STOP_GEO_REDIS_KEY}:new");
+        cmd("DEL")
+            .arg(&new_key)
+            .query_async::<_, ()>(&mut *conn)
+            .await?;
+        for chunk in stations.chunks(GEOADD_CHUNK_SIZE) {
+            let mut geoadd = cmd("GEOADD");
+            geoadd.arg(&new_key);
+            for station in chunk {
+                geoadd
+                    .arg(station.lon)
+                    .arg(station.lat)
+                    .arg(&station.stop_id);
+            }
+            geoadd.query_async::<_, ()>(&mut *conn).await?;
+        }
+        cmd("RENAME")
+            .arg(&new_key)
+            .arg(STOP_GEO_REDIS_KEY)
+            .query_async::<_, ()>(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
     fn csv_get_generic<T>(path: &str) -> Result<Vec<T>, Box<dyn Error>>
     where
         T: DeserializeOwned,
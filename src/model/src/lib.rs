@@ -9,6 +9,7 @@ use std::{
     str::FromStr,
 };
 
+pub use chrono;
 pub use methods::*;
 
 mod methods;
@@ -16,19 +17,57 @@ mod methods;
 mod method_names {
     pub const ADD_LIST_ITEM: &str = "add_list_item";
     pub const GET_LIST_ITEMS: &str = "get_list_items";
+    pub const UPDATE_LIST_ITEM: &str = "update_list_item";
     pub const DELETE_LIST_ITEM: &str = "delete_list_item";
+    pub const COMPLETE_LIST_ITEM: &str = "complete_list_item";
+    pub const UNCOMPLETE_LIST_ITEM: &str = "uncomplete_list_item";
+    pub const MOVE_LIST_ITEM: &str = "move_list_item";
+    pub const GET_DELETED_LIST_ITEMS: &str = "get_deleted_list_items";
+    pub const RESTORE_LIST_ITEM: &str = "restore_list_item";
+    pub const GET_LIST_ITEM_HISTORY: &str = "get_list_item_history";
+    pub const REVERT_LIST_ITEM: &str = "revert_list_item";
+    pub const GET_LIST_ITEM_ATTACHMENTS: &str = "get_list_item_attachments";
+    pub const ADD_LIST_ITEM_ATTACHMENT: &str = "add_list_item_attachment";
+    pub const DELETE_LIST_ITEM_ATTACHMENT: &str = "delete_list_item_attachment";
+    pub const REGENERATE_CALENDAR_FEED: &str = "regenerate_calendar_feed";
+    pub const ADD_LIST_ITEMS: &str = "add_list_items";
+    pub const DELETE_LIST_ITEMS: &str = "delete_list_items";
+    pub const MOVE_LIST_ITEMS: &str = "move_list_items";
+    pub const GET_LIST_CHANGES: &str = "get_list_changes";
+    pub const GET_LIST_EVENTS_URL: &str = "get_list_events_url";
+    pub const SEARCH_LIST_ITEMS: &str = "search_list_items";
+    pub const EXPORT_LIST: &str = "export_list";
+    pub const IMPORT_LIST: &str = "import_list";
+    pub const QUICK_ADD_LIST_ITEM: &str = "quick_add_list_item";
+    pub const SET_LIST_ITEM_RECURRENCE: &str = "set_list_item_recurrence";
     pub const GET_LIST_TYPES: &str = "get_list_types";
     pub const RENAME_LIST_TYPE: &str = "rename_list_type";
+    pub const DELETE_LIST_TYPE: &str = "delete_list_type";
+    pub const MERGE_LIST_TYPES: &str = "merge_list_types";
+    pub const SAVE_LIST_AS_TEMPLATE: &str = "save_list_as_template";
+    pub const GET_TEMPLATES: &str = "get_templates";
+    pub const INSTANTIATE_TEMPLATE: &str = "instantiate_template";
+    pub const SHARE_LIST_TYPE: &str = "share_list_type";
+    pub const UNSHARE_LIST_TYPE: &str = "unshare_list_type";
 
     pub const GET_DEPARTURES: &str = "get_departures";
+    pub const GET_ARRIVALS: &str = "get_arrivals";
+    pub const SEARCH_STOPS: &str = "search_stops";
+    pub const GET_NEARBY_STOPS: &str = "get_nearby_stops";
 
     pub const SLEEP: &str = "sleep";
 
     pub const ADD_USER: &str = "add_user";
     pub const GET_USER: &str = "get_user";
     pub const GET_TOKEN: &str = "get_token";
+    pub const UNLOCK_USER: &str = "unlock_user";
+    pub const ENROLL_TOTP: &str = "enroll_totp";
+    pub const CONFIRM_TOTP: &str = "confirm_totp";
 
     pub const GENERATE_SAS_KEY: &str = "generate_sas_key";
+    pub const VERIFY_SAS_KEY: &str = "verify_sas_key";
+
+    pub const GET_AUDIT_EVENTS: &str = "get_audit_events";
 }
 
 pub mod error_codes {
@@ -43,6 +82,11 @@ pub mod error_codes {
     pub mod application {
         pub const ITEM_DOES_NOT_EXIST: i32 = -31999;
         pub const NOT_AUTHORIZED: i32 = -31998;
+        pub const TOO_MANY_LOGIN_ATTEMPTS: i32 = -31997;
+        pub const TOTP_REQUIRED: i32 = -31996;
+        pub const VERSION_CONFLICT: i32 = -31995;
+        pub const USER_DOES_NOT_EXIST: i32 = -31994;
+        pub const NOT_PERMITTED: i32 = -31993;
     }
 }
 
@@ -51,17 +95,82 @@ pub mod error_codes {
 pub enum Method {
     /// Add a list item
     AddListItem,
-    /// Get all list items of a given list type
+    /// Get all list items of a given list type, in their manual order
     GetListItems,
-    /// Delete a list item
+    /// Update a list item, rejecting stale writes
+    UpdateListItem,
+    /// Move a list item to the trash
     DeleteListItem,
-    /// Get all existing list types
+    /// Mark a list item as completed
+    CompleteListItem,
+    /// Mark a list item as not completed
+    UncompleteListItem,
+    /// Move a list item before or after another item
+    MoveListItem,
+    /// Get the list items of a given list type that are in the trash
+    GetDeletedListItems,
+    /// Move a list item out of the trash
+    RestoreListItem,
+    /// Get every version of a list item, with who changed it and when
+    GetListItemHistory,
+    /// Set a list item back to an earlier version
+    RevertListItem,
+    /// Get the attachments of a list item, with signed urls to download them from
+    GetListItemAttachments,
+    /// Upload a file as an attachment of a list item, with a `POST` to `/api/attachments`
+    /// instead of JSON RPC
+    AddListItemAttachment,
+    /// Delete an attachment of a list item
+    DeleteListItemAttachment,
+    /// Create a new secret url for the calendar feed of list items with due dates
+    RegenerateCalendarFeed,
+    /// Add several list items, either all of them or none of them
+    AddListItems,
+    /// Move several list items to the trash, either all of them or none of them
+    DeleteListItems,
+    /// Apply several moves of list items, either all of them or none of them
+    MoveListItems,
+    /// Get the changes of list items since a given revision
+    GetListChanges,
+    /// Get a short-lived signed url to subscribe to the changes of a list type
+    GetListEventsUrl,
+    /// Full-text search of list items
+    SearchListItems,
+    /// Export a list type as CSV, JSON or a Markdown checklist
+    ExportList,
+    /// Import items into a list type from CSV, JSON or a Markdown checklist
+    ImportList,
+    /// Add a list item from a line of text like "2 l milk #groceries tomorrow"
+    QuickAddListItem,
+    /// Make a list item recur daily, weekly, monthly or some days after it was completed
+    SetListItemRecurrence,
+    /// Get the list types owned by or shared with the caller
     GetListTypes,
     /// Rename a list type
     RenameListType,
+    /// Move all items of a list type to the trash
+    DeleteListType,
+    /// Move all items of a list type into another list type
+    MergeListTypes,
+    /// Save the items of a list type as a named template
+    SaveListAsTemplate,
+    /// Get the list templates of the caller
+    GetTemplates,
+    /// Create a new list type from a template
+    InstantiateTemplate,
+    /// Share a list type with another user
+    ShareListType,
+    /// Stop sharing a list type with another user
+    UnshareListType,
 
     /// Get upcoming departures for a given stop
     GetDepartures,
+    /// Get upcoming arrivals at a given stop
+    GetArrivals,
+    /// Search for stops by name
+    SearchStops,
+    /// Get the stops closest to a position
+    GetNearbyStops,
 
     /// Tell the server to sleep
     Sleep,
@@ -72,9 +181,20 @@ pub enum Method {
     GetUser,
     /// Get a JWT
     GetToken,
+    /// Clear the failed login attempts of a user
+    UnlockUser,
+    /// Start two-factor enrollment for the calling user
+    EnrollTotp,
+    /// Confirm two-factor enrollment with a first code
+    ConfirmTotp,
 
     /// Generate an SAS key
     GenerateSasKey,
+    /// Verify an SAS key
+    VerifySasKey,
+
+    /// Get recorded changes made by mutating methods
+    GetAuditEvents,
 }
 
 impl FromStr for Method {
@@ -85,15 +205,52 @@ impl FromStr for Method {
         match s {
             ADD_LIST_ITEM => Ok(AddListItem),
             GET_LIST_ITEMS => Ok(GetListItems),
+            UPDATE_LIST_ITEM => Ok(UpdateListItem),
             DELETE_LIST_ITEM => Ok(DeleteListItem),
+            COMPLETE_LIST_ITEM => Ok(CompleteListItem),
+            UNCOMPLETE_LIST_ITEM => Ok(UncompleteListItem),
+            MOVE_LIST_ITEM => Ok(MoveListItem),
+            GET_DELETED_LIST_ITEMS => Ok(GetDeletedListItems),
+            RESTORE_LIST_ITEM => Ok(RestoreListItem),
+            GET_LIST_ITEM_HISTORY => Ok(GetListItemHistory),
+            REVERT_LIST_ITEM => Ok(RevertListItem),
+            GET_LIST_ITEM_ATTACHMENTS => Ok(GetListItemAttachments),
+            ADD_LIST_ITEM_ATTACHMENT => Ok(AddListItemAttachment),
+            DELETE_LIST_ITEM_ATTACHMENT => Ok(DeleteListItemAttachment),
+            REGENERATE_CALENDAR_FEED => Ok(RegenerateCalendarFeed),
+            ADD_LIST_ITEMS => Ok(AddListItems),
+            DELETE_LIST_ITEMS => Ok(DeleteListItems),
+            MOVE_LIST_ITEMS => Ok(MoveListItems),
+            GET_LIST_CHANGES => Ok(GetListChanges),
+            GET_LIST_EVENTS_URL => Ok(GetListEventsUrl),
+            SEARCH_LIST_ITEMS => Ok(SearchListItems),
+            EXPORT_LIST => Ok(ExportList),
+            IMPORT_LIST => Ok(ImportList),
+            QUICK_ADD_LIST_ITEM => Ok(QuickAddListItem),
+            SET_LIST_ITEM_RECURRENCE => Ok(SetListItemRecurrence),
             GET_LIST_TYPES => Ok(GetListTypes),
             RENAME_LIST_TYPE => Ok(RenameListType),
+            DELETE_LIST_TYPE => Ok(DeleteListType),
+            MERGE_LIST_TYPES => Ok(MergeListTypes),
+            SAVE_LIST_AS_TEMPLATE => Ok(SaveListAsTemplate),
+            GET_TEMPLATES => Ok(GetTemplates),
+            INSTANTIATE_TEMPLATE => Ok(InstantiateTemplate),
+            SHARE_LIST_TYPE => Ok(ShareListType),
+            UNSHARE_LIST_TYPE => Ok(UnshareListType),
             GET_DEPARTURES => Ok(GetDepartures),
+            GET_ARRIVALS => Ok(GetArrivals),
+            SEARCH_STOPS => Ok(SearchStops),
+            GET_NEARBY_STOPS => Ok(GetNearbyStops),
             SLEEP => Ok(Sleep),
             GENERATE_SAS_KEY => Ok(GenerateSasKey),
+            VERIFY_SAS_KEY => Ok(VerifySasKey),
             ADD_USER => Ok(AddUser),
             GET_USER => Ok(GetUser),
             GET_TOKEN => Ok(GetToken),
+            GET_AUDIT_EVENTS => Ok(GetAuditEvents),
+            UNLOCK_USER => Ok(UnlockUser),
+            ENROLL_TOTP => Ok(EnrollTotp),
+            CONFIRM_TOTP => Ok(ConfirmTotp),
             _ => Err(()),
         }
     }
@@ -106,15 +263,52 @@ impl Display for Method {
         let ouput = match self {
             AddListItem => ADD_LIST_ITEM,
             GetListItems => GET_LIST_ITEMS,
+            UpdateListItem => UPDATE_LIST_ITEM,
             DeleteListItem => DELETE_LIST_ITEM,
+            CompleteListItem => COMPLETE_LIST_ITEM,
+            UncompleteListItem => UNCOMPLETE_LIST_ITEM,
+            MoveListItem => MOVE_LIST_ITEM,
+            GetDeletedListItems => GET_DELETED_LIST_ITEMS,
+            RestoreListItem => RESTORE_LIST_ITEM,
+            GetListItemHistory => GET_LIST_ITEM_HISTORY,
+            RevertListItem => REVERT_LIST_ITEM,
+            GetListItemAttachments => GET_LIST_ITEM_ATTACHMENTS,
+            AddListItemAttachment => ADD_LIST_ITEM_ATTACHMENT,
+            DeleteListItemAttachment => DELETE_LIST_ITEM_ATTACHMENT,
+            RegenerateCalendarFeed => REGENERATE_CALENDAR_FEED,
+            AddListItems => ADD_LIST_ITEMS,
+            DeleteListItems => DELETE_LIST_ITEMS,
+            MoveListItems => MOVE_LIST_ITEMS,
+            GetListChanges => GET_LIST_CHANGES,
+            GetListEventsUrl => GET_LIST_EVENTS_URL,
+            SearchListItems => SEARCH_LIST_ITEMS,
+            ExportList => EXPORT_LIST,
+            ImportList => IMPORT_LIST,
+            QuickAddListItem => QUICK_ADD_LIST_ITEM,
+            SetListItemRecurrence => SET_LIST_ITEM_RECURRENCE,
             GetListTypes => GET_LIST_TYPES,
             RenameListType => RENAME_LIST_TYPE,
+            DeleteListType => DELETE_LIST_TYPE,
+            MergeListTypes => MERGE_LIST_TYPES,
+            SaveListAsTemplate => SAVE_LIST_AS_TEMPLATE,
+            GetTemplates => GET_TEMPLATES,
+            InstantiateTemplate => INSTANTIATE_TEMPLATE,
+            ShareListType => SHARE_LIST_TYPE,
+            UnshareListType => UNSHARE_LIST_TYPE,
             Sleep => SLEEP,
             GetDepartures => GET_DEPARTURES,
+            GetArrivals => GET_ARRIVALS,
+            SearchStops => SEARCH_STOPS,
+            GetNearbyStops => GET_NEARBY_STOPS,
             GenerateSasKey => GENERATE_SAS_KEY,
+            VerifySasKey => VERIFY_SAS_KEY,
             AddUser => ADD_USER,
             GetUser => GET_USER,
             GetToken => GET_TOKEN,
+            GetAuditEvents => GET_AUDIT_EVENTS,
+            UnlockUser => UNLOCK_USER,
+            EnrollTotp => ENROLL_TOTP,
+            ConfirmTotp => CONFIRM_TOTP,
         };
         write!(f, "{}", ouput)
     }
@@ -446,7 +640,8 @@ impl JsonRpcError {
 
     /// Constructor for a "Not permitted" webserver error.
     pub fn not_permitted() -> Self {
-        Self::internal_error().with_message("not permitted")
+        Self::application_error(error_codes::application::NOT_PERMITTED)
+            .with_message("not permitted")
     }
 
     pub fn database_error() -> Self {
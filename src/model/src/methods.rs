@@ -1,3 +1,4 @@
+pub mod audit;
 pub mod list;
 pub mod sas;
 pub mod server;
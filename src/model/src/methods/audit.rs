@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+pub mod get_audit_events;
+
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
+#[non_exhaustive]
+pub struct AuditEvent {
+    pub id: Uuid,
+    /// Id of the user that made the change, if the request was authenticated.
+    pub actor_id: Option<String>,
+    pub method: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub created: DateTime<Utc>,
+}
+
+impl AuditEvent {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: Uuid,
+        actor_id: Option<String>,
+        method: String,
+        entity_type: String,
+        entity_id: String,
+        before: Option<Value>,
+        after: Option<Value>,
+        created: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id,
+            actor_id,
+            method,
+            entity_type,
+            entity_id,
+            before,
+            after,
+            created,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use super::AuditEvent;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+const MIN_LIMIT: u32 = 1;
+const MAX_LIMIT: u32 = 500;
+const DEFAULT_LIMIT: u32 = 50;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub actor_id: Option<String>,
+    pub method: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `limit` is outside the range (1..=500).
+    pub fn new(
+        actor_id: Option<String>,
+        method: Option<String>,
+        entity_type: Option<String>,
+        entity_id: Option<String>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Self, InvalidParams> {
+        let limit = match limit {
+            Some(limit) if (MIN_LIMIT..=MAX_LIMIT).contains(&limit) => limit,
+            None => DEFAULT_LIMIT,
+            Some(_invalid) => return Err(InvalidParams::InvalidLimit),
+        };
+
+        Ok(Self {
+            actor_id: non_empty(actor_id),
+            method: non_empty(method),
+            entity_type: non_empty(entity_type),
+            entity_id: non_empty(entity_id),
+            limit,
+            offset: offset.unwrap_or(0),
+        })
+    }
+}
+
+/// Empty or whitespace filters are treated as missing.
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_owned()).filter(|v| !v.is_empty())
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(
+            builder.actor_id,
+            builder.method,
+            builder.entity_type,
+            builder.entity_id,
+            builder.limit,
+            builder.offset,
+        )
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    actor_id: Option<String>,
+    method: Option<String>,
+    entity_type: Option<String>,
+    entity_id: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidLimit,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidLimit => format!(
+                "invalid limit, should be integer in [{}, {}]",
+                MIN_LIMIT, MAX_LIMIT
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub events: Vec<AuditEvent>,
+}
+
+impl MethodResult {
+    pub fn new(events: Vec<AuditEvent>) -> Self {
+        Self { events }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params = Params::new(None, None, None, None, None, None).unwrap();
+        assert_eq!(params.limit, DEFAULT_LIMIT);
+        assert_eq!(params.offset, 0);
+
+        assert!(Params::new(None, None, None, None, Some(MIN_LIMIT), None).is_ok());
+        assert!(Params::new(None, None, None, None, Some(MAX_LIMIT), None).is_ok());
+        assert!(Params::new(None, None, None, None, Some(0), None).is_err());
+        assert!(Params::new(None, None, None, None, Some(MAX_LIMIT + 1), None).is_err());
+
+        let params = Params::new(
+            Some(" user-id ".to_owned()),
+            Some("  ".to_owned()),
+            Some(String::new()),
+            None,
+            None,
+            Some(100),
+        )
+        .unwrap();
+        assert_eq!(params.actor_id.as_deref(), Some("user-id"));
+        assert_eq!(params.method, None);
+        assert_eq!(params.entity_type, None);
+        assert_eq!(params.offset, 100);
+    }
+}
@@ -1,10 +1,41 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use std::{fmt::Display, str::FromStr};
 use uuid::Uuid;
 
 pub mod add_list_item;
+pub mod add_list_items;
+pub mod complete_list_item;
 pub mod delete_list_item;
+pub mod delete_list_item_attachment;
+pub mod delete_list_items;
+pub mod delete_list_type;
+pub mod export_list;
+pub mod get_deleted_list_items;
+pub mod get_list_changes;
+pub mod get_list_events_url;
+pub mod get_list_item_attachments;
+pub mod get_list_item_history;
 pub mod get_list_items;
 pub mod get_list_types;
+pub mod get_templates;
+pub mod import_list;
+pub mod instantiate_template;
+pub mod merge_list_types;
+pub mod move_list_item;
+pub mod move_list_items;
+pub mod quick_add;
+pub mod quick_add_list_item;
+pub mod regenerate_calendar_feed;
 pub mod rename_list_type;
+pub mod restore_list_item;
+pub mod revert_list_item;
+pub mod save_list_as_template;
+pub mod search_list_items;
+pub mod set_list_item_recurrence;
+pub mod share_list_type;
+pub mod uncomplete_list_item;
+pub mod unshare_list_type;
+pub mod update_list_item;
 
 #[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
 #[non_exhaustive]
@@ -12,14 +43,621 @@ pub struct ListItem {
     pub id: Uuid,
     pub list_type: String,
     pub item_name: String,
+    /// Incremented on every change, used to detect stale updates.
+    pub version: i64,
+    #[serde(flatten)]
+    pub details: ListItemDetails,
+    pub completed: bool,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Set if the item is uncompleted again some time after it has been completed.
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
 }
 
 impl ListItem {
-    pub fn new(id: Uuid, list_type: String, item_name: String) -> Self {
+    pub fn new(
+        id: Uuid,
+        list_type: String,
+        item_name: String,
+        version: i64,
+        details: ListItemDetails,
+        completed_at: Option<DateTime<Utc>>,
+    ) -> Self {
         Self {
             id,
             list_type,
             item_name,
+            version,
+            details,
+            completed: completed_at.is_some(),
+            completed_at,
+            recurrence: None,
         }
     }
+
+    pub fn with_recurrence(self, recurrence: Option<Recurrence>) -> Self {
+        Self { recurrence, ..self }
+    }
+}
+
+/// A list item in the trash.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
+#[non_exhaustive]
+pub struct DeletedListItem {
+    #[serde(flatten)]
+    pub list_item: ListItem,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl DeletedListItem {
+    pub fn new(list_item: ListItem, deleted_at: DateTime<Utc>) -> Self {
+        Self {
+            list_item,
+            deleted_at,
+        }
+    }
+}
+
+/// A version of a list item, as it was after one of its changes.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
+#[non_exhaustive]
+pub struct ListItemVersion {
+    #[serde(flatten)]
+    pub list_item: ListItem,
+    /// The user that made the change, `None` for changes made by the server, e.g. recurrences.
+    pub actor_id: Option<String>,
+    pub changed_at: DateTime<Utc>,
+    /// Set if the item was in the trash after the change.
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl ListItemVersion {
+    pub fn new(
+        list_item: ListItem,
+        actor_id: Option<String>,
+        changed_at: DateTime<Utc>,
+        deleted_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            list_item,
+            actor_id,
+            changed_at,
+            deleted_at,
+        }
+    }
+}
+
+/// A list item found by `search_list_items`.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
+#[non_exhaustive]
+pub struct ListItemSearchHit {
+    #[serde(flatten)]
+    pub list_item: ListItem,
+    /// How well the item matches, only comparable between hits of the same search.
+    pub rank: f32,
+    pub highlight: SearchHighlight,
+}
+
+impl ListItemSearchHit {
+    pub fn new(list_item: ListItem, rank: f32, highlight: SearchHighlight) -> Self {
+        Self {
+            list_item,
+            rank,
+            highlight,
+        }
+    }
+}
+
+/// The matching words are surrounded by `<mark>` and `</mark>`.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
+#[non_exhaustive]
+pub struct SearchHighlight {
+    pub item_name: String,
+    /// Only the matching fragments of the notes, if the item has notes.
+    pub notes: Option<String>,
+}
+
+impl SearchHighlight {
+    pub fn new(item_name: String, notes: Option<String>) -> Self {
+        Self { item_name, notes }
+    }
+}
+
+/// A change of a list item, used to sync lists incrementally.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ListItemChange {
+    Inserted {
+        revision: i64,
+        list_item: ListItem,
+    },
+    Updated {
+        revision: i64,
+        list_item: ListItem,
+    },
+    /// The item was moved to the trash or purged from it.
+    Deleted {
+        revision: i64,
+        id: Uuid,
+        list_type: String,
+    },
+}
+
+impl ListItemChange {
+    pub fn revision(&self) -> i64 {
+        match self {
+            ListItemChange::Inserted { revision, .. }
+            | ListItemChange::Updated { revision, .. }
+            | ListItemChange::Deleted { revision, .. } => *revision,
+        }
+    }
+}
+
+/// The outcome for one of the items of a bulk method.
+///
+/// Bulk methods change either all of their items or none of them,
+/// so if any item fails the others are `not_applied`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[non_exhaustive]
+pub struct BulkItemResult {
+    pub id: Uuid,
+    pub success: bool,
+    pub error: Option<BulkItemError>,
+}
+
+impl BulkItemResult {
+    pub fn succeeded(id: Uuid) -> Self {
+        Self {
+            id,
+            success: true,
+            error: None,
+        }
+    }
+
+    pub fn failed(id: Uuid, error: BulkItemError) -> Self {
+        Self {
+            id,
+            success: false,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkItemError {
+    AlreadyExists,
+    DoesNotExist,
+    NotPermitted,
+    /// The item to move relative to doesn't exist or is in another list type.
+    InvalidTarget,
+    /// The item was fine, but nothing was changed since another item failed.
+    NotApplied,
+}
+
+/// The optional checklist fields of a list item, e.g. "3 l milk, lactose free, by friday".
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct ListItemDetails {
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub notes: Option<String>,
+    pub due_date: Option<NaiveDate>,
+}
+
+impl ListItemDetails {
+    /// Empty or whitespace `unit` and `notes` are treated as not set.
+    pub fn new(
+        quantity: Option<f64>,
+        unit: Option<String>,
+        notes: Option<String>,
+        due_date: Option<NaiveDate>,
+    ) -> Self {
+        fn trimmed(value: Option<String>) -> Option<String> {
+            value
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+        }
+
+        Self {
+            quantity,
+            unit: trimmed(unit),
+            notes: trimmed(notes),
+            due_date,
+        }
+    }
+
+    /// A quantity has to be a positive, finite number.
+    pub fn has_valid_quantity(&self) -> bool {
+        !matches!(self.quantity, Some(quantity) if !(quantity.is_finite() && quantity > 0.0))
+    }
+}
+
+/// When a completed list item becomes uncompleted again, e.g. for weekly chores or staples.
+///
+/// Dates are in UTC, an item recurs at the start of the first matching day after it was completed.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "frequency", rename_all = "snake_case")]
+pub enum Recurrence {
+    Daily,
+    /// On any of `weekdays`, e.g. `["Mon", "Thu"]`.
+    Weekly {
+        weekdays: Vec<Weekday>,
+    },
+    /// On `day` of every month, or on the last day of months that are shorter.
+    Monthly {
+        day: u32,
+    },
+    /// A number of days after the item was completed.
+    AfterCompletion {
+        days: u32,
+    },
+}
+
+impl Recurrence {
+    pub const MAX_DAYS: u32 = 366;
+
+    /// Weekly recurrences need at least one weekday, monthly ones a day in `[1, 31]`
+    /// and the ones after completion a number of days in `[1, MAX_DAYS]`.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Recurrence::Daily => true,
+            Recurrence::Weekly { weekdays } => !weekdays.is_empty(),
+            Recurrence::Monthly { day } => (1..=31).contains(day),
+            Recurrence::AfterCompletion { days } => (1..=Self::MAX_DAYS).contains(days),
+        }
+    }
+
+    /// The first day after `completed_on` that the item recurs on.
+    pub fn next_occurrence(&self, completed_on: NaiveDate) -> NaiveDate {
+        match self {
+            Recurrence::Daily => completed_on + Duration::days(1),
+            Recurrence::Weekly { weekdays } => (1..=7)
+                .map(|days| completed_on + Duration::days(days))
+                .find(|date| weekdays.contains(&date.weekday()))
+                .unwrap_or_else(|| completed_on + Duration::days(7)),
+            Recurrence::Monthly { day } => {
+                let this_month = day_of_month(completed_on.year(), completed_on.month(), *day);
+                if this_month > completed_on {
+                    this_month
+                } else if completed_on.month() == 12 {
+                    day_of_month(completed_on.year() + 1, 1, *day)
+                } else {
+                    day_of_month(completed_on.year(), completed_on.month() + 1, *day)
+                }
+            }
+            Recurrence::AfterCompletion { days } => completed_on + Duration::days(*days as i64),
+        }
+    }
+}
+
+/// `day` of the month, or its last day if the month is shorter than that.
+fn day_of_month(year: i32, month: u32, day: u32) -> NaiveDate {
+    (1..=day.clamp(1, 31))
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .expect("every month has a first day")
+}
+
+/// A named copy of the items of a list type, that new list types can be created from,
+/// e.g. a packing list.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
+#[non_exhaustive]
+pub struct ListTemplate {
+    pub name: String,
+    pub items: Vec<TemplateItem>,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+impl ListTemplate {
+    pub fn new(
+        name: String,
+        items: Vec<TemplateItem>,
+        created: DateTime<Utc>,
+        updated: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            name,
+            items,
+            created,
+            updated,
+        }
+    }
+}
+
+/// An item of a template, due dates and completion are not part of templates.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct TemplateItem {
+    pub item_name: String,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl TemplateItem {
+    pub fn new(
+        item_name: String,
+        quantity: Option<f64>,
+        unit: Option<String>,
+        notes: Option<String>,
+    ) -> Self {
+        Self {
+            item_name,
+            quantity,
+            unit,
+            notes,
+        }
+    }
+}
+
+/// A file attached to a list item, like a receipt or a photo.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
+#[non_exhaustive]
+pub struct Attachment {
+    pub id: Uuid,
+    pub list_item_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: u64,
+    pub created: DateTime<Utc>,
+    /// A signed url that the file can be downloaded from until `url_expires_at`,
+    /// without any other authorization. Not set if the server has no key to sign urls with.
+    pub download_url: Option<String>,
+    pub url_expires_at: Option<DateTime<Utc>>,
+}
+
+impl Attachment {
+    /// The types of files that can be attached.
+    pub const ALLOWED_CONTENT_TYPES: [&'static str; 7] = [
+        "image/jpeg",
+        "image/png",
+        "image/gif",
+        "image/webp",
+        "image/heic",
+        "application/pdf",
+        "text/plain",
+    ];
+
+    pub fn new(
+        id: Uuid,
+        list_item_id: Uuid,
+        file_name: String,
+        content_type: String,
+        size_bytes: u64,
+        created: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id,
+            list_item_id,
+            file_name,
+            content_type,
+            size_bytes,
+            created,
+            download_url: None,
+            url_expires_at: None,
+        }
+    }
+
+    pub fn with_download_url(mut self, download_url: String, expires_at: DateTime<Utc>) -> Self {
+        self.download_url = Some(download_url);
+        self.url_expires_at = Some(expires_at);
+        self
+    }
+
+    /// Whether files of `content_type` can be attached, ignoring parameters like `; charset=utf-8`.
+    pub fn is_allowed_content_type(content_type: &str) -> bool {
+        let mime_type = content_type.split(';').next().unwrap_or_default().trim();
+        Self::ALLOWED_CONTENT_TYPES
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(mime_type))
+    }
+}
+
+/// A format that list types can be exported to and imported from.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ListFormat {
+    Csv,
+    Json,
+    /// A checklist of `- [ ] item` lines, without the details of the items.
+    Markdown,
+}
+
+/// The access a user has been given to a list type owned by someone else.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ListPermission {
+    Read,
+    Write,
+}
+
+impl ListPermission {
+    /// Write access also grants read access.
+    pub fn allows(self, required: ListPermission) -> bool {
+        match self {
+            ListPermission::Write => true,
+            ListPermission::Read => required == ListPermission::Read,
+        }
+    }
+}
+
+impl FromStr for ListPermission {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(ListPermission::Read),
+            "write" => Ok(ListPermission::Write),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for ListPermission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            ListPermission::Read => "read",
+            ListPermission::Write => "write",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// A list type that another user has shared with the caller.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
+#[non_exhaustive]
+pub struct SharedListType {
+    pub owner_id: String,
+    pub list_type: String,
+    pub permission: ListPermission,
+}
+
+impl SharedListType {
+    pub fn new(owner_id: String, list_type: String, permission: ListPermission) -> Self {
+        Self {
+            owner_id,
+            list_type,
+            permission,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn next_occurrence() {
+        // a wednesday
+        let completed_on = date(2022, 1, 26);
+
+        assert_eq!(
+            Recurrence::Daily.next_occurrence(completed_on),
+            date(2022, 1, 27)
+        );
+
+        let weekly = Recurrence::Weekly {
+            weekdays: vec![Weekday::Mon, Weekday::Wed],
+        };
+        assert_eq!(weekly.next_occurrence(completed_on), date(2022, 1, 31));
+        assert_eq!(weekly.next_occurrence(date(2022, 1, 31)), date(2022, 2, 2));
+
+        let monthly = Recurrence::Monthly { day: 31 };
+        assert_eq!(monthly.next_occurrence(completed_on), date(2022, 1, 31));
+        assert_eq!(
+            monthly.next_occurrence(date(2022, 1, 31)),
+            date(2022, 2, 28)
+        );
+        assert_eq!(
+            monthly.next_occurrence(date(2022, 12, 31)),
+            date(2023, 1, 31)
+        );
+
+        let after_completion = Recurrence::AfterCompletion { days: 10 };
+        assert_eq!(
+            after_completion.next_occurrence(completed_on),
+            date(2022, 2, 5)
+        );
+    }
+
+    #[test]
+    fn allowed_attachment_content_types() {
+        assert!(Attachment::is_allowed_content_type("image/jpeg"));
+        assert!(Attachment::is_allowed_content_type("Application/PDF"));
+        assert!(Attachment::is_allowed_content_type(
+            "text/plain; charset=utf-8"
+        ));
+        assert!(!Attachment::is_allowed_content_type("text/html"));
+        assert!(!Attachment::is_allowed_content_type("image/svg+xml"));
+        assert!(!Attachment::is_allowed_content_type(""));
+    }
+
+    #[test]
+    fn recurrence_format() {
+        let weekly: Recurrence =
+            serde_json::from_str(r#"{"frequency": "weekly", "weekdays": ["Mon", "thursday"]}"#)
+                .unwrap();
+        assert_eq!(
+            weekly,
+            Recurrence::Weekly {
+                weekdays: vec![Weekday::Mon, Weekday::Thu]
+            }
+        );
+
+        let after_completion = Recurrence::AfterCompletion { days: 3 };
+        assert_eq!(
+            serde_json::to_string(&after_completion).unwrap(),
+            r#"{"frequency":"after_completion","days":3}"#
+        );
+        assert!(!Recurrence::Weekly { weekdays: vec![] }.is_valid());
+        assert!(!Recurrence::Monthly { day: 0 }.is_valid());
+    }
+
+    #[test]
+    fn list_permissions() {
+        for permission in [ListPermission::Read, ListPermission::Write] {
+            assert_eq!(
+                ListPermission::from_str(&permission.to_string()),
+                Ok(permission)
+            );
+            assert!(permission.allows(ListPermission::Read));
+        }
+        assert!(ListPermission::Write.allows(ListPermission::Write));
+        assert!(!ListPermission::Read.allows(ListPermission::Write));
+        assert!(ListPermission::from_str("admin").is_err());
+    }
+
+    #[test]
+    fn deleted_list_items_are_flattened() {
+        let list_item = ListItem::new(
+            Uuid::nil(),
+            "groceries".to_owned(),
+            "milk".to_owned(),
+            2,
+            ListItemDetails::new(Some(1.0), None, None, None),
+            None,
+        );
+        let deleted_at = Utc.timestamp_opt(1_646_000_000, 0).unwrap();
+        let value = serde_json::to_value(DeletedListItem::new(list_item, deleted_at)).unwrap();
+
+        assert_eq!(value["item_name"], "milk");
+        assert_eq!(value["version"], 2);
+        assert_eq!(value["deleted_at"], "2022-02-27T22:13:20Z");
+
+        let deleted = serde_json::from_value::<DeletedListItem>(value).unwrap();
+        assert_eq!(deleted.list_item.item_name, "milk");
+        assert_eq!(deleted.deleted_at, deleted_at);
+    }
+
+    #[test]
+    fn list_item_versions_are_flattened() {
+        let list_item = ListItem::new(
+            Uuid::nil(),
+            "groceries".to_owned(),
+            "milk".to_owned(),
+            3,
+            ListItemDetails::new(None, None, Some("oat".to_owned()), None),
+            None,
+        );
+        let changed_at = Utc.timestamp_opt(1_646_000_000, 0).unwrap();
+        let version = ListItemVersion::new(list_item, None, changed_at, None);
+        let value = serde_json::to_value(version).unwrap();
+
+        assert_eq!(value["item_name"], "milk");
+        assert_eq!(value["version"], 3);
+        assert_eq!(value["actor_id"], serde_json::Value::Null);
+        assert_eq!(value["changed_at"], "2022-02-27T22:13:20Z");
+        assert_eq!(value["deleted_at"], serde_json::Value::Null);
+
+        let version = serde_json::from_value::<ListItemVersion>(value).unwrap();
+        assert_eq!(version.list_item.version, 3);
+        assert_eq!(version.changed_at, changed_at);
+    }
 }
@@ -1,4 +1,6 @@
+use super::ListItemDetails;
 use crate::JsonRpcRequest;
+use chrono::NaiveDate;
 use std::{
     convert::{TryFrom, TryInto},
     error::Error,
@@ -13,16 +15,24 @@ pub struct Params {
     pub id: Option<Uuid>,
     pub list_type: String,
     pub item_name: String,
+    /// The owner of the list type, when adding to a list type shared with the caller.
+    /// Defaults to the caller.
+    pub owner_id: Option<String>,
+    #[serde(flatten)]
+    pub details: ListItemDetails,
 }
 
 impl Params {
     /// ## Error
     /// * If `list_type` is empty or whitespace.
     /// * If `list_name` is empty or whitespace.
+    /// * If `details` has a quantity that isn't positive.
     pub fn new(
         id: Option<Uuid>,
         list_type: String,
         item_name: String,
+        owner_id: Option<String>,
+        details: ListItemDetails,
     ) -> Result<Self, InvalidParams> {
         use InvalidParams::*;
 
@@ -36,10 +46,16 @@ impl Params {
             return Err(InvalidItemName);
         }
 
+        if !details.has_valid_quantity() {
+            return Err(InvalidQuantity);
+        }
+
         Ok(Self {
             id,
             list_type: list_type_trimmed.to_owned(),
             item_name: item_name_trimmed.to_owned(),
+            owner_id,
+            details,
         })
     }
 }
@@ -59,7 +75,18 @@ impl TryFrom<ParamsBuilder> for Params {
     type Error = InvalidParams;
 
     fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
-        Self::new(builder.id, builder.list_type, builder.item_name)
+        Self::new(
+            builder.id,
+            builder.list_type,
+            builder.item_name,
+            builder.owner_id,
+            ListItemDetails::new(
+                builder.quantity,
+                builder.unit,
+                builder.notes,
+                builder.due_date,
+            ),
+        )
     }
 }
 
@@ -68,6 +95,11 @@ struct ParamsBuilder {
     id: Option<Uuid>,
     list_type: String,
     item_name: String,
+    owner_id: Option<String>,
+    quantity: Option<f64>,
+    unit: Option<String>,
+    notes: Option<String>,
+    due_date: Option<NaiveDate>,
 }
 
 #[derive(Debug)]
@@ -75,6 +107,7 @@ pub enum InvalidParams {
     InvalidFormat(serde_json::Error),
     InvalidListType,
     InvalidItemName,
+    InvalidQuantity,
 }
 
 impl Error for InvalidParams {}
@@ -87,6 +120,10 @@ impl Display for InvalidParams {
             }
             InvalidParams::InvalidListType => crate::generic_invalid_value_message("list_type"),
             InvalidParams::InvalidItemName => crate::generic_invalid_value_message("item_name"),
+            InvalidParams::InvalidQuantity => crate::invalid_value_because_message(
+                "quantity",
+                "must be a positive number".to_owned(),
+            ),
         };
 
         write!(f, "{}", output)
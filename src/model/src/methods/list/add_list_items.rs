@@ -0,0 +1,195 @@
+use super::{BulkItemResult, ListItemDetails};
+use crate::JsonRpcRequest;
+use chrono::NaiveDate;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+use uuid::Uuid;
+
+const MIN_ITEMS: usize = 1;
+const MAX_ITEMS: usize = 500;
+
+/// Add several items to a list type, either all of them or none of them.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub list_type: String,
+    /// The owner of the list type, when adding to a list type shared with the caller.
+    /// Defaults to the caller.
+    pub owner_id: Option<String>,
+    pub items: Vec<NewListItem>,
+}
+
+/// One of the items of `add_list_items`, see `add_list_item`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct NewListItem {
+    pub id: Option<Uuid>,
+    pub item_name: String,
+    #[serde(flatten)]
+    pub details: ListItemDetails,
+}
+
+impl NewListItem {
+    pub fn new(id: Option<Uuid>, item_name: String, details: ListItemDetails) -> Self {
+        Self {
+            id,
+            item_name,
+            details,
+        }
+    }
+}
+
+impl Params {
+    /// ## Error
+    /// * If `list_type` is empty or whitespace.
+    /// * If `items` is empty or has more than 500 items.
+    /// * If the `item_name` of an item is empty or whitespace.
+    /// * If the `details` of an item has a quantity that isn't positive.
+    pub fn new(
+        list_type: String,
+        owner_id: Option<String>,
+        items: Vec<NewListItem>,
+    ) -> Result<Self, InvalidParams> {
+        use InvalidParams::*;
+
+        let list_type_trimmed = list_type.trim();
+        if list_type_trimmed.is_empty() {
+            return Err(InvalidListType);
+        }
+
+        if !(MIN_ITEMS..=MAX_ITEMS).contains(&items.len()) {
+            return Err(InvalidItemCount);
+        }
+
+        let mut trimmed_items = Vec::with_capacity(items.len());
+        for (index, item) in items.into_iter().enumerate() {
+            let item_name_trimmed = item.item_name.trim();
+            if item_name_trimmed.is_empty() {
+                return Err(InvalidItemName(index));
+            }
+
+            if !item.details.has_valid_quantity() {
+                return Err(InvalidQuantity(index));
+            }
+
+            trimmed_items.push(NewListItem {
+                item_name: item_name_trimmed.to_owned(),
+                ..item
+            });
+        }
+
+        Ok(Self {
+            list_type: list_type_trimmed.to_owned(),
+            owner_id,
+            items: trimmed_items,
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        let items = builder
+            .items
+            .into_iter()
+            .map(|item| {
+                NewListItem::new(
+                    item.id,
+                    item.item_name,
+                    ListItemDetails::new(item.quantity, item.unit, item.notes, item.due_date),
+                )
+            })
+            .collect();
+
+        Self::new(builder.list_type, builder.owner_id, items)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    list_type: String,
+    owner_id: Option<String>,
+    items: Vec<NewListItemBuilder>,
+}
+
+#[derive(serde::Deserialize)]
+struct NewListItemBuilder {
+    id: Option<Uuid>,
+    item_name: String,
+    quantity: Option<f64>,
+    unit: Option<String>,
+    notes: Option<String>,
+    due_date: Option<NaiveDate>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidListType,
+    InvalidItemCount,
+    /// The index of the item with an invalid name.
+    InvalidItemName(usize),
+    /// The index of the item with an invalid quantity.
+    InvalidQuantity(usize),
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidListType => crate::generic_invalid_value_message("list_type"),
+            InvalidParams::InvalidItemCount => format!(
+                "invalid number of items, should be integer in [{}, {}]",
+                MIN_ITEMS, MAX_ITEMS
+            ),
+            InvalidParams::InvalidItemName(index) => {
+                crate::generic_invalid_value_message(&format!("items[{}].item_name", index))
+            }
+            InvalidParams::InvalidQuantity(index) => crate::invalid_value_because_message(
+                &format!("items[{}].quantity", index),
+                "must be a positive number".to_owned(),
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+/// `success` is only set if all of the items were added,
+/// otherwise nothing was added and `results` tells why.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    /// In the same order as the items of the request.
+    pub results: Vec<BulkItemResult>,
+}
+
+impl MethodResult {
+    pub fn new(results: Vec<BulkItemResult>) -> Self {
+        Self {
+            success: results.iter().all(|result| result.success),
+            results,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use super::BulkItemResult;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+use uuid::Uuid;
+
+const MIN_ITEMS: usize = 1;
+const MAX_ITEMS: usize = 500;
+
+/// Move several list items to the trash, either all of them or none of them.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub ids: Vec<Uuid>,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `ids` is empty or has more than 500 ids.
+    /// * If `ids` contains the same id more than once.
+    pub fn new(ids: Vec<Uuid>) -> Result<Self, InvalidParams> {
+        if !(MIN_ITEMS..=MAX_ITEMS).contains(&ids.len()) {
+            return Err(InvalidParams::InvalidItemCount);
+        }
+
+        for (index, id) in ids.iter().enumerate() {
+            if ids[..index].contains(id) {
+                return Err(InvalidParams::DuplicateId(*id));
+            }
+        }
+
+        Ok(Self { ids })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.ids)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    ids: Vec<Uuid>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidItemCount,
+    DuplicateId(Uuid),
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidItemCount => format!(
+                "invalid number of ids, should be integer in [{}, {}]",
+                MIN_ITEMS, MAX_ITEMS
+            ),
+            InvalidParams::DuplicateId(id) => {
+                crate::invalid_value_because_message("ids", format!("'{}' is repeated", id))
+            }
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+/// `success` is only set if all of the items were deleted,
+/// otherwise nothing was deleted and `results` tells why.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    /// In the same order as `ids` of the request.
+    pub results: Vec<BulkItemResult>,
+}
+
+impl MethodResult {
+    pub fn new(results: Vec<BulkItemResult>) -> Self {
+        Self {
+            success: results.iter().all(|result| result.success),
+            results,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let ids: Vec<_> = (0..MAX_ITEMS as u128).map(Uuid::from_u128).collect();
+        assert!(Params::new(ids[..MIN_ITEMS].to_vec()).is_ok());
+        assert!(Params::new(ids.clone()).is_ok());
+
+        assert!(matches!(
+            Params::new(Vec::new()),
+            Err(InvalidParams::InvalidItemCount)
+        ));
+        let mut too_many = ids.clone();
+        too_many.push(Uuid::from_u128(MAX_ITEMS as u128));
+        assert!(matches!(
+            Params::new(too_many),
+            Err(InvalidParams::InvalidItemCount)
+        ));
+        assert!(matches!(
+            Params::new(vec![ids[0], ids[1], ids[0]]),
+            Err(InvalidParams::DuplicateId(id)) if id == ids[0]
+        ));
+    }
+}
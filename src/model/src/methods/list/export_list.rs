@@ -0,0 +1,100 @@
+use super::ListFormat;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+/// Export the items of a list type that are not in the trash, in their manual order.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub list_type: String,
+    /// The owner of the list type, when exporting a list type shared with the caller.
+    /// Defaults to the caller.
+    pub owner_id: Option<String>,
+    pub format: ListFormat,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `list_type` is empty or whitespace.
+    pub fn new(
+        list_type: String,
+        owner_id: Option<String>,
+        format: ListFormat,
+    ) -> Result<Self, InvalidParams> {
+        let list_type_trimmed = list_type.trim();
+        if list_type_trimmed.is_empty() {
+            return Err(InvalidParams::InvalidListType);
+        }
+
+        Ok(Self {
+            list_type: list_type_trimmed.to_owned(),
+            owner_id,
+            format,
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.list_type, builder.owner_id, builder.format)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    list_type: String,
+    owner_id: Option<String>,
+    format: ListFormat,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidListType,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidListType => crate::generic_invalid_value_message("list_type"),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub format: ListFormat,
+    pub content: String,
+}
+
+impl MethodResult {
+    pub fn new(format: ListFormat, content: String) -> Self {
+        Self { format, content }
+    }
+}
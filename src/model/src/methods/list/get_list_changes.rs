@@ -0,0 +1,133 @@
+use super::ListItemChange;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+const MIN_LIMIT: u32 = 1;
+const MAX_LIMIT: u32 = 1000;
+const DEFAULT_LIMIT: u32 = 500;
+
+/// Get the changes of list items after `since_revision`, oldest first.
+///
+/// Pass the `revision` of the result as `since_revision` in the next call
+/// to continue where the previous call left off.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub since_revision: i64,
+    /// The owner of the list types, when syncing list types shared with the caller.
+    /// Defaults to the caller.
+    pub owner_id: Option<String>,
+    pub limit: u32,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `since_revision` is negative.
+    /// * If `limit` is outside the range (1..=1000).
+    pub fn new(
+        since_revision: Option<i64>,
+        owner_id: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Self, InvalidParams> {
+        let since_revision = match since_revision {
+            Some(revision) if revision >= 0 => revision,
+            None => 0,
+            Some(_invalid) => return Err(InvalidParams::InvalidSinceRevision),
+        };
+
+        let limit = match limit {
+            Some(limit) if (MIN_LIMIT..=MAX_LIMIT).contains(&limit) => limit,
+            None => DEFAULT_LIMIT,
+            Some(_invalid) => return Err(InvalidParams::InvalidLimit),
+        };
+
+        Ok(Self {
+            since_revision,
+            owner_id,
+            limit,
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.since_revision, builder.owner_id, builder.limit)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    since_revision: Option<i64>,
+    owner_id: Option<String>,
+    limit: Option<u32>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidSinceRevision,
+    InvalidLimit,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidSinceRevision => crate::invalid_value_because_message(
+                "since_revision",
+                "must not be negative".to_owned(),
+            ),
+            InvalidParams::InvalidLimit => format!(
+                "invalid limit, should be integer in [{}, {}]",
+                MIN_LIMIT, MAX_LIMIT
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub changes: Vec<ListItemChange>,
+    /// The revision of the last change, or `since_revision` if there are no changes.
+    pub revision: i64,
+    /// `true` if there are more changes after `revision`.
+    pub has_more: bool,
+}
+
+impl MethodResult {
+    pub fn new(changes: Vec<ListItemChange>, since_revision: i64, has_more: bool) -> Self {
+        let revision = changes
+            .last()
+            .map_or(since_revision, ListItemChange::revision);
+        Self {
+            changes,
+            revision,
+            has_more,
+        }
+    }
+}
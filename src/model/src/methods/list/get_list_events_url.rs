@@ -0,0 +1,121 @@
+use crate::JsonRpcRequest;
+use chrono::{DateTime, Utc};
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+/// Get a short-lived signed url to subscribe to the changes of a list type as server-sent events,
+/// for clients like browsers that can't set the `Authorization` header of the subscription.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub list_type: String,
+    /// The owner of the list type, when subscribing to a list type shared with the caller.
+    /// Defaults to the caller.
+    pub owner_id: Option<String>,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `list_type` is empty or whitespace.
+    pub fn new(list_type: String, owner_id: Option<String>) -> Result<Self, InvalidParams> {
+        let trimmed = list_type.trim();
+        if trimmed.is_empty() {
+            Err(InvalidParams::ListTypeEmptyOrWhitespace)
+        } else {
+            Ok(Self {
+                list_type: trimmed.to_owned(),
+                owner_id,
+            })
+        }
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.list_type, builder.owner_id)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    list_type: String,
+    owner_id: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    ListTypeEmptyOrWhitespace,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::ListTypeEmptyOrWhitespace => {
+                crate::generic_invalid_value_message("list_type")
+            }
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    /// The url to subscribe to, relative to the server. It can only be connected to
+    /// until `url_expires_at`, but the subscription stays open after that.
+    pub url: String,
+    pub url_expires_at: DateTime<Utc>,
+}
+
+impl MethodResult {
+    pub fn new(url: String, url_expires_at: DateTime<Utc>) -> Self {
+        Self {
+            url,
+            url_expires_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params = serde_json::from_str::<Params>(r#"{ "list_type": " groceries " }"#).unwrap();
+        assert_eq!(params.list_type, "groceries");
+        assert_eq!(params.owner_id, None);
+
+        let params =
+            serde_json::from_str::<Params>(r#"{ "list_type": "groceries", "owner_id": "owner" }"#)
+                .unwrap();
+        assert_eq!(params.owner_id.as_deref(), Some("owner"));
+
+        assert!(serde_json::from_str::<Params>(r#"{ "list_type": "" }"#).is_err());
+        assert!(serde_json::from_str::<Params>(r#"{}"#).is_err());
+    }
+}
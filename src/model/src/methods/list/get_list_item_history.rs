@@ -0,0 +1,106 @@
+use super::ListItemVersion;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+use uuid::Uuid;
+
+/// Get every version of a list item, with who changed it and when, newest first.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub id: Uuid,
+}
+
+impl Params {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    id: Uuid,
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Ok(Self::new(builder.id))
+    }
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    pub history: Vec<ListItemVersion>,
+}
+
+impl MethodResult {
+    pub fn found(history: Vec<ListItemVersion>) -> Self {
+        Self {
+            success: true,
+            history,
+        }
+    }
+
+    pub fn missing() -> Self {
+        Self {
+            success: false,
+            history: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params =
+            serde_json::from_str::<Params>(r#"{ "id": "6b0b3c5e-5d3c-4b8e-9a1e-2f3c4d5e6f70" }"#)
+                .unwrap();
+        assert_eq!(
+            params.id.to_string(),
+            "6b0b3c5e-5d3c-4b8e-9a1e-2f3c4d5e6f70"
+        );
+
+        assert!(serde_json::from_str::<Params>(r#"{ "id": "not an id" }"#).is_err());
+        assert!(serde_json::from_str::<Params>(r#"{}"#).is_err());
+    }
+}
@@ -11,18 +11,22 @@ use std::{
 #[non_exhaustive]
 pub struct Params {
     pub list_type: String,
+    /// The owner of the list type, when reading a list type shared with the caller.
+    /// Defaults to the caller.
+    pub owner_id: Option<String>,
 }
 
 impl Params {
     /// ## Error
     /// * If `list_type` is empty or whitespace.
-    pub fn new(list_type: String) -> Result<Self, InvalidParams> {
+    pub fn new(list_type: String, owner_id: Option<String>) -> Result<Self, InvalidParams> {
         let trimmed = list_type.trim();
         if trimmed.is_empty() {
             Err(InvalidParams::ListTypeEmptyOrWhitespace)
         } else {
             Ok(Self {
                 list_type: trimmed.to_owned(),
+                owner_id,
             })
         }
     }
@@ -43,13 +47,14 @@ impl TryFrom<ParamsBuilder> for Params {
     type Error = InvalidParams;
 
     fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
-        Self::new(builder.list_type)
+        Self::new(builder.list_type, builder.owner_id)
     }
 }
 
 #[derive(serde::Deserialize)]
 struct ParamsBuilder {
     list_type: String,
+    owner_id: Option<String>,
 }
 
 #[derive(Debug)]
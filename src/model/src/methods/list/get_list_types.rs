@@ -1,3 +1,4 @@
+use super::SharedListType;
 use crate::JsonRpcRequest;
 use std::{
     convert::{TryFrom, TryInto},
@@ -60,11 +61,17 @@ impl Display for InvalidParams {
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[non_exhaustive]
 pub struct MethodResult {
+    /// The list types owned by the caller.
     pub list_types: Vec<String>,
+    /// The list types that other users have shared with the caller.
+    pub shared_list_types: Vec<SharedListType>,
 }
 
 impl MethodResult {
-    pub fn new(list_types: Vec<String>) -> Self {
-        Self { list_types }
+    pub fn new(list_types: Vec<String>, shared_list_types: Vec<SharedListType>) -> Self {
+        Self {
+            list_types,
+            shared_list_types,
+        }
     }
 }
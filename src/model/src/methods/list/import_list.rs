@@ -0,0 +1,198 @@
+use super::ListFormat;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+const MAX_CONTENT_LENGTH: usize = 1024 * 1024;
+
+/// Import items into a list type from the content of an exported list.
+///
+/// Items with the same name (ignoring case) as an existing item, or as an earlier line,
+/// are reported as duplicates and skipped.
+///
+/// Nothing is imported if any line is invalid, which is an invalid params error with the outcome
+/// of every line as `lines` of its `data`. A dry run reports invalid lines in its result instead.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub list_type: String,
+    /// The owner of the list type, when importing into a list type shared with the caller.
+    /// Defaults to the caller.
+    pub owner_id: Option<String>,
+    pub format: ListFormat,
+    pub content: String,
+    /// Only report what would be imported, without importing anything.
+    pub dry_run: bool,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `list_type` is empty or whitespace.
+    /// * If `content` is empty or whitespace, or longer than 1 MiB.
+    pub fn new(
+        list_type: String,
+        owner_id: Option<String>,
+        format: ListFormat,
+        content: String,
+        dry_run: bool,
+    ) -> Result<Self, InvalidParams> {
+        let list_type_trimmed = list_type.trim();
+        if list_type_trimmed.is_empty() {
+            return Err(InvalidParams::InvalidListType);
+        }
+
+        if content.trim().is_empty() || content.len() > MAX_CONTENT_LENGTH {
+            return Err(InvalidParams::InvalidContent);
+        }
+
+        Ok(Self {
+            list_type: list_type_trimmed.to_owned(),
+            owner_id,
+            format,
+            content,
+            dry_run,
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(
+            builder.list_type,
+            builder.owner_id,
+            builder.format,
+            builder.content,
+            builder.dry_run.unwrap_or(false),
+        )
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    list_type: String,
+    owner_id: Option<String>,
+    format: ListFormat,
+    content: String,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidListType,
+    InvalidContent,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidListType => crate::generic_invalid_value_message("list_type"),
+            InvalidParams::InvalidContent => crate::invalid_value_because_message(
+                "content",
+                format!(
+                    "should be non-empty and at most {} bytes",
+                    MAX_CONTENT_LENGTH
+                ),
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportLineStatus {
+    /// The item is imported, or would be imported in a dry run.
+    Ok,
+    Duplicate,
+    Invalid,
+}
+
+/// The outcome for one line of the imported content.
+///
+/// For JSON, `line` is the position of the item in the array (starting at 1).
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct ImportLine {
+    pub line: usize,
+    pub item_name: Option<String>,
+    pub status: ImportLineStatus,
+    /// Why the line is invalid.
+    pub error: Option<String>,
+}
+
+impl ImportLine {
+    pub fn ok(line: usize, item_name: String) -> Self {
+        Self {
+            line,
+            item_name: Some(item_name),
+            status: ImportLineStatus::Ok,
+            error: None,
+        }
+    }
+
+    pub fn duplicate(line: usize, item_name: String) -> Self {
+        Self {
+            line,
+            item_name: Some(item_name),
+            status: ImportLineStatus::Duplicate,
+            error: None,
+        }
+    }
+
+    pub fn invalid(line: usize, error: String) -> Self {
+        Self {
+            line,
+            item_name: None,
+            status: ImportLineStatus::Invalid,
+            error: Some(error),
+        }
+    }
+}
+
+/// `success` is set if no line is invalid, which can only happen in a dry run.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    pub dry_run: bool,
+    /// The number of items that were imported, always 0 in a dry run.
+    pub imported: usize,
+    pub lines: Vec<ImportLine>,
+}
+
+impl MethodResult {
+    pub fn new(dry_run: bool, imported: usize, lines: Vec<ImportLine>) -> Self {
+        Self {
+            success: lines
+                .iter()
+                .all(|line| line.status != ImportLineStatus::Invalid),
+            dry_run,
+            imported,
+            lines,
+        }
+    }
+}
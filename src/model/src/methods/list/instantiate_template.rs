@@ -0,0 +1,156 @@
+use super::ListItem;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+/// Create a new list type of the caller with the items of one of the caller's templates,
+/// either with all of the items or not at all.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    /// The name of the template.
+    pub name: String,
+    /// The list type to create, it can't already have items.
+    pub list_type: String,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `name` is empty or whitespace.
+    /// * If `list_type` is empty or whitespace.
+    pub fn new(name: String, list_type: String) -> Result<Self, InvalidParams> {
+        let name = name.trim().to_owned();
+        if name.is_empty() {
+            return Err(InvalidParams::EmptyName);
+        }
+
+        let list_type = list_type.trim().to_owned();
+        if list_type.is_empty() {
+            return Err(InvalidParams::EmptyListType);
+        }
+
+        Ok(Self { name, list_type })
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    name: String,
+    list_type: String,
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.name, builder.list_type)
+    }
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    EmptyName,
+    EmptyListType,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::EmptyName => crate::generic_invalid_value_message("name"),
+            InvalidParams::EmptyListType => crate::generic_invalid_value_message("list_type"),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstantiationError {
+    MissingTemplate,
+    /// The list type already has items.
+    ListTypeExists,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    /// The items of the created list type, in order.
+    pub list_items: Vec<ListItem>,
+    pub error: Option<InstantiationError>,
+}
+
+impl MethodResult {
+    pub fn created(list_items: Vec<ListItem>) -> Self {
+        Self {
+            success: true,
+            list_items,
+            error: None,
+        }
+    }
+
+    pub fn failed(error: InstantiationError) -> Self {
+        Self {
+            success: false,
+            list_items: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params =
+            serde_json::from_str::<Params>(r#"{ "name": " weekly ", "list_type": " groceries " }"#)
+                .unwrap();
+        assert_eq!(params.name, "weekly");
+        assert_eq!(params.list_type, "groceries");
+
+        assert!(
+            serde_json::from_str::<Params>(r#"{ "name": " ", "list_type": "groceries" }"#).is_err()
+        );
+        assert!(
+            serde_json::from_str::<Params>(r#"{ "name": "weekly", "list_type": "" }"#).is_err()
+        );
+        assert!(serde_json::from_str::<Params>(r#"{ "name": "weekly" }"#).is_err());
+    }
+
+    #[test]
+    fn failures_have_no_items() {
+        let value =
+            serde_json::to_value(MethodResult::failed(InstantiationError::ListTypeExists)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "success": false, "list_items": [], "error": "list_type_exists" })
+        );
+
+        let value = serde_json::to_value(MethodResult::created(Vec::new())).unwrap();
+        assert_eq!(value["success"], true);
+        assert_eq!(value["error"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+/// Move all items of one of the caller's list types to the end of another,
+/// `target` stays shared with the same users and `source` is no longer shared.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub source: String,
+    pub target: String,
+    pub on_duplicate: DuplicateStrategy,
+}
+
+/// What to do with items that have the same name (ignoring case) in both list types.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateStrategy {
+    #[default]
+    KeepBoth,
+    /// Move the duplicates from `source` to the trash.
+    KeepTarget,
+    /// Move the duplicates in `target` to the trash.
+    KeepSource,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `source` is empty or whitespace.
+    /// * If `target` is empty or whitespace.
+    /// * If `source` and `target` are the same list type.
+    pub fn new(
+        source: String,
+        target: String,
+        on_duplicate: DuplicateStrategy,
+    ) -> Result<Self, InvalidParams> {
+        use InvalidParams::*;
+
+        let source_trimmed = source.trim();
+        if source_trimmed.is_empty() {
+            return Err(InvalidSource);
+        }
+
+        let target_trimmed = target.trim();
+        if target_trimmed.is_empty() {
+            return Err(InvalidTarget);
+        }
+
+        if source_trimmed == target_trimmed {
+            return Err(SameListType);
+        }
+
+        Ok(Self {
+            source: source_trimmed.to_owned(),
+            target: target_trimmed.to_owned(),
+            on_duplicate,
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(
+            builder.source,
+            builder.target,
+            builder.on_duplicate.unwrap_or_default(),
+        )
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    source: String,
+    target: String,
+    on_duplicate: Option<DuplicateStrategy>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidSource,
+    InvalidTarget,
+    SameListType,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidSource => crate::generic_invalid_value_message("source"),
+            InvalidParams::InvalidTarget => crate::generic_invalid_value_message("target"),
+            InvalidParams::SameListType => crate::invalid_value_because_message(
+                "target",
+                "a list type can't be merged into itself".to_owned(),
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    /// The number of items that were moved from `source` to `target`.
+    pub moved: u64,
+    /// The number of duplicates that were moved to the trash.
+    pub discarded: u64,
+}
+
+impl MethodResult {
+    pub fn merged(moved: u64, discarded: u64) -> Self {
+        Self {
+            success: true,
+            moved,
+            discarded,
+        }
+    }
+
+    pub fn missing() -> Self {
+        Self {
+            success: false,
+            moved: 0,
+            discarded: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params =
+            serde_json::from_str::<Params>(r#"{ "source": " groceries ", "target": "shopping" }"#)
+                .unwrap();
+        assert_eq!(params.source, "groceries");
+        assert_eq!(params.on_duplicate, DuplicateStrategy::KeepBoth);
+
+        let params = serde_json::from_str::<Params>(
+            r#"{ "source": "groceries", "target": "shopping", "on_duplicate": "keep_source" }"#,
+        )
+        .unwrap();
+        assert_eq!(params.on_duplicate, DuplicateStrategy::KeepSource);
+
+        let invalids = [
+            r#"{ "source": " ", "target": "shopping" }"#,
+            r#"{ "source": "groceries", "target": "" }"#,
+            r#"{ "source": "groceries", "target": " groceries" }"#,
+            r#"{ "source": "groceries", "target": "shopping", "on_duplicate": "keep_newest" }"#,
+        ];
+        for invalid in &invalids {
+            assert!(
+                serde_json::from_str::<Params>(invalid).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+use uuid::Uuid;
+
+/// Move a list item to directly before or after another item in the same list type.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub id: Uuid,
+    pub before: Option<Uuid>,
+    pub after: Option<Uuid>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Placement {
+    Before,
+    After,
+}
+
+impl Params {
+    /// ## Error
+    /// * If not exactly one of `before` and `after` is set.
+    /// * If `before` or `after` is the item that is moved.
+    pub fn new(id: Uuid, before: Option<Uuid>, after: Option<Uuid>) -> Result<Self, InvalidParams> {
+        let target = match (before, after) {
+            (Some(target), None) | (None, Some(target)) => target,
+            _ => return Err(InvalidParams::NotExactlyOneTarget),
+        };
+
+        if target == id {
+            return Err(InvalidParams::TargetIsMovedItem);
+        }
+
+        Ok(Self { id, before, after })
+    }
+
+    /// The item to move relative to, and on which side of it.
+    pub fn target(&self) -> (Uuid, Placement) {
+        match (self.before, self.after) {
+            (Some(before), _) => (before, Placement::Before),
+            (None, Some(after)) => (after, Placement::After),
+            (None, None) => unreachable!("validated in Params::new"),
+        }
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.id, builder.before, builder.after)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    id: Uuid,
+    before: Option<Uuid>,
+    after: Option<Uuid>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    NotExactlyOneTarget,
+    TargetIsMovedItem,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::NotExactlyOneTarget => {
+                "exactly one of 'before' and 'after' has to be set".to_owned()
+            }
+            InvalidParams::TargetIsMovedItem => crate::invalid_value_because_message(
+                "id",
+                "an item can't be moved relative to itself".to_owned(),
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+}
+
+impl MethodResult {
+    pub fn new(success: bool) -> Self {
+        Self { success }
+    }
+}
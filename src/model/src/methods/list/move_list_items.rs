@@ -0,0 +1,98 @@
+use super::{move_list_item, BulkItemResult};
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+const MIN_MOVES: usize = 1;
+const MAX_MOVES: usize = 500;
+
+/// Apply several moves in order, either all of them or none of them.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    /// Each move is validated like the params of `move_list_item`.
+    pub moves: Vec<move_list_item::Params>,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `moves` is empty or has more than 500 moves.
+    pub fn new(moves: Vec<move_list_item::Params>) -> Result<Self, InvalidParams> {
+        if !(MIN_MOVES..=MAX_MOVES).contains(&moves.len()) {
+            return Err(InvalidParams::InvalidMoveCount);
+        }
+
+        Ok(Self { moves })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.moves)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    moves: Vec<move_list_item::Params>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidMoveCount,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidMoveCount => format!(
+                "invalid number of moves, should be integer in [{}, {}]",
+                MIN_MOVES, MAX_MOVES
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+/// `success` is only set if all of the moves were applied,
+/// otherwise nothing was moved and `results` tells why.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    /// In the same order as `moves` of the request, `id` is the moved item.
+    pub results: Vec<BulkItemResult>,
+}
+
+impl MethodResult {
+    pub fn new(results: Vec<BulkItemResult>) -> Self {
+        Self {
+            success: results.iter().all(|result| result.success),
+            results,
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use super::ListItemDetails;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::{error::Error, fmt::Display};
+
+/// Units that are recognized after a quantity, in English and Swedish.
+const UNITS: [&str; 44] = [
+    "l", "dl", "cl", "ml", "kg", "hg", "g", "mg", "st", "pcs", "pc", "pack", "packs", "paket",
+    "pkt", "förp", "burk", "burkar", "can", "cans", "bottle", "bottles", "flaska", "flaskor",
+    "tsk", "msk", "krm", "tsp", "tbsp", "cup", "cups", "lb", "lbs", "oz", "dozen", "dussin", "box",
+    "boxes", "bag", "bags", "påse", "påsar", "bunch", "knippe",
+];
+
+/// Words that can come before a date, e.g. "by friday" or "till fredag".
+const DATE_PREPOSITIONS: [&str; 9] = [
+    "on", "by", "due", "before", "next", "på", "till", "senast", "nästa",
+];
+
+const WEEKDAYS: [(&str, &str, Weekday); 7] = [
+    ("monday", "måndag", Weekday::Mon),
+    ("tuesday", "tisdag", Weekday::Tue),
+    ("wednesday", "onsdag", Weekday::Wed),
+    ("thursday", "torsdag", Weekday::Thu),
+    ("friday", "fredag", Weekday::Fri),
+    ("saturday", "lördag", Weekday::Sat),
+    ("sunday", "söndag", Weekday::Sun),
+];
+
+/// The furthest ahead that "in 3 days" or "om 2 veckor" can be, in days.
+const MAX_DAYS_AHEAD: i64 = 100 * 366;
+
+/// A list item parsed from a line of text, like "2 l milk #groceries tomorrow".
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ParsedItem {
+    pub item_name: String,
+    /// From a `#tag`, the last one if there are several.
+    pub list_type: Option<String>,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub due_date: Option<NaiveDate>,
+}
+
+impl ParsedItem {
+    pub fn details(&self) -> ListItemDetails {
+        ListItemDetails::new(self.quantity, self.unit.clone(), None, self.due_date)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Nothing was left of the text for the item name.
+    MissingItemName,
+}
+
+impl Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            ParseError::MissingItemName => "no item name left after quantity, list type and date",
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+/// Parses the words of `text` into a list item, the words that aren't
+/// a quantity (with unit), a `#list_type` or a due date make up the item name.
+///
+/// Dates are relative to `today`, and can be written in English or Swedish:
+/// * "today", "tomorrow", "day after tomorrow", "idag", "imorgon", "i övermorgon"
+/// * weekdays like "friday" or "fredag", which mean the next one after today
+/// * "in 3 days", "in 2 weeks", "om 3 dagar", "om 2 veckor", "next week", "nästa vecka"
+/// * ISO dates like "2022-03-04"
+///
+/// Dates can be preceded by words like "on", "by", "på" or "till", e.g. "by friday".
+/// Only the first quantity and the first date are used, later ones are part of the item name.
+pub fn parse(text: &str, today: NaiveDate) -> Result<ParsedItem, ParseError> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let lowercase: Vec<String> = words
+        .iter()
+        .map(|word| word.trim_end_matches(&[',', '.'][..]).to_lowercase())
+        .collect();
+
+    let mut item_name = Vec::new();
+    let mut list_type = None;
+    let mut quantity = None;
+    let mut unit = None;
+    let mut due_date = None;
+
+    let mut index = 0;
+    while index < words.len() {
+        let word = words[index];
+
+        if let Some(tag) = word.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+            list_type = Some(tag.to_owned());
+            index += 1;
+            continue;
+        }
+
+        if due_date.is_none() {
+            if let Some((date, length)) = parse_date(&lowercase[index..], today) {
+                due_date = Some(date);
+                index += length;
+                continue;
+            }
+        }
+
+        if quantity.is_none() {
+            if let Some((amount, amount_unit, length)) = parse_quantity(&lowercase[index..]) {
+                quantity = Some(amount);
+                unit = amount_unit;
+                index += length;
+                continue;
+            }
+        }
+
+        item_name.push(word);
+        index += 1;
+    }
+
+    if item_name.is_empty() {
+        return Err(ParseError::MissingItemName);
+    }
+
+    Ok(ParsedItem {
+        item_name: item_name.join(" "),
+        list_type,
+        quantity,
+        unit,
+        due_date,
+    })
+}
+
+/// A date at the start of `words`, and how many words it is.
+fn parse_date(words: &[String], today: NaiveDate) -> Option<(NaiveDate, usize)> {
+    if let Some(date) = parse_date_without_preposition(words, today) {
+        return Some(date);
+    }
+
+    let first = words.first()?;
+    if DATE_PREPOSITIONS.contains(&first.as_str()) {
+        // "next week" and "nästa vecka" are dates of their own
+        parse_date_without_preposition(&words[1..], today).map(|(date, length)| (date, length + 1))
+    } else {
+        None
+    }
+}
+
+fn parse_date_without_preposition(
+    words: &[String],
+    today: NaiveDate,
+) -> Option<(NaiveDate, usize)> {
+    let word = |index: usize| words.get(index).map(String::as_str);
+
+    let relative = match (word(0)?, word(1), word(2)) {
+        ("day", Some("after"), Some("tomorrow")) => Some((2, 3)),
+        ("today", _, _) | ("idag", _, _) | ("tonight", _, _) | ("ikväll", _, _) => Some((0, 1)),
+        ("i", Some("dag"), _) | ("i", Some("kväll"), _) => Some((0, 2)),
+        ("tomorrow", _, _) | ("imorgon", _, _) | ("imorn", _, _) => Some((1, 1)),
+        ("i", Some("morgon"), _) | ("i", Some("morn"), _) => Some((1, 2)),
+        ("övermorgon", _, _) => Some((2, 1)),
+        ("i", Some("övermorgon"), _) => Some((2, 2)),
+        ("next", Some("week"), _) | ("nästa", Some("vecka"), _) => Some((7, 2)),
+        ("in", Some(count), Some(period)) | ("om", Some(count), Some(period)) => {
+            let count: i64 = count.parse().ok().filter(|count| *count > 0)?;
+            match period {
+                "day" | "days" | "dag" | "dagar" => Some((count, 3)),
+                "week" | "weeks" | "vecka" | "veckor" => Some((count.checked_mul(7)?, 3)),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    if let Some((days, length)) = relative {
+        // a date too far ahead is not a date, which also keeps it from overflowing
+        if days > MAX_DAYS_AHEAD {
+            return None;
+        }
+        return today
+            .checked_add_signed(Duration::days(days))
+            .map(|date| (date, length));
+    }
+
+    let first = word(0)?;
+    if let Some(&(_, _, weekday)) = WEEKDAYS
+        .iter()
+        .find(|(english, swedish, _)| first == *english || first == *swedish)
+    {
+        return Some((next_weekday(today, weekday), 1));
+    }
+
+    NaiveDate::parse_from_str(first, "%Y-%m-%d")
+        .ok()
+        .map(|date| (date, 1))
+}
+
+/// The first `weekday` after `today`, a week from today if it is that weekday.
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days_ahead =
+        (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let days_ahead = if days_ahead == 0 { 7 } else { days_ahead };
+    today + Duration::days(days_ahead as i64)
+}
+
+/// A positive quantity at the start of `words`, with the unit either attached ("2l")
+/// or as the next word ("2 l"), and how many words it is.
+fn parse_quantity(words: &[String]) -> Option<(f64, Option<String>, usize)> {
+    let first = words.first()?;
+    let split = first
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .unwrap_or(first.len());
+    let (number, attached_unit) = first.split_at(split);
+
+    let amount: f64 = number.replace(',', ".").parse().ok()?;
+    if !amount.is_finite() || amount <= 0.0 {
+        return None;
+    }
+
+    if !attached_unit.is_empty() {
+        return if UNITS.contains(&attached_unit) {
+            Some((amount, Some(attached_unit.to_owned()), 1))
+        } else {
+            None
+        };
+    }
+
+    match words.get(1) {
+        Some(unit) if UNITS.contains(&unit.as_str()) => Some((amount, Some(unit.clone()), 2)),
+        _ => Some((amount, None, 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a wednesday
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2022, 3, 2).unwrap()
+    }
+
+    fn date(day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(2022, 3, day)
+    }
+
+    #[test]
+    fn parses_quantity_list_type_and_date() {
+        let parsed = parse("2 l milk #groceries tomorrow", today()).unwrap();
+        assert_eq!(
+            parsed,
+            ParsedItem {
+                item_name: "milk".to_owned(),
+                list_type: Some("groceries".to_owned()),
+                quantity: Some(2.0),
+                unit: Some("l".to_owned()),
+                due_date: date(3),
+            }
+        );
+
+        let parsed = parse("Laktosfri mjölk 1,5l till fredag #handla", today()).unwrap();
+        assert_eq!(parsed.item_name, "Laktosfri mjölk");
+        assert_eq!(parsed.quantity, Some(1.5));
+        assert_eq!(parsed.unit.as_deref(), Some("l"));
+        assert_eq!(parsed.due_date, date(4));
+        assert_eq!(parsed.list_type.as_deref(), Some("handla"));
+
+        let parsed = parse("3 apples", today()).unwrap();
+        assert_eq!(parsed.item_name, "apples");
+        assert_eq!((parsed.quantity, parsed.unit), (Some(3.0), None));
+    }
+
+    #[test]
+    fn parses_english_and_swedish_dates() {
+        let cases = [
+            ("today", date(2)),
+            ("i dag", date(2)),
+            ("imorgon", date(3)),
+            ("i morgon", date(3)),
+            ("day after tomorrow", date(4)),
+            ("i övermorgon", date(4)),
+            ("on wednesday", date(9)),
+            ("på måndag", date(7)),
+            ("in 3 days", date(5)),
+            ("om 2 veckor", date(16)),
+            ("next week", date(9)),
+            ("by 2022-03-20", date(20)),
+        ];
+
+        for (text, expected) in cases.iter() {
+            let parsed = parse(&format!("call mom {}", text), today()).unwrap();
+            assert_eq!(parsed.due_date, *expected, "'{}'", text);
+            assert_eq!(parsed.item_name, "call mom", "'{}'", text);
+        }
+    }
+
+    #[test]
+    fn keeps_words_that_are_not_dates_or_quantities() {
+        let parsed = parse("book on databases", today()).unwrap();
+        assert_eq!(parsed.item_name, "book on databases");
+        assert_eq!(parsed.due_date, None);
+
+        let parsed = parse("7up 0 #", today()).unwrap();
+        assert_eq!(parsed.item_name, "7up 0 #");
+        assert_eq!(parsed.quantity, None);
+
+        assert_eq!(
+            parse("2 kg #groceries tomorrow", today()),
+            Err(ParseError::MissingItemName)
+        );
+    }
+
+    #[test]
+    fn dates_too_far_ahead_are_not_dates() {
+        for text in [
+            "milk in 99999999999 days",
+            "milk in 9999999999999999999 days",
+            "milk om 2000000000000000000 veckor",
+        ] {
+            let parsed = parse(text, today()).unwrap();
+            assert_eq!(parsed.due_date, None, "{}", text);
+        }
+
+        let parsed = parse("passport in 520 weeks", today()).unwrap();
+        assert_eq!(parsed.item_name, "passport");
+        assert!(parsed.due_date.is_some());
+    }
+}
@@ -0,0 +1,185 @@
+use super::{
+    quick_add::{self, ParseError, ParsedItem},
+    ListItem,
+};
+use crate::JsonRpcRequest;
+use chrono::{NaiveDate, Utc};
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+/// Add a list item from a line of text, like "2 l milk #groceries tomorrow", see `quick_add::parse`.
+///
+/// With `dry_run` the item is only parsed, so that it can be previewed.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    /// The parsed item, its list type is set from `list_type` if the text has no `#tag`.
+    pub parsed: ParsedItem,
+    /// The owner of the list type, defaults to the caller.
+    pub owner_id: Option<String>,
+    pub dry_run: bool,
+}
+
+impl Params {
+    pub const MAX_TEXT_LENGTH: usize = 500;
+
+    /// `today` is the date of the caller, relative dates like "tomorrow" are resolved from it.
+    ///
+    /// ## Error
+    /// * If `text` is empty or longer than `MAX_TEXT_LENGTH`
+    /// * If nothing is left of `text` for the item name
+    /// * If `text` has no `#tag` and `list_type` isn't set
+    /// * If the quantity isn't positive
+    pub fn new(
+        text: &str,
+        list_type: Option<String>,
+        owner_id: Option<String>,
+        today: NaiveDate,
+        dry_run: bool,
+    ) -> Result<Self, InvalidParams> {
+        let text = text.trim();
+        if text.is_empty() || text.chars().count() > Self::MAX_TEXT_LENGTH {
+            return Err(InvalidParams::InvalidText);
+        }
+
+        let mut parsed = quick_add::parse(text, today).map_err(InvalidParams::Unparsable)?;
+
+        if parsed.list_type.is_none() {
+            parsed.list_type = list_type
+                .map(|list_type| list_type.trim().to_owned())
+                .filter(|list_type| !list_type.is_empty());
+        }
+        if parsed.list_type.is_none() {
+            return Err(InvalidParams::MissingListType);
+        }
+
+        if !parsed.details().has_valid_quantity() {
+            return Err(InvalidParams::InvalidQuantity);
+        }
+
+        Ok(Self {
+            parsed,
+            owner_id,
+            dry_run,
+        })
+    }
+
+    pub fn list_type(&self) -> &str {
+        self.parsed.list_type.as_deref().unwrap_or_default()
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    text: String,
+    list_type: Option<String>,
+    owner_id: Option<String>,
+    /// Defaults to the current date in UTC.
+    today: Option<NaiveDate>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        let today = builder
+            .today
+            .unwrap_or_else(|| Utc::now().naive_utc().date());
+
+        Self::new(
+            &builder.text,
+            builder.list_type,
+            builder.owner_id,
+            today,
+            builder.dry_run,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidText,
+    Unparsable(ParseError),
+    MissingListType,
+    InvalidQuantity,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidText => crate::invalid_value_because_message(
+                "text",
+                format!(
+                    "should be between 1 and {} characters",
+                    Params::MAX_TEXT_LENGTH
+                ),
+            ),
+            InvalidParams::Unparsable(parse_error) => {
+                crate::invalid_value_because_message("text", parse_error.to_string())
+            }
+            InvalidParams::MissingListType => crate::invalid_value_because_message(
+                "list_type",
+                "required when the text has no #list_type".to_owned(),
+            ),
+            InvalidParams::InvalidQuantity => crate::invalid_value_because_message(
+                "text",
+                "quantity must be a positive number".to_owned(),
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    pub dry_run: bool,
+    /// What the text was parsed into.
+    pub parsed: ParsedItem,
+    /// The added list item, unless it was a dry run.
+    pub list_item: Option<ListItem>,
+}
+
+impl MethodResult {
+    pub fn added(parsed: ParsedItem, list_item: ListItem) -> Self {
+        Self {
+            success: true,
+            dry_run: false,
+            parsed,
+            list_item: Some(list_item),
+        }
+    }
+
+    pub fn preview(parsed: ParsedItem) -> Self {
+        Self {
+            success: true,
+            dry_run: true,
+            parsed,
+            list_item: None,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+/// Create a new secret url for the calendar feed of the caller, the feed has the items
+/// with due dates of all the caller's list types. Any earlier url of the caller stops working.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {}
+
+impl Params {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(_: ParamsBuilder) -> Result<Self, Self::Error> {
+        Ok(Self::new())
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    /// The url of the feed, relative to the server. Anyone with the url can read the feed.
+    pub feed_url: String,
+}
+
+impl MethodResult {
+    pub fn new(feed_url: String) -> Self {
+        Self { feed_url }
+    }
+}
@@ -0,0 +1,129 @@
+use super::ListItem;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+use uuid::Uuid;
+
+/// Set the name, details, completion and recurrence of a list item back to an earlier version,
+/// as listed by `get_list_item_history`.
+///
+/// The revert is a new version of the item, so it can be reverted as well.
+/// The item stays in its current list type, and items in the trash have to be restored first.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub id: Uuid,
+    pub version: i64,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `version` is less than 1
+    pub fn new(id: Uuid, version: i64) -> Result<Self, InvalidParams> {
+        if version < 1 {
+            return Err(InvalidParams::InvalidVersion);
+        }
+
+        Ok(Self { id, version })
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    id: Uuid,
+    version: i64,
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.id, builder.version)
+    }
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidVersion,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidVersion => crate::invalid_value_because_message(
+                "version",
+                "should be a positive integer".to_owned(),
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    /// The reverted list item.
+    pub list_item: Option<ListItem>,
+}
+
+impl MethodResult {
+    pub fn reverted(list_item: ListItem) -> Self {
+        Self {
+            success: true,
+            list_item: Some(list_item),
+        }
+    }
+
+    /// The item, or the version of it, doesn't exist.
+    pub fn missing() -> Self {
+        Self {
+            success: false,
+            list_item: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params = serde_json::from_str::<Params>(
+            r#"{ "id": "6b0b3c5e-5d3c-4b8e-9a1e-2f3c4d5e6f70", "version": 1 }"#,
+        )
+        .unwrap();
+        assert_eq!(params.version, 1);
+
+        for version in ["0", "-1", "1.5", "null"] {
+            let json = format!(
+                r#"{{ "id": "6b0b3c5e-5d3c-4b8e-9a1e-2f3c4d5e6f70", "version": {} }}"#,
+                version
+            );
+            assert!(serde_json::from_str::<Params>(&json).is_err());
+        }
+        assert!(serde_json::from_str::<Params>(r#"{ "id": "1", "version": 1 }"#).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use super::ListTemplate;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+/// Save the items of a list type as a template of the caller, without their due dates and completion.
+///
+/// The list type can be owned by another user, if it is shared with the caller.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub list_type: String,
+    /// The owner of the list type, defaults to the caller.
+    pub owner_id: Option<String>,
+    pub name: String,
+    /// Replace the items of an existing template with the same name.
+    pub overwrite: bool,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `list_type` is empty or whitespace.
+    /// * If `name` is empty or whitespace.
+    pub fn new(
+        list_type: String,
+        owner_id: Option<String>,
+        name: String,
+        overwrite: bool,
+    ) -> Result<Self, InvalidParams> {
+        let list_type = list_type.trim().to_owned();
+        if list_type.is_empty() {
+            return Err(InvalidParams::EmptyListType);
+        }
+
+        let name = name.trim().to_owned();
+        if name.is_empty() {
+            return Err(InvalidParams::EmptyName);
+        }
+
+        Ok(Self {
+            list_type,
+            owner_id,
+            name,
+            overwrite,
+        })
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    list_type: String,
+    owner_id: Option<String>,
+    name: String,
+    #[serde(default)]
+    overwrite: bool,
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(
+            builder.list_type,
+            builder.owner_id,
+            builder.name,
+            builder.overwrite,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    EmptyListType,
+    EmptyName,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::EmptyListType => crate::generic_invalid_value_message("list_type"),
+            InvalidParams::EmptyName => crate::generic_invalid_value_message("name"),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    /// The saved template.
+    pub template: Option<ListTemplate>,
+}
+
+impl MethodResult {
+    pub fn saved(template: ListTemplate) -> Self {
+        Self {
+            success: true,
+            template: Some(template),
+        }
+    }
+
+    /// A template with the same name exists, and `overwrite` wasn't set.
+    pub fn already_exists() -> Self {
+        Self {
+            success: false,
+            template: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params =
+            serde_json::from_str::<Params>(r#"{ "list_type": " groceries ", "name": " weekly " }"#)
+                .unwrap();
+        assert_eq!(params.list_type, "groceries");
+        assert_eq!(params.owner_id, None);
+        assert_eq!(params.name, "weekly");
+        assert!(!params.overwrite);
+
+        let params = serde_json::from_str::<Params>(
+            r#"{ "list_type": "groceries", "owner_id": "owner", "name": "weekly", "overwrite": true }"#,
+        )
+        .unwrap();
+        assert_eq!(params.owner_id.as_deref(), Some("owner"));
+        assert!(params.overwrite);
+
+        assert!(
+            serde_json::from_str::<Params>(r#"{ "list_type": " ", "name": "weekly" }"#).is_err()
+        );
+        assert!(
+            serde_json::from_str::<Params>(r#"{ "list_type": "groceries", "name": "" }"#).is_err()
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use super::ListItemSearchHit;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+const MIN_LIMIT: u32 = 1;
+const MAX_LIMIT: u32 = 100;
+const DEFAULT_LIMIT: u32 = 20;
+
+/// Full-text search of the names and notes of list items, best match first.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    /// Words to search for, `"quoted phrases"`, `or` and `-excluded` words are supported.
+    pub query: String,
+    /// Only search this list type, if set.
+    pub list_type: Option<String>,
+    /// The owner of the list types to search, other users can only search the list types
+    /// that are shared with them. Defaults to the caller.
+    pub owner_id: Option<String>,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `query` is empty or whitespace.
+    /// * If `limit` is outside the range (1..=100).
+    pub fn new(
+        query: String,
+        list_type: Option<String>,
+        owner_id: Option<String>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Self, InvalidParams> {
+        let query_trimmed = query.trim();
+        if query_trimmed.is_empty() {
+            return Err(InvalidParams::InvalidQuery);
+        }
+
+        let limit = match limit {
+            Some(limit) if (MIN_LIMIT..=MAX_LIMIT).contains(&limit) => limit,
+            None => DEFAULT_LIMIT,
+            Some(_invalid) => return Err(InvalidParams::InvalidLimit),
+        };
+
+        Ok(Self {
+            query: query_trimmed.to_owned(),
+            list_type: list_type
+                .map(|list_type| list_type.trim().to_owned())
+                .filter(|list_type| !list_type.is_empty()),
+            owner_id,
+            limit,
+            offset: offset.unwrap_or(0),
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(
+            builder.query,
+            builder.list_type,
+            builder.owner_id,
+            builder.limit,
+            builder.offset,
+        )
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    query: String,
+    list_type: Option<String>,
+    owner_id: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidQuery,
+    InvalidLimit,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidQuery => crate::generic_invalid_value_message("query"),
+            InvalidParams::InvalidLimit => format!(
+                "invalid limit, should be integer in [{}, {}]",
+                MIN_LIMIT, MAX_LIMIT
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub hits: Vec<ListItemSearchHit>,
+    /// Set if there are more hits after `offset + limit`.
+    pub has_more: bool,
+}
+
+impl MethodResult {
+    pub fn new(hits: Vec<ListItemSearchHit>, has_more: bool) -> Self {
+        Self { hits, has_more }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params = serde_json::from_str::<Params>(r#"{ "query": " milk -oat " }"#).unwrap();
+        assert_eq!(params.query, "milk -oat");
+        assert_eq!(params.list_type, None);
+        assert_eq!(params.limit, DEFAULT_LIMIT);
+        assert_eq!(params.offset, 0);
+
+        let params = serde_json::from_str::<Params>(
+            r#"{ "query": "\"whole grain\"", "list_type": " ", "limit": 100, "offset": 20 }"#,
+        )
+        .unwrap();
+        assert_eq!(params.query, "\"whole grain\"");
+        assert_eq!(params.list_type, None);
+        assert_eq!(params.offset, 20);
+
+        let invalids = [
+            Params::new(" ".to_owned(), None, None, None, None),
+            Params::new("milk".to_owned(), None, None, Some(MIN_LIMIT - 1), None),
+            Params::new("milk".to_owned(), None, None, Some(MAX_LIMIT + 1), None),
+        ];
+        for invalid in &invalids {
+            assert!(invalid.is_err(), "{:?}", invalid);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use super::{ListItem, Recurrence};
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+use uuid::Uuid;
+
+/// Make a list item recur, so that it is uncompleted again some time after it has been completed,
+/// or stop it from recurring by setting `recurrence` to `null`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub id: Uuid,
+    pub recurrence: Option<Recurrence>,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `recurrence` is a weekly recurrence without weekdays
+    /// * If `recurrence` is a monthly recurrence with a day outside of `[1, 31]`
+    /// * If `recurrence` is a recurrence after completion with days outside of `[1, Recurrence::MAX_DAYS]`
+    pub fn new(id: Uuid, recurrence: Option<Recurrence>) -> Result<Self, InvalidParams> {
+        if let Some(recurrence) = &recurrence {
+            if !recurrence.is_valid() {
+                return Err(InvalidParams::InvalidRecurrence);
+            }
+        }
+
+        Ok(Self { id, recurrence })
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    id: Uuid,
+    recurrence: Option<Recurrence>,
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.id, builder.recurrence)
+    }
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidRecurrence,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidRecurrence => crate::invalid_value_because_message(
+                "recurrence",
+                format!(
+                    "weekdays can't be empty, day should be in [1, 31] and days in [1, {}]",
+                    Recurrence::MAX_DAYS
+                ),
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    /// The updated list item.
+    pub list_item: Option<ListItem>,
+}
+
+impl MethodResult {
+    pub fn updated(list_item: ListItem) -> Self {
+        Self {
+            success: true,
+            list_item: Some(list_item),
+        }
+    }
+
+    pub fn missing() -> Self {
+        Self {
+            success: false,
+            list_item: None,
+        }
+    }
+}
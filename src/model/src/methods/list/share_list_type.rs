@@ -0,0 +1,106 @@
+use super::ListPermission;
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+/// Share one of the caller's list types with another user.
+/// Sharing an already shared list type again replaces the permission.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub list_type: String,
+    pub user_id: String,
+    pub permission: ListPermission,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `list_type` is empty or whitespace.
+    /// * If `user_id` is empty or whitespace.
+    pub fn new(
+        list_type: String,
+        user_id: String,
+        permission: ListPermission,
+    ) -> Result<Self, InvalidParams> {
+        let list_type = list_type.trim();
+        if list_type.is_empty() {
+            return Err(InvalidParams::InvalidListType);
+        }
+
+        let user_id = user_id.trim();
+        if user_id.is_empty() {
+            return Err(InvalidParams::InvalidUserId);
+        }
+
+        Ok(Self {
+            list_type: list_type.to_owned(),
+            user_id: user_id.to_owned(),
+            permission,
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.list_type, builder.user_id, builder.permission)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    list_type: String,
+    user_id: String,
+    permission: ListPermission,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidListType,
+    InvalidUserId,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidListType => crate::generic_invalid_value_message("list_type"),
+            InvalidParams::InvalidUserId => crate::generic_invalid_value_message("user_id"),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+}
+
+impl MethodResult {
+    pub fn new(success: bool) -> Self {
+        Self { success }
+    }
+}
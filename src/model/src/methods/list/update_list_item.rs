@@ -0,0 +1,174 @@
+use super::{ListItem, ListItemDetails};
+use crate::JsonRpcRequest;
+use chrono::NaiveDate;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+use uuid::Uuid;
+
+/// Update a list item in place, keeping its id.
+///
+/// `version` must be the version of the item that the change was based on,
+/// if the item has been changed since then the update is rejected as stale.
+///
+/// The name and all details are replaced, so details that are left out are cleared.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub id: Uuid,
+    pub version: i64,
+    pub item_name: String,
+    #[serde(flatten)]
+    pub details: ListItemDetails,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `item_name` is empty or whitespace.
+    /// * If `details` has a quantity that isn't positive.
+    pub fn new(
+        id: Uuid,
+        version: i64,
+        item_name: String,
+        details: ListItemDetails,
+    ) -> Result<Self, InvalidParams> {
+        let item_name = item_name.trim();
+        if item_name.is_empty() {
+            return Err(InvalidParams::InvalidItemName);
+        }
+
+        if !details.has_valid_quantity() {
+            return Err(InvalidParams::InvalidQuantity);
+        }
+
+        Ok(Self {
+            id,
+            version,
+            item_name: item_name.to_owned(),
+            details,
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(
+            builder.id,
+            builder.version,
+            builder.item_name,
+            ListItemDetails::new(
+                builder.quantity,
+                builder.unit,
+                builder.notes,
+                builder.due_date,
+            ),
+        )
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    id: Uuid,
+    version: i64,
+    item_name: String,
+    quantity: Option<f64>,
+    unit: Option<String>,
+    notes: Option<String>,
+    due_date: Option<NaiveDate>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidItemName,
+    InvalidQuantity,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidItemName => crate::generic_invalid_value_message("item_name"),
+            InvalidParams::InvalidQuantity => crate::invalid_value_because_message(
+                "quantity",
+                "must be a positive number".to_owned(),
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub success: bool,
+    /// The updated list item, with its new version.
+    pub list_item: Option<ListItem>,
+}
+
+impl MethodResult {
+    pub fn updated(list_item: ListItem) -> Self {
+        Self {
+            success: true,
+            list_item: Some(list_item),
+        }
+    }
+
+    pub fn missing() -> Self {
+        Self {
+            success: false,
+            list_item: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params = serde_json::from_str::<Params>(
+            r#"{ "id": "2b5f4e36-2f6e-4b8a-9a4d-1f1a5e9a8c11", "version": 3, "item_name": " milk ", "quantity": 2 }"#,
+        )
+        .unwrap();
+        assert_eq!(params.version, 3);
+        assert_eq!(params.item_name, "milk");
+        assert_eq!(params.details.quantity, Some(2.0));
+        assert_eq!(params.details.unit, None);
+
+        let invalids = [
+            r#"{ "id": "2b5f4e36-2f6e-4b8a-9a4d-1f1a5e9a8c11", "item_name": "milk" }"#,
+            r#"{ "id": "2b5f4e36-2f6e-4b8a-9a4d-1f1a5e9a8c11", "version": 3, "item_name": " " }"#,
+            r#"{ "id": "2b5f4e36-2f6e-4b8a-9a4d-1f1a5e9a8c11", "version": 3, "item_name": "milk", "quantity": 0 }"#,
+        ];
+        for invalid in &invalids {
+            assert!(
+                serde_json::from_str::<Params>(invalid).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+}
@@ -5,6 +5,8 @@ use std::{
     fmt::Display,
 };
 
+pub mod verify_sas_key;
+
 #[derive(Clone, Debug, serde::Serialize)]
 #[non_exhaustive]
 pub struct Params {
@@ -0,0 +1,135 @@
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub sas_key: String,
+    pub key_value: String,
+    /// If set, the token must also be valid for this resource uri.
+    pub resource_uri: Option<String>,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `sas_key` is empty or whitespace.
+    /// * If `key_value` is empty or whitespace.
+    pub fn new(
+        sas_key: String,
+        key_value: String,
+        resource_uri: Option<String>,
+    ) -> Result<Self, InvalidParams> {
+        use InvalidParams::*;
+        let sas_key = sas_key.trim().to_owned();
+        if sas_key.is_empty() {
+            return Err(InvalidSasKey);
+        }
+
+        let key_value = key_value.trim().to_owned();
+        if key_value.is_empty() {
+            return Err(InvalidKeyValue);
+        }
+
+        let resource_uri = resource_uri
+            .map(|uri| uri.trim().to_owned())
+            .filter(|uri| !uri.is_empty());
+
+        Ok(Self {
+            sas_key,
+            key_value,
+            resource_uri,
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(value: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(value.params).map_err(Self::Error::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.sas_key, builder.key_value, builder.resource_uri)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    sas_key: String,
+    key_value: String,
+    resource_uri: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidSasKey,
+    InvalidKeyValue,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidSasKey => crate::generic_invalid_value_message("sas_key"),
+            InvalidParams::InvalidKeyValue => crate::generic_invalid_value_message("key_value"),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub valid: bool,
+    /// Why the token is not valid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_uri: Option<String>,
+    /// Unix timestamp (in seconds) when the token expires.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_name: Option<String>,
+}
+
+impl MethodResult {
+    pub fn valid(resource_uri: String, expiry: i64, key_name: String) -> Self {
+        Self {
+            valid: true,
+            reason: None,
+            resource_uri: Some(resource_uri),
+            expiry: Some(expiry),
+            key_name: Some(key_name),
+        }
+    }
+
+    pub fn invalid(reason: String) -> Self {
+        Self {
+            valid: false,
+            reason: Some(reason),
+            resource_uri: None,
+            expiry: None,
+            key_name: None,
+        }
+    }
+}
@@ -1,14 +1,141 @@
+use chrono::NaiveDateTime;
+
+pub mod get_arrivals;
 pub mod get_departures;
+pub mod get_nearby_stops;
+pub mod search_stops;
 
+/// A departure from a stop, times are in the local time of the stop.
 #[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
 #[non_exhaustive]
 pub struct Departure {
-    pub time: String,
+    /// The line number, e.g. "4" or "Tåg 42".
+    pub line: Option<String>,
+    pub transport_mode: TransportMode,
     pub direction: String,
+    /// The track or stop position, the realtime one if it has been changed.
+    pub track: Option<String>,
+    /// The date and time of the timetable.
+    pub scheduled: NaiveDateTime,
+    /// The expected date and time, if there is realtime data.
+    pub realtime: Option<NaiveDateTime>,
+    pub cancelled: bool,
 }
 
 impl Departure {
-    pub fn new(time: String, direction: String) -> Self {
-        Self { time, direction }
+    pub fn new(
+        line: Option<String>,
+        transport_mode: TransportMode,
+        direction: String,
+        track: Option<String>,
+        scheduled: NaiveDateTime,
+        realtime: Option<NaiveDateTime>,
+        cancelled: bool,
+    ) -> Self {
+        Self {
+            line,
+            transport_mode,
+            direction,
+            track,
+            scheduled,
+            realtime,
+            cancelled,
+        }
+    }
+
+    /// The realtime date and time if there is one, otherwise the scheduled one.
+    pub fn expected(&self) -> NaiveDateTime {
+        self.realtime.unwrap_or(self.scheduled)
+    }
+}
+
+/// An arrival at a stop, times are in the local time of the stop.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
+#[non_exhaustive]
+pub struct Arrival {
+    /// The line number, e.g. "4" or "Tåg 42".
+    pub line: Option<String>,
+    pub transport_mode: TransportMode,
+    /// Where the trip started.
+    pub origin: String,
+    /// The track or stop position, the realtime one if it has been changed.
+    pub track: Option<String>,
+    /// The date and time of the timetable.
+    pub scheduled: NaiveDateTime,
+    /// The expected date and time, if there is realtime data.
+    pub realtime: Option<NaiveDateTime>,
+    pub cancelled: bool,
+}
+
+impl Arrival {
+    pub fn new(
+        line: Option<String>,
+        transport_mode: TransportMode,
+        origin: String,
+        track: Option<String>,
+        scheduled: NaiveDateTime,
+        realtime: Option<NaiveDateTime>,
+        cancelled: bool,
+    ) -> Self {
+        Self {
+            line,
+            transport_mode,
+            origin,
+            track,
+            scheduled,
+            realtime,
+            cancelled,
+        }
+    }
+
+    /// The realtime date and time if there is one, otherwise the scheduled one.
+    pub fn expected(&self) -> NaiveDateTime {
+        self.realtime.unwrap_or(self.scheduled)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportMode {
+    HighSpeedTrain,
+    RegionalTrain,
+    ExpressBus,
+    LocalTrain,
+    Metro,
+    Tram,
+    Bus,
+    Ferry,
+    Taxi,
+    Other,
+}
+
+/// A stop or station of the GTFS data, the `id` can be used to get its departures and arrivals.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct Stop {
+    pub id: String,
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl Stop {
+    pub fn new(id: String, name: String, lat: f64, lon: f64) -> Self {
+        Self { id, name, lat, lon }
+    }
+}
+
+/// A stop and how far away it is.
+#[derive(serde::Serialize, Clone, Debug, serde::Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct NearbyStop {
+    #[serde(flatten)]
+    pub stop: Stop,
+    pub distance_m: f64,
+}
+
+impl NearbyStop {
+    pub fn new(stop: Stop, distance_m: f64) -> Self {
+        Self { stop, distance_m }
     }
 }
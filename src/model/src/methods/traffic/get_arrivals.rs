@@ -0,0 +1,18 @@
+use super::Arrival;
+
+/// Arrivals are requested with the same parameters as departures,
+/// with `start` and `duration_m` as the window of arrival times.
+pub use super::get_departures::{InvalidParams, Params};
+
+/// The arrivals at a stop, in the order that they arrive.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub arrivals: Vec<Arrival>,
+}
+
+impl MethodResult {
+    pub fn new(arrivals: Vec<Arrival>) -> Self {
+        Self { arrivals }
+    }
+}
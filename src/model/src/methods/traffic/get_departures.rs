@@ -1,4 +1,5 @@
 use crate::JsonRpcRequest;
+use chrono::NaiveDateTime;
 use std::{
     convert::{TryFrom, TryInto},
     error::Error,
@@ -7,17 +8,65 @@ use std::{
 
 use super::Departure;
 
+const MIN_COUNT: u32 = 1;
+const MAX_COUNT: u32 = 100;
+const DEFAULT_COUNT: u32 = 20;
+const MIN_DURATION_M: u32 = 1;
+const MAX_DURATION_M: u32 = 1439;
+const DEFAULT_DURATION_M: u32 = 30;
+
+/// Get the departures from a stop, in the order that they depart.
+///
+/// Arrivals are requested with the same parameters, see `get_arrivals`.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 #[serde(try_from = "ParamsBuilder")]
 #[non_exhaustive]
 pub struct Params {
-   pub stop_id: String,
-   pub count: u32,
+    pub stop_id: String,
+    /// The most departures to get.
+    pub count: u32,
+    /// The local time of the stop to get departures from, defaults to now.
+    pub start: Option<NaiveDateTime>,
+    /// How many minutes after `start` to get departures for.
+    pub duration_m: u32,
 }
 
 impl Params {
-    pub fn new(stop_id: String, count: u32) -> Self {
-        Self { stop_id, count }
+    /// ## Error
+    /// * If `stop_id` is empty.
+    /// * If `count` is outside the range (1..=100).
+    /// * If `duration_m` is outside the range (1..=1439).
+    pub fn new(
+        stop_id: String,
+        count: Option<u32>,
+        start: Option<NaiveDateTime>,
+        duration_m: Option<u32>,
+    ) -> Result<Self, InvalidParams> {
+        let stop_id = stop_id.trim().to_owned();
+        if stop_id.is_empty() {
+            return Err(InvalidParams::InvalidStopId);
+        }
+
+        let count = match count {
+            Some(count) if (MIN_COUNT..=MAX_COUNT).contains(&count) => count,
+            None => DEFAULT_COUNT,
+            Some(_invalid) => return Err(InvalidParams::InvalidCount),
+        };
+
+        let duration_m = match duration_m {
+            Some(duration_m) if (MIN_DURATION_M..=MAX_DURATION_M).contains(&duration_m) => {
+                duration_m
+            }
+            None => DEFAULT_DURATION_M,
+            Some(_invalid) => return Err(InvalidParams::InvalidDuration),
+        };
+
+        Ok(Self {
+            stop_id,
+            count,
+            start,
+            duration_m,
+        })
     }
 }
 
@@ -36,19 +85,29 @@ impl TryFrom<ParamsBuilder> for Params {
     type Error = InvalidParams;
 
     fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
-        Ok(Params::new(builder.stop_id, builder.count))
+        Params::new(
+            builder.stop_id,
+            builder.count,
+            builder.start,
+            builder.duration_m,
+        )
     }
 }
 
 #[derive(serde::Deserialize)]
 struct ParamsBuilder {
     stop_id: String,
-    count: u32,
+    count: Option<u32>,
+    start: Option<NaiveDateTime>,
+    duration_m: Option<u32>,
 }
 
 #[derive(Debug)]
 pub enum InvalidParams {
     InvalidFormat(serde_json::Error),
+    InvalidStopId,
+    InvalidCount,
+    InvalidDuration,
 }
 
 impl Error for InvalidParams {}
@@ -59,6 +118,15 @@ impl Display for InvalidParams {
             InvalidParams::InvalidFormat(serde_error) => {
                 crate::invalid_params_serde_message(&serde_error)
             }
+            InvalidParams::InvalidStopId => crate::generic_invalid_value_message("stop_id"),
+            InvalidParams::InvalidCount => format!(
+                "invalid count, should be integer in [{}, {}]",
+                MIN_COUNT, MAX_COUNT
+            ),
+            InvalidParams::InvalidDuration => format!(
+                "invalid duration_m, should be integer in [{}, {}]",
+                MIN_DURATION_M, MAX_DURATION_M
+            ),
         };
         write!(f, "{}", output)
     }
@@ -75,3 +143,32 @@ impl MethodResult {
         Self { departures }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params = serde_json::from_str::<Params>(r#"{ "stop_id": "740000001" }"#).unwrap();
+        assert_eq!(params.count, DEFAULT_COUNT);
+        assert_eq!(params.duration_m, DEFAULT_DURATION_M);
+        assert_eq!(params.start, None);
+
+        let params = serde_json::from_str::<Params>(
+            r#"{ "stop_id": "740000001", "count": 5, "start": "2022-03-02T07:30:00", "duration_m": 120 }"#,
+        )
+        .unwrap();
+        assert_eq!(params.count, 5);
+        assert_eq!(params.start.unwrap().to_string(), "2022-03-02 07:30:00");
+
+        let invalids = [
+            Params::new(" ".to_owned(), None, None, None),
+            Params::new("740000001".to_owned(), Some(0), None, None),
+            Params::new("740000001".to_owned(), None, None, Some(1440)),
+        ];
+        for invalid in &invalids {
+            assert!(invalid.is_err(), "{:?}", invalid);
+        }
+    }
+}
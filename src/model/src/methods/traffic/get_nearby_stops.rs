@@ -0,0 +1,193 @@
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+use super::NearbyStop;
+
+const MIN_COUNT: usize = 1;
+const MAX_COUNT: usize = 100;
+const DEFAULT_COUNT: usize = 10;
+const MIN_DISTANCE_M: u32 = 1;
+const MAX_DISTANCE_M: u32 = 5000;
+const DEFAULT_DISTANCE_M: u32 = 500;
+const MIN_LATITUDE: f64 = -90.0;
+const MAX_LATITUDE: f64 = 90.0;
+const MIN_LONGITUDE: f64 = -180.0;
+const MAX_LONGITUDE: f64 = 180.0;
+
+/// Get the stops within a distance of a position, the closest first.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub lat: f64,
+    pub lon: f64,
+    pub distance_m: u32,
+    /// The most stops to get.
+    pub count: usize,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `lat` is an invalid latitude.
+    /// * If `lon` is an invalid longitude.
+    /// * If `distance_m` is outside the range (1..=5000).
+    /// * If `count` is outside the range (1..=100).
+    pub fn new(
+        lat: f64,
+        lon: f64,
+        distance_m: Option<u32>,
+        count: Option<usize>,
+    ) -> Result<Self, InvalidParams> {
+        if !(MIN_LATITUDE..=MAX_LATITUDE).contains(&lat) {
+            return Err(InvalidParams::InvalidLatitude);
+        }
+
+        if !(MIN_LONGITUDE..=MAX_LONGITUDE).contains(&lon) {
+            return Err(InvalidParams::InvalidLongitude);
+        }
+
+        let distance_m = match distance_m {
+            Some(distance_m) if (MIN_DISTANCE_M..=MAX_DISTANCE_M).contains(&distance_m) => {
+                distance_m
+            }
+            None => DEFAULT_DISTANCE_M,
+            Some(_invalid) => return Err(InvalidParams::InvalidDistance),
+        };
+
+        let count = match count {
+            Some(count) if (MIN_COUNT..=MAX_COUNT).contains(&count) => count,
+            None => DEFAULT_COUNT,
+            Some(_invalid) => return Err(InvalidParams::InvalidCount),
+        };
+
+        Ok(Self {
+            lat,
+            lon,
+            distance_m,
+            count,
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Self::new(builder.lat, builder.lon, builder.distance_m, builder.count)
+    }
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidLatitude,
+    InvalidLongitude,
+    InvalidDistance,
+    InvalidCount,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidLatitude => format!(
+                "invalid lat, should be float in [{}, {}]",
+                MIN_LATITUDE, MAX_LATITUDE
+            ),
+            InvalidParams::InvalidLongitude => format!(
+                "invalid lon, should be float in [{}, {}]",
+                MIN_LONGITUDE, MAX_LONGITUDE
+            ),
+            InvalidParams::InvalidDistance => format!(
+                "invalid distance_m, should be integer in [{}, {}]",
+                MIN_DISTANCE_M, MAX_DISTANCE_M
+            ),
+            InvalidParams::InvalidCount => format!(
+                "invalid count, should be integer in [{}, {}]",
+                MIN_COUNT, MAX_COUNT
+            ),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    lat: f64,
+    lon: f64,
+    distance_m: Option<u32>,
+    count: Option<usize>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub stops: Vec<NearbyStop>,
+}
+
+impl MethodResult {
+    pub fn new(stops: Vec<NearbyStop>) -> Self {
+        Self { stops }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let valids = [
+            Params::new(59.33258, 18.0649, None, None),
+            Params::new(-33.86785, 151.20732, Some(MAX_DISTANCE_M), Some(MAX_COUNT)),
+        ];
+        for valid in &valids {
+            assert!(valid.is_ok(), "{:?}", valid);
+        }
+
+        let invalids = [
+            Params::new(90.5, 18.0649, None, None),
+            Params::new(59.33258, -180.5, None, None),
+            Params::new(59.33258, 18.0649, Some(0), None),
+            Params::new(59.33258, 18.0649, Some(MAX_DISTANCE_M + 1), None),
+            Params::new(59.33258, 18.0649, None, Some(0)),
+        ];
+        for invalid in &invalids {
+            assert!(invalid.is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn deser() {
+        let json = r#"
+        {
+            "lat": 59.33258,
+            "lon": 18.0649,
+            "distance_m": 250
+        }
+        "#;
+        let params = serde_json::from_str::<Params>(json).unwrap();
+        assert_eq!(params.distance_m, 250);
+        assert_eq!(params.count, DEFAULT_COUNT);
+    }
+}
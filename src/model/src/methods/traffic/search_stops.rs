@@ -0,0 +1,128 @@
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+use super::Stop;
+
+const MIN_LIMIT: usize = 1;
+const MAX_LIMIT: usize = 50;
+const DEFAULT_LIMIT: usize = 10;
+const MAX_QUERY_LENGTH: usize = 100;
+
+/// Search for stops by name, the best matches first.
+///
+/// Matching ignores case and accents, and tolerates a few typos in longer queries.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub query: String,
+    /// The most stops to get.
+    pub limit: usize,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `query` is empty or longer than 100 characters.
+    /// * If `limit` is outside the range (1..=50).
+    pub fn new(query: String, limit: Option<usize>) -> Result<Self, InvalidParams> {
+        let query = query.trim().to_owned();
+        if query.is_empty() || query.chars().count() > MAX_QUERY_LENGTH {
+            return Err(InvalidParams::InvalidQuery);
+        }
+
+        let limit = match limit {
+            Some(limit) if (MIN_LIMIT..=MAX_LIMIT).contains(&limit) => limit,
+            None => DEFAULT_LIMIT,
+            Some(_invalid) => return Err(InvalidParams::InvalidLimit),
+        };
+
+        Ok(Self { query, limit })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Params::new(builder.query, builder.limit)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    query: String,
+    limit: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidQuery,
+    InvalidLimit,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidQuery => crate::invalid_value_because_message(
+                "query",
+                format!("should be 1 to {} characters", MAX_QUERY_LENGTH),
+            ),
+            InvalidParams::InvalidLimit => format!(
+                "invalid limit, should be integer in [{}, {}]",
+                MIN_LIMIT, MAX_LIMIT
+            ),
+        };
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub stops: Vec<Stop>,
+}
+
+impl MethodResult {
+    pub fn new(stops: Vec<Stop>) -> Self {
+        Self { stops }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_test() {
+        let params = Params::new("  Södra station ".to_owned(), None).unwrap();
+        assert_eq!(params.query, "Södra station");
+        assert_eq!(params.limit, DEFAULT_LIMIT);
+
+        assert!(Params::new("Slussen".to_owned(), Some(MAX_LIMIT)).is_ok());
+        assert!(Params::new(" ".to_owned(), None).is_err());
+        assert!(Params::new("a".repeat(MAX_QUERY_LENGTH + 1), None).is_err());
+        assert!(Params::new("Slussen".to_owned(), Some(0)).is_err());
+        assert!(Params::new("Slussen".to_owned(), Some(MAX_LIMIT + 1)).is_err());
+    }
+}
@@ -1,6 +1,9 @@
 pub mod add_user;
+pub mod confirm_totp;
+pub mod enroll_totp;
 pub mod get_token;
 pub mod get_user;
+pub mod unlock_user;
 
 #[derive(serde::Serialize, Clone, Debug, serde::Deserialize)]
 #[non_exhaustive]
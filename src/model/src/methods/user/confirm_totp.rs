@@ -0,0 +1,100 @@
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+const CODE_LEN: usize = 6;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub code: String,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `code` is not a 6 digit number.
+    pub fn new(code: String) -> Result<Self, InvalidParams> {
+        let code = code.trim();
+        if code.len() != CODE_LEN || !code.chars().all(|c| c.is_ascii_digit()) {
+            return Err(InvalidParams::InvalidCode);
+        }
+
+        Ok(Self {
+            code: code.to_owned(),
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Params::new(builder.code)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    code: String,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidCode,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidCode => crate::invalid_value_because_message(
+                "code",
+                format!("must be a {} digit number", CODE_LEN),
+            ),
+        };
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub confirmed: bool,
+    /// Single-use codes that can be used instead of a TOTP code. Only returned once.
+    pub recovery_codes: Vec<String>,
+}
+
+impl MethodResult {
+    pub fn success(recovery_codes: Vec<String>) -> Self {
+        Self {
+            confirmed: true,
+            recovery_codes,
+        }
+    }
+
+    pub fn failure() -> Self {
+        Self {
+            confirmed: false,
+            recovery_codes: Vec::new(),
+        }
+    }
+}
@@ -11,11 +11,25 @@ use std::{
 pub struct Params {
     pub username: String,
     pub password: String,
+    /// A TOTP code or a recovery code, required if the user has enabled two-factor authentication.
+    pub totp_code: Option<String>,
 }
 
 impl Params {
-    pub fn new(username: String, password: String) -> Result<Self, InvalidParams> {
-        Ok(Self { username, password })
+    pub fn new(
+        username: String,
+        password: String,
+        totp_code: Option<String>,
+    ) -> Result<Self, InvalidParams> {
+        let totp_code = totp_code
+            .map(|code| code.trim().to_owned())
+            .filter(|code| !code.is_empty());
+
+        Ok(Self {
+            username,
+            password,
+            totp_code,
+        })
     }
 }
 
@@ -33,7 +47,7 @@ impl TryFrom<ParamsBuilder> for Params {
     type Error = InvalidParams;
 
     fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
-        Params::new(builder.username, builder.password)
+        Params::new(builder.username, builder.password, builder.totp_code)
     }
 }
 
@@ -41,6 +55,7 @@ impl TryFrom<ParamsBuilder> for Params {
 struct ParamsBuilder {
     username: String,
     password: String,
+    totp_code: Option<String>,
 }
 
 #[derive(Debug)]
@@ -0,0 +1,83 @@
+use crate::JsonRpcRequest;
+use std::{
+    convert::{TryFrom, TryInto},
+    error::Error,
+    fmt::Display,
+};
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "ParamsBuilder")]
+#[non_exhaustive]
+pub struct Params {
+    pub username: String,
+}
+
+impl Params {
+    /// ## Error
+    /// * If `username` is empty or whitespace.
+    pub fn new(username: String) -> Result<Self, InvalidParams> {
+        let trimmed = username.trim();
+        if trimmed.is_empty() {
+            return Err(InvalidParams::InvalidUsername);
+        }
+
+        Ok(Self {
+            username: trimmed.to_owned(),
+        })
+    }
+}
+
+impl TryFrom<JsonRpcRequest> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(request: JsonRpcRequest) -> Result<Self, Self::Error> {
+        let builder: ParamsBuilder =
+            serde_json::from_value(request.params).map_err(InvalidParams::InvalidFormat)?;
+        builder.try_into()
+    }
+}
+
+impl TryFrom<ParamsBuilder> for Params {
+    type Error = InvalidParams;
+
+    fn try_from(builder: ParamsBuilder) -> Result<Self, Self::Error> {
+        Params::new(builder.username)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ParamsBuilder {
+    username: String,
+}
+
+#[derive(Debug)]
+pub enum InvalidParams {
+    InvalidFormat(serde_json::Error),
+    InvalidUsername,
+}
+
+impl Error for InvalidParams {}
+
+impl Display for InvalidParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            InvalidParams::InvalidFormat(serde_error) => {
+                crate::invalid_params_serde_message(&serde_error)
+            }
+            InvalidParams::InvalidUsername => crate::generic_invalid_value_message("username"),
+        };
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodResult {
+    pub unlocked: bool,
+}
+
+impl MethodResult {
+    pub fn new(unlocked: bool) -> Self {
+        Self { unlocked }
+    }
+}
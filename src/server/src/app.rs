@@ -1,19 +1,29 @@
 use crate::{
+    attachments::AttachmentStore,
+    audit::Auditor,
     auth::{Claims, TokenHandler},
+    calendar::CalendarComponent,
     controller::*,
     influx::InfluxClient,
-    AppSettings,
+    list_events::ListEvents,
+    recurrence::{RecurrenceScheduler, SystemClock},
+    trash, AppSettings,
 };
 use database::{self as db, Database};
 use db::{DatabaseError, Request as DbRequest, RequestLogDb};
 use hmac::crypto_mac::InvalidKeyLength;
+use hyper::Body;
 use isahc::HttpClient;
 use model::*;
-use redis::async_pool::mobc_redis::{mobc, redis::RedisError};
+use redis::async_pool::{
+    mobc_redis::{mobc, redis::RedisError},
+    AsyncRedisPool,
+};
 use std::{
     convert::TryFrom,
     error::Error,
     fmt::{Debug, Display},
+    net::IpAddr,
     str::FromStr,
     sync::Arc,
 };
@@ -29,6 +39,7 @@ pub struct App {
     traffic_controller: TrafficController,
     user_controller: UserController,
     server_controller: ServerController,
+    audit_controller: AuditController,
 }
 
 impl App {
@@ -47,11 +58,39 @@ impl App {
 
         let request_log_db = Arc::new(Database::new(opts.database_addr.clone()).await.unwrap());
         let user_db = Arc::new(Database::new(opts.database_addr.clone()).await.unwrap());
+        let audit_db = Arc::new(Database::new(opts.database_addr.clone()).await.unwrap());
 
-        let list_controller = ListItemController::new(list_item_db);
-        let user_controller = UserController::new(user_db, token_handler);
-        let traffic_controller =
-            TrafficController::new(HttpClient::new().unwrap(), opts.resrobot_api_key.clone());
+        let signing_key = match (&opts.sas_key_name, &opts.sas_key_value) {
+            (Some(key_name), Some(key_value)) => Some((key_name.clone(), key_value.clone())),
+            _ => None,
+        };
+        let attachments = Arc::new(AttachmentStore::new(
+            opts.attachment_root.clone(),
+            opts.attachment_max_bytes,
+            signing_key.clone(),
+        ));
+
+        tokio::spawn(trash::purge_periodically(
+            list_item_db.clone(),
+            attachments.clone(),
+            opts.trash_retention_days,
+        ));
+        tokio::spawn(
+            RecurrenceScheduler::new(list_item_db.clone(), SystemClock).run_periodically(),
+        );
+        let list_events = ListEvents::new(signing_key);
+        tokio::spawn(list_events.clone().listen(list_item_db.clone()));
+
+        let auditor = Arc::new(Auditor::new(audit_db.clone()));
+        let list_controller =
+            ListItemController::new(list_item_db, auditor.clone(), attachments, list_events);
+        let user_controller = UserController::new(user_db, token_handler, auditor);
+        let audit_controller = AuditController::new(audit_db);
+        let traffic_controller = TrafficController::new(
+            HttpClient::new().unwrap(),
+            opts.resrobot_api_key.clone(),
+            AsyncRedisPool::new(opts.redis_addr.clone()),
+        );
         let server_controller = ServerController::new();
 
         Self {
@@ -61,15 +100,78 @@ impl App {
             traffic_controller,
             user_controller,
             server_controller,
+            audit_controller,
             influx_db,
         }
     }
 
+    /// Subscribe to the changes of a list type as server-sent events,
+    /// which requires the same access as `get_list_items`.
+    pub async fn subscribe_list_events(
+        &self,
+        claims: &Option<Claims>,
+        owner_id: Option<&str>,
+        list_type: &str,
+    ) -> AppResult<Body> {
+        if crate::auth::authenticate(Method::GetListItems, claims).is_err() {
+            return Err(AppError::not_permitted());
+        }
+
+        self.list_controller
+            .subscribe_list_events(claims, owner_id, list_type)
+            .await
+    }
+
+    /// Subscribe to the changes of a list type with the `query` of a signed subscription url,
+    /// which is all the access that is needed.
+    pub async fn subscribe_signed_list_events(&self, query: &str) -> AppResult<Body> {
+        self.list_controller
+            .subscribe_signed_list_events(query)
+            .await
+    }
+
+    /// Store an uploaded file as an attachment of a list item.
+    pub async fn upload_attachment(
+        &self,
+        claims: &Option<Claims>,
+        list_item_id: Uuid,
+        file_name: &str,
+        content_type: &str,
+        body: Body,
+    ) -> AppResult<list::Attachment> {
+        if crate::auth::authenticate(Method::AddListItemAttachment, claims).is_err() {
+            return Err(AppError::not_permitted());
+        }
+
+        self.list_controller
+            .add_list_item_attachment(claims, list_item_id, file_name, content_type, body)
+            .await
+    }
+
+    /// The attachment `id` and its file, if `query` is the signature of its download url.
+    pub async fn download_attachment(
+        &self,
+        id: Uuid,
+        query: &str,
+    ) -> AppResult<Option<(list::Attachment, tokio::fs::File)>> {
+        self.list_controller.download_attachment(id, query).await
+    }
+
+    /// The calendar feed with the secret `token`, which is all the access that is needed.
+    pub async fn calendar_feed(
+        &self,
+        token: &str,
+        component: CalendarComponent,
+    ) -> AppResult<Option<String>> {
+        self.list_controller.calendar_feed(token, component).await
+    }
+
     /// Handle a single JSON RPC request
     pub async fn handle_single(
         &self,
         request: JsonRpcRequest,
         claims: &Option<Claims>,
+        client_ip: Option<IpAddr>,
     ) -> JsonRpcResponse {
         let timer = std::time::Instant::now();
         let id = request.id.clone();
@@ -91,27 +193,171 @@ impl App {
                     match method {
                         Method::AddListItem => self
                             .list_controller
-                            .add_list_item(request)
+                            .add_list_item(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::AddListItems => self
+                            .list_controller
+                            .add_list_items(request, claims)
                             .await
                             .map(|result| JsonRpcResponse::success(result, id)),
                         Method::GetListItems => self
                             .list_controller
-                            .get_list_items(request)
+                            .get_list_items(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::GetListChanges => self
+                            .list_controller
+                            .get_list_changes(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::GetListEventsUrl => self
+                            .list_controller
+                            .get_list_events_url(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::SearchListItems => self
+                            .list_controller
+                            .search_list_items(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::SetListItemRecurrence => self
+                            .list_controller
+                            .set_list_item_recurrence(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::ExportList => self
+                            .list_controller
+                            .export_list(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::QuickAddListItem => self
+                            .list_controller
+                            .quick_add_list_item(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::ImportList => self
+                            .list_controller
+                            .import_list(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::UpdateListItem => self
+                            .list_controller
+                            .update_list_item(request, claims)
                             .await
                             .map(|result| JsonRpcResponse::success(result, id)),
                         Method::DeleteListItem => self
                             .list_controller
-                            .delete_list_item(request)
+                            .delete_list_item(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::DeleteListItems => self
+                            .list_controller
+                            .delete_list_items(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::GetDeletedListItems => self
+                            .list_controller
+                            .get_deleted_list_items(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::RestoreListItem => self
+                            .list_controller
+                            .restore_list_item(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::GetListItemHistory => self
+                            .list_controller
+                            .get_list_item_history(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::RevertListItem => self
+                            .list_controller
+                            .revert_list_item(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::GetListItemAttachments => self
+                            .list_controller
+                            .get_list_item_attachments(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        // files are uploaded outside of JSON RPC, see `upload_attachment`
+                        Method::AddListItemAttachment => {
+                            Err(AppError::from(JsonRpcError::method_not_found()))
+                        }
+                        Method::DeleteListItemAttachment => self
+                            .list_controller
+                            .delete_list_item_attachment(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::RegenerateCalendarFeed => self
+                            .list_controller
+                            .regenerate_calendar_feed(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::CompleteListItem => self
+                            .list_controller
+                            .complete_list_item(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::UncompleteListItem => self
+                            .list_controller
+                            .uncomplete_list_item(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::MoveListItem => self
+                            .list_controller
+                            .move_list_item(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::MoveListItems => self
+                            .list_controller
+                            .move_list_items(request, claims)
                             .await
                             .map(|result| JsonRpcResponse::success(result, id)),
                         Method::GetListTypes => self
                             .list_controller
-                            .get_list_types(request)
+                            .get_list_types(request, claims)
                             .await
                             .map(|result| JsonRpcResponse::success(result, id)),
                         Method::RenameListType => self
                             .list_controller
-                            .rename_list_type(request)
+                            .rename_list_type(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::DeleteListType => self
+                            .list_controller
+                            .delete_list_type(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::MergeListTypes => self
+                            .list_controller
+                            .merge_list_types(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::SaveListAsTemplate => self
+                            .list_controller
+                            .save_list_as_template(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::GetTemplates => self
+                            .list_controller
+                            .get_templates(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::InstantiateTemplate => self
+                            .list_controller
+                            .instantiate_template(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::ShareListType => self
+                            .list_controller
+                            .share_list_type(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::UnshareListType => self
+                            .list_controller
+                            .unshare_list_type(request, claims)
                             .await
                             .map(|result| JsonRpcResponse::success(result, id)),
                         Method::Sleep => self
@@ -124,14 +370,34 @@ impl App {
                             .generate_sas_key(request)
                             .await
                             .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::VerifySasKey => self
+                            .server_controller
+                            .verify_sas_key(request)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
                         Method::GetDepartures => self
                             .traffic_controller
                             .get_departures(request)
                             .await
                             .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::GetArrivals => self
+                            .traffic_controller
+                            .get_arrivals(request)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::SearchStops => self
+                            .traffic_controller
+                            .search_stops(request)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::GetNearbyStops => self
+                            .traffic_controller
+                            .get_nearby_stops(request)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
                         Method::AddUser => self
                             .user_controller
-                            .add_user(request)
+                            .add_user(request, claims)
                             .await
                             .map(|result| JsonRpcResponse::success(result, id)),
                         Method::GetUser => self
@@ -141,7 +407,27 @@ impl App {
                             .map(|result| JsonRpcResponse::success(result, id)),
                         Method::GetToken => self
                             .user_controller
-                            .get_token(request)
+                            .get_token(request, client_ip)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::EnrollTotp => self
+                            .user_controller
+                            .enroll_totp(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::ConfirmTotp => self
+                            .user_controller
+                            .confirm_totp(request, claims)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::GetAuditEvents => self
+                            .audit_controller
+                            .get_audit_events(request)
+                            .await
+                            .map(|result| JsonRpcResponse::success(result, id)),
+                        Method::UnlockUser => self
+                            .user_controller
+                            .unlock_user(request, claims)
                             .await
                             .map(|result| JsonRpcResponse::success(result, id)),
                     }
@@ -281,6 +567,44 @@ impl AppError {
     pub fn not_permitted() -> Self {
         Self::from(JsonRpcError::not_permitted())
     }
+
+    pub fn too_many_login_attempts(retry_after_s: i64) -> Self {
+        Self::from(
+            JsonRpcError::application_error(error_codes::application::TOO_MANY_LOGIN_ATTEMPTS)
+                .with_message("too many failed login attempts")
+                .with_data(serde_json::json!({ "retry_after_s": retry_after_s })),
+        )
+    }
+
+    /// The item has been changed since the version that the request was based on.
+    pub fn version_conflict(current_version: i64) -> Self {
+        Self::from(
+            JsonRpcError::application_error(error_codes::application::VERSION_CONFLICT)
+                .with_message("the item has been changed by someone else")
+                .with_data(serde_json::json!({ "current_version": current_version })),
+        )
+    }
+
+    pub fn item_does_not_exist() -> Self {
+        Self::from(
+            JsonRpcError::application_error(error_codes::application::ITEM_DOES_NOT_EXIST)
+                .with_message("the item does not exist"),
+        )
+    }
+
+    pub fn user_does_not_exist() -> Self {
+        Self::from(
+            JsonRpcError::application_error(error_codes::application::USER_DOES_NOT_EXIST)
+                .with_message("the user does not exist"),
+        )
+    }
+
+    pub fn totp_required() -> Self {
+        Self::from(
+            JsonRpcError::application_error(error_codes::application::TOTP_REQUIRED)
+                .with_message("two-factor code required"),
+        )
+    }
 }
 
 impl Display for AppError {
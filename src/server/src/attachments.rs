@@ -0,0 +1,268 @@
+use crate::sas::{self, SasError, SasToken};
+use hyper::{body::HttpBody, Body};
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+pub const ATTACHMENTS_URI: &str = "/api/attachments";
+
+/// How long the download url of an attachment is valid for.
+const DOWNLOAD_URL_VALIDITY_S: i64 = 60 * 60;
+
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+/// Stores the files of attachments on local disk, one file per attachment named by its id,
+/// and signs the urls they can be downloaded from.
+pub struct AttachmentStore {
+    root: PathBuf,
+    max_bytes: u64,
+    /// The name and value of the shared access key that download urls are signed with.
+    signing_key: Option<(String, String)>,
+}
+
+impl AttachmentStore {
+    pub fn new(root: PathBuf, max_bytes: u64, signing_key: Option<(String, String)>) -> Self {
+        Self {
+            root,
+            max_bytes,
+            signing_key,
+        }
+    }
+
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// Writes `body` to the file of `id` and returns its size,
+    /// nothing is kept if it fails or the body is larger than `max_bytes`.
+    pub async fn write(&self, id: &Uuid, body: Body) -> Result<u64, AttachmentError> {
+        tokio::fs::create_dir_all(&self.root).await?;
+
+        // written to a temporary file first, so that a partial upload is never served
+        let partial_path = self.root.join(format!("{}.part", id));
+        let result = write_limited(&partial_path, body, self.max_bytes).await;
+        let result = match result {
+            Ok(size) => tokio::fs::rename(&partial_path, self.path(id))
+                .await
+                .map(|_| size)
+                .map_err(AttachmentError::from),
+            Err(e) => Err(e),
+        };
+
+        if result.is_err() {
+            if let Err(e) = tokio::fs::remove_file(&partial_path).await {
+                warn!(
+                    "failed to remove partial attachment '{}' with error: '{}'",
+                    id, e
+                );
+            }
+        }
+
+        result
+    }
+
+    pub async fn open(&self, id: &Uuid) -> io::Result<tokio::fs::File> {
+        tokio::fs::File::open(self.path(id)).await
+    }
+
+    /// Removing the file of an attachment that has no file is not an error.
+    pub async fn remove(&self, id: &Uuid) -> io::Result<()> {
+        match tokio::fs::remove_file(self.path(id)).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// A url that the attachment `id` can be downloaded from until the returned unix timestamp,
+    /// relative to the server. `None` if the server has no key to sign it with.
+    pub fn download_url(&self, id: &Uuid, now: i64) -> Option<(String, i64)> {
+        let (key_name, key_value) = self.signing_key.as_ref()?;
+        let expiry = now + DOWNLOAD_URL_VALIDITY_S;
+        let resource_uri = download_uri(id);
+
+        // the fields of the token are used as the query of the url
+        let token = sas::generate(expiry, &resource_uri, key_value, key_name).ok()?;
+        let fields = token.split_once(' ')?.1;
+
+        Some((format!("{}?{}", resource_uri, fields), expiry))
+    }
+
+    /// Checks that the `query` of a download url is a valid signature for the attachment `id`.
+    pub fn verify_download(&self, id: &Uuid, query: &str, now: i64) -> Result<(), SasError> {
+        let (key_name, key_value) = self.signing_key.as_ref().ok_or(SasError::InvalidKey)?;
+        let token = SasToken::from_str(&format!("SharedAccessSignature {}", query))?;
+        if token.key_name() != key_name {
+            return Err(SasError::UnknownKeyName);
+        }
+
+        token.verify(key_value, now, Some(&download_uri(id)))
+    }
+
+    fn path(&self, id: &Uuid) -> PathBuf {
+        self.root.join(id.to_string())
+    }
+}
+
+fn download_uri(id: &Uuid) -> String {
+    format!("{}/{}", ATTACHMENTS_URI, id)
+}
+
+async fn write_limited(
+    path: &Path,
+    mut body: Body,
+    max_bytes: u64,
+) -> Result<u64, AttachmentError> {
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut size = 0;
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(AttachmentError::Body)?;
+        size += chunk.len() as u64;
+        if size > max_bytes {
+            return Err(AttachmentError::TooLarge);
+        }
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+
+    Ok(size)
+}
+
+/// The name of an uploaded file without any directories, quotes or control characters,
+/// `None` if nothing is left of it.
+pub fn sanitize_file_name(file_name: &str) -> Option<String> {
+    let base_name = file_name
+        .rsplit(&['/', '\\'][..])
+        .next()
+        .unwrap_or_default();
+    let sanitized: String = base_name
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(MAX_FILE_NAME_LENGTH)
+        .collect();
+    let sanitized = sanitized.trim();
+
+    if sanitized.is_empty() || sanitized == "." || sanitized == ".." {
+        None
+    } else {
+        Some(sanitized.to_owned())
+    }
+}
+
+#[derive(Debug)]
+pub enum AttachmentError {
+    /// The file is larger than the configured maximum size.
+    TooLarge,
+    Body(hyper::Error),
+    Io(io::Error),
+}
+
+impl From<io::Error> for AttachmentError {
+    fn from(e: io::Error) -> Self {
+        AttachmentError::Io(e)
+    }
+}
+
+impl Display for AttachmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttachmentError::TooLarge => write!(f, "file is too large"),
+            AttachmentError::Body(e) => write!(f, "failed to read the uploaded file: {}", e),
+            AttachmentError::Io(e) => write!(f, "failed to store the file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AttachmentError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    const NOW: i64 = 1_600_000_000;
+
+    fn store(root: PathBuf, max_bytes: u64) -> AttachmentStore {
+        AttachmentStore::new(
+            root,
+            max_bytes,
+            Some(("test_key".to_owned(), "c2VjcmV0IGtleSB2YWx1ZQ==".to_owned())),
+        )
+    }
+
+    #[test]
+    fn download_urls_are_signed_for_one_attachment() {
+        let store = store(PathBuf::from("attachments"), 10);
+        let id = Uuid::new_v4();
+
+        let (url, expiry) = store.download_url(&id, NOW).unwrap();
+        assert_eq!(expiry, NOW + DOWNLOAD_URL_VALIDITY_S);
+        let (path, query) = url.split_once('?').unwrap();
+        assert_eq!(path, format!("/api/attachments/{}", id));
+
+        assert_eq!(store.verify_download(&id, query, NOW), Ok(()));
+        assert_eq!(
+            store.verify_download(&Uuid::new_v4(), query, NOW),
+            Err(SasError::ResourceMismatch)
+        );
+        assert_eq!(
+            store.verify_download(&id, query, expiry),
+            Err(SasError::Expired)
+        );
+
+        let unsigned = AttachmentStore::new(PathBuf::from("attachments"), 10, None);
+        assert_eq!(unsigned.download_url(&id, NOW), None);
+        assert_eq!(
+            unsigned.verify_download(&id, query, NOW),
+            Err(SasError::InvalidKey)
+        );
+    }
+
+    #[tokio::test]
+    async fn files_larger_than_the_limit_are_not_kept() {
+        let root = std::env::temp_dir().join(format!("attachments-{}", Uuid::new_v4()));
+        let store = store(root.clone(), 4);
+
+        let small = Uuid::new_v4();
+        assert_eq!(store.write(&small, Body::from("abcd")).await.unwrap(), 4);
+        let mut content = Vec::new();
+        let mut file = store.open(&small).await.unwrap();
+        file.read_to_end(&mut content).await.unwrap();
+        assert_eq!(content, b"abcd");
+
+        let large = Uuid::new_v4();
+        assert!(matches!(
+            store.write(&large, Body::from("abcde")).await,
+            Err(AttachmentError::TooLarge)
+        ));
+        assert!(store.open(&large).await.is_err());
+
+        store.remove(&small).await.unwrap();
+        store.remove(&small).await.unwrap();
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 0);
+
+        std::fs::remove_dir(root).unwrap();
+    }
+
+    #[test]
+    fn file_names_are_sanitized() {
+        assert_eq!(
+            sanitize_file_name("receipt.pdf").as_deref(),
+            Some("receipt.pdf")
+        );
+        assert_eq!(
+            sanitize_file_name("../../etc/passwd").as_deref(),
+            Some("passwd")
+        );
+        assert_eq!(
+            sanitize_file_name("C:\\photos\\\"milk\".jpg\n").as_deref(),
+            Some("milk.jpg")
+        );
+        assert_eq!(sanitize_file_name("photos/"), None);
+        assert_eq!(sanitize_file_name(".."), None);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::auth::Claims;
+use database::AuditDb;
+use model::Method;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The entity that was changed by a mutating method.
+#[derive(Debug, Clone)]
+pub enum AuditEntity {
+    ListItem(String),
+    ListType(String),
+    ListTemplate(String),
+    User(String),
+}
+
+impl AuditEntity {
+    fn entity_type(&self) -> &'static str {
+        match self {
+            AuditEntity::ListItem(_) => "list_item",
+            AuditEntity::ListType(_) => "list_type",
+            AuditEntity::ListTemplate(_) => "list_template",
+            AuditEntity::User(_) => "user",
+        }
+    }
+
+    fn entity_id(&self) -> &str {
+        match self {
+            AuditEntity::ListItem(id)
+            | AuditEntity::ListType(id)
+            | AuditEntity::ListTemplate(id)
+            | AuditEntity::User(id) => id,
+        }
+    }
+}
+
+/// Records who changed what, and how, for mutating methods.
+pub struct Auditor {
+    db: Arc<AuditDb>,
+}
+
+impl Auditor {
+    pub fn new(db: Arc<AuditDb>) -> Self {
+        Self { db }
+    }
+
+    /// The change has already been made when this is called,
+    /// so failing to record it is logged instead of failing the request.
+    pub async fn record(
+        &self,
+        claims: &Option<Claims>,
+        method: Method,
+        entity: AuditEntity,
+        before: Option<JsonValue>,
+        after: Option<JsonValue>,
+    ) {
+        let id = Uuid::new_v4().to_string();
+        let actor_id = claims.as_ref().map(Claims::subject);
+        let method = method.to_string();
+        let before = before.map(|value| value.to_string());
+        let after = after.map(|value| value.to_string());
+
+        if let Err(e) = self
+            .db
+            .insert_audit_event(
+                &id,
+                actor_id,
+                &method,
+                entity.entity_type(),
+                entity.entity_id(),
+                before.as_deref(),
+                after.as_deref(),
+            )
+            .await
+        {
+            error!(
+                "failed to insert audit event for '{}' on {:?} with error: '{:?}'",
+                method, entity, e
+            );
+        }
+    }
+}
+
+/// Serialize `value` for use as `before` or `after` in an audit event.
+pub fn to_json<T>(value: &T) -> Option<JsonValue>
+where
+    T: Serialize,
+{
+    match serde_json::to_value(value) {
+        Ok(json) => Some(json),
+        Err(e) => {
+            error!("failed to serialize audit value with error: '{}'", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entity_types_and_ids() {
+        let entities = [
+            (AuditEntity::ListItem("item-id".to_owned()), "list_item"),
+            (AuditEntity::ListType("groceries".to_owned()), "list_type"),
+            (
+                AuditEntity::ListTemplate("template-id".to_owned()),
+                "list_template",
+            ),
+            (AuditEntity::User("user-id".to_owned()), "user"),
+        ];
+        for (entity, entity_type) in &entities {
+            assert_eq!(entity.entity_type(), *entity_type);
+        }
+        assert_eq!(entities[3].0.entity_id(), "user-id");
+    }
+
+    #[test]
+    fn values_are_serialized_as_json() {
+        #[derive(Serialize)]
+        struct Item {
+            name: &'static str,
+            quantity: Option<f64>,
+        }
+
+        let item = Item {
+            name: "milk",
+            quantity: None,
+        };
+        assert_eq!(
+            to_json(&item),
+            Some(serde_json::json!({ "name": "milk", "quantity": null }))
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::sas;
 use jsonwebtoken::{
     errors::Error as JwtError, Algorithm, DecodingKey, EncodingKey, Header, Validation,
 };
@@ -31,12 +32,17 @@ impl TokenHandler {
         }
     }
 
-    pub fn generate_token(&self, expiry: OffsetDateTime, mut roles: Vec<Role>) -> Option<String> {
+    pub fn generate_token(
+        &self,
+        user_id: String,
+        expiry: OffsetDateTime,
+        mut roles: Vec<Role>,
+    ) -> Option<String> {
         roles.push(Role::User);
         roles.push(Role::Anon);
         jsonwebtoken::encode(
             &Header::default(),
-            &Claims::new(expiry.unix_timestamp(), roles),
+            &Claims::new(user_id, expiry.unix_timestamp(), roles),
             &self.encoding_key,
         )
         .ok()
@@ -45,15 +51,53 @@ impl TokenHandler {
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Claims {
+    sub: String,
     exp: i64,
     roles: HashSet<String>,
+    /// If set, the claims are only valid for methods whose resource uri is covered by this uri.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
 }
 
 impl Claims {
-    pub fn new(exp: i64, roles: Vec<Role>) -> Self {
+    pub fn new(sub: String, exp: i64, roles: Vec<Role>) -> Self {
         Self {
+            sub,
             exp,
             roles: roles.into_iter().map(|r| r.to_string()).collect(),
+            scope: None,
+        }
+    }
+
+    /// Claims for a request authenticated with an SAS token, limited to the signed resource uri.
+    ///
+    /// SAS tokens are not issued to a user, so they can't be used for methods that need one,
+    /// and never for the methods of admins.
+    pub fn sas(key_name: &str, exp: i64, resource_uri: String) -> Self {
+        use Role::*;
+        Self {
+            scope: Some(resource_uri),
+            ..Self::new(format!("sas:{}", key_name), exp, vec![User, Anon])
+        }
+    }
+
+    fn covers(&self, method: Method) -> bool {
+        match &self.scope {
+            Some(scope) => sas::resource_covers(scope, &method_resource_uri(method)),
+            None => true,
+        }
+    }
+
+    /// Who the token was issued to, a user id or the key name of an SAS token.
+    pub fn subject(&self) -> &str {
+        &self.sub
+    }
+
+    /// The id of the user that the token was issued to, `None` for SAS tokens.
+    pub fn user_id(&self) -> Option<&str> {
+        match self.scope {
+            Some(_) => None,
+            None => Some(&self.sub),
         }
     }
 }
@@ -62,7 +106,7 @@ pub fn authenticate(method: Method, claims: &Option<Claims>) -> Result<(), ()> {
     let roles = method_roles(method);
     match claims {
         Some(claims) => {
-            if claims.roles.is_superset(&roles) {
+            if claims.roles.is_superset(&roles) && claims.covers(method) {
                 Ok(())
             } else {
                 Err(())
@@ -83,6 +127,45 @@ pub fn method_roles(method: Method) -> HashSet<String> {
     match method {
         Method::GetToken => vec![Anon],
         Method::GetDepartures => vec![Anon],
+        Method::GetArrivals => vec![Anon],
+        Method::SearchStops => vec![Anon],
+        Method::GetNearbyStops => vec![Anon],
+        Method::EnrollTotp => vec![User],
+        Method::ConfirmTotp => vec![User],
+        Method::AddListItem => vec![User],
+        Method::AddListItems => vec![User],
+        Method::GetListItems => vec![User],
+        Method::UpdateListItem => vec![User],
+        Method::DeleteListItem => vec![User],
+        Method::DeleteListItems => vec![User],
+        Method::CompleteListItem => vec![User],
+        Method::UncompleteListItem => vec![User],
+        Method::MoveListItem => vec![User],
+        Method::MoveListItems => vec![User],
+        Method::GetDeletedListItems => vec![User],
+        Method::RestoreListItem => vec![User],
+        Method::GetListItemHistory => vec![User],
+        Method::RevertListItem => vec![User],
+        Method::GetListItemAttachments => vec![User],
+        Method::AddListItemAttachment => vec![User],
+        Method::DeleteListItemAttachment => vec![User],
+        Method::RegenerateCalendarFeed => vec![User],
+        Method::GetListChanges => vec![User],
+        Method::GetListEventsUrl => vec![User],
+        Method::SearchListItems => vec![User],
+        Method::SetListItemRecurrence => vec![User],
+        Method::ExportList => vec![User],
+        Method::ImportList => vec![User],
+        Method::QuickAddListItem => vec![User],
+        Method::GetListTypes => vec![User],
+        Method::RenameListType => vec![User],
+        Method::DeleteListType => vec![User],
+        Method::MergeListTypes => vec![User],
+        Method::SaveListAsTemplate => vec![User],
+        Method::GetTemplates => vec![User],
+        Method::InstantiateTemplate => vec![User],
+        Method::ShareListType => vec![User],
+        Method::UnshareListType => vec![User],
         _default => vec![SuperAdmin],
     }
     .into_iter()
@@ -90,6 +173,11 @@ pub fn method_roles(method: Method) -> HashSet<String> {
     .collect()
 }
 
+/// The resource uri of a method, used to scope SAS tokens, e.g. `/api/get_list_items`.
+pub fn method_resource_uri(method: Method) -> String {
+    format!("{}/{}", crate::API_URI, method)
+}
+
 #[allow(unused)]
 #[derive(Clone, Copy, Debug)]
 pub enum Role {
@@ -133,6 +221,46 @@ impl Display for Role {
 mod tests {
     use super::*;
 
+    #[test]
+    fn sas_claims_are_scoped_to_resource_uri() {
+        let claims = Some(Claims::sas("key", 0, "/api/get_list_items".to_string()));
+
+        assert!(authenticate(Method::GetListItems, &claims).is_ok());
+        assert!(authenticate(Method::DeleteListItem, &claims).is_err());
+
+        let claims = Some(Claims::sas("key", 0, "/api".to_string()));
+        assert!(authenticate(Method::DeleteListItem, &claims).is_ok());
+    }
+
+    #[test]
+    fn attachment_uploads_are_a_method_of_their_own() {
+        let method = "add_list_item_attachment".parse::<Method>().unwrap();
+        assert!(matches!(method, Method::AddListItemAttachment));
+        assert_eq!(method.to_string(), "add_list_item_attachment");
+
+        let claims = Some(Claims::new("user-id".to_string(), 0, vec![Role::User]));
+        assert!(authenticate(method, &claims).is_ok());
+        assert!(authenticate(method, &None).is_err());
+    }
+
+    #[test]
+    fn sas_claims_are_not_admins_or_users() {
+        let claims = Claims::sas("key", 0, "/api".to_string());
+
+        for method in [
+            Method::AddUser,
+            Method::GenerateSasKey,
+            Method::GetAuditEvents,
+        ] {
+            assert!(authenticate(method, &Some(claims.clone())).is_err());
+        }
+        assert_eq!(claims.user_id(), None);
+        assert_eq!(claims.subject(), "sas:key");
+
+        let claims = Claims::new("user-id".to_string(), 0, vec![Role::User]);
+        assert_eq!(claims.user_id(), Some("user-id"));
+    }
+
     #[test]
     fn unix_timestamp_test() {
         let date = OffsetDateTime::from_unix_timestamp(1_546_300_800).unwrap();
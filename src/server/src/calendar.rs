@@ -0,0 +1,270 @@
+use model::{
+    chrono::{DateTime, Duration, NaiveDate, Utc},
+    list::ListItem,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+
+pub const CALENDAR_URI: &str = "/api/calendar";
+
+const TOKEN_LENGTH: usize = 40;
+
+const PRODUCT_ID: &str = "-//ijagberg//lists//EN";
+
+/// Longer lines are folded, see RFC 5545 section 3.1.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// How list items are shown in a calendar, not all calendars show to-dos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarComponent {
+    /// A to-do that is due on the due date of the item.
+    Todo,
+    /// An all-day event on the due date of the item.
+    Event,
+}
+
+impl FromStr for CalendarComponent {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "vtodo" => Ok(CalendarComponent::Todo),
+            "vevent" => Ok(CalendarComponent::Event),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A new secret token for a calendar feed.
+pub fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// Only the hash of a token is stored, so that the feeds can't be read from the database.
+pub fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// The url of the feed of `token`, relative to the server.
+pub fn feed_url(token: &str) -> String {
+    format!("{}/{}.ics", CALENDAR_URI, token)
+}
+
+/// The token of a feed url path like `/api/calendar/<token>.ics`.
+pub fn token_from_path(path: &str) -> Option<&str> {
+    let token = path
+        .strip_prefix(CALENDAR_URI)?
+        .strip_prefix('/')?
+        .strip_suffix(".ics")?;
+
+    if !token.is_empty() && token.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Some(token)
+    } else {
+        None
+    }
+}
+
+/// An RFC 5545 calendar with one component per list item that has a due date.
+///
+/// `now` is used as the time stamp of the components, since the feed is generated on every request.
+pub fn render(list_items: &[ListItem], component: CalendarComponent, now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_owned(),
+        "X-WR-CALNAME:Lists".to_owned(),
+    ];
+
+    for list_item in list_items {
+        let due_date = match list_item.details.due_date {
+            Some(due_date) => due_date,
+            None => continue,
+        };
+
+        let name = match component {
+            CalendarComponent::Todo => "VTODO",
+            CalendarComponent::Event => "VEVENT",
+        };
+        lines.push(format!("BEGIN:{}", name));
+        lines.push(format!("UID:{}", list_item.id));
+        lines.push(format!("DTSTAMP:{}", date_time(now)));
+        lines.push(format!("SEQUENCE:{}", (list_item.version - 1).max(0)));
+        lines.push(format!("SUMMARY:{}", escape_text(&list_item.item_name)));
+        lines.push(format!("CATEGORIES:{}", escape_text(&list_item.list_type)));
+        if let Some(description) = description(list_item) {
+            lines.push(format!("DESCRIPTION:{}", escape_text(&description)));
+        }
+
+        match component {
+            CalendarComponent::Todo => {
+                lines.push(format!("DUE;VALUE=DATE:{}", date(due_date)));
+                match list_item.completed_at {
+                    Some(completed_at) => {
+                        lines.push("STATUS:COMPLETED".to_owned());
+                        lines.push(format!("COMPLETED:{}", date_time(completed_at)));
+                    }
+                    None => lines.push("STATUS:NEEDS-ACTION".to_owned()),
+                }
+            }
+            CalendarComponent::Event => {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", date(due_date)));
+                lines.push(format!(
+                    "DTEND;VALUE=DATE:{}",
+                    date(due_date + Duration::days(1))
+                ));
+                lines.push("TRANSP:TRANSPARENT".to_owned());
+            }
+        }
+        lines.push(format!("END:{}", name));
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines
+        .iter()
+        .map(|line| fold_line(line))
+        .map(|line| line + "\r\n")
+        .collect()
+}
+
+/// The quantity and notes of an item, on separate lines.
+fn description(list_item: &ListItem) -> Option<String> {
+    let details = &list_item.details;
+    let quantity = details
+        .quantity
+        .map(|quantity| match details.unit.as_deref() {
+            Some(unit) => format!("{} {}", quantity, unit),
+            None => quantity.to_string(),
+        });
+    let lines: Vec<_> = quantity.into_iter().chain(details.notes.clone()).collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+fn date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+fn date_time(date_time: DateTime<Utc>) -> String {
+    date_time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+/// Splits a line into lines of at most `MAX_LINE_OCTETS` octets, without splitting characters,
+/// where every line but the first starts with a space.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_octets = 0;
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{chrono::TimeZone, list::ListItemDetails};
+    use uuid::Uuid;
+
+    fn list_item(due_date: Option<NaiveDate>, completed: bool) -> ListItem {
+        ListItem::new(
+            Uuid::parse_str("6f1c0a5e-2b7e-4a43-9a3e-0c5b3d1e6a11").unwrap(),
+            "groceries".to_owned(),
+            "milk, lactose free".to_owned(),
+            3,
+            ListItemDetails::new(
+                Some(2.0),
+                Some("l".to_owned()),
+                Some("the blue one".to_owned()),
+                due_date,
+            ),
+            if completed {
+                Some(Utc.with_ymd_and_hms(2022, 3, 3, 18, 30, 0).unwrap())
+            } else {
+                None
+            },
+        )
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2022, 3, 2, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn renders_items_with_due_dates() {
+        let due_date = NaiveDate::from_ymd_opt(2022, 3, 4);
+        let items = [list_item(due_date, true), list_item(None, false)];
+
+        let todos = render(&items, CalendarComponent::Todo, now());
+        assert_eq!(
+            todos,
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//ijagberg//lists//EN\r\n\
+             CALSCALE:GREGORIAN\r\n\
+             X-WR-CALNAME:Lists\r\n\
+             BEGIN:VTODO\r\n\
+             UID:6f1c0a5e-2b7e-4a43-9a3e-0c5b3d1e6a11\r\n\
+             DTSTAMP:20220302T120000Z\r\n\
+             SEQUENCE:2\r\n\
+             SUMMARY:milk\\, lactose free\r\n\
+             CATEGORIES:groceries\r\n\
+             DESCRIPTION:2 l\\nthe blue one\r\n\
+             DUE;VALUE=DATE:20220304\r\n\
+             STATUS:COMPLETED\r\n\
+             COMPLETED:20220303T183000Z\r\n\
+             END:VTODO\r\n\
+             END:VCALENDAR\r\n"
+        );
+
+        let events = render(&items[..1], CalendarComponent::Event, now());
+        assert!(events.contains("DTSTART;VALUE=DATE:20220304\r\nDTEND;VALUE=DATE:20220305\r\n"));
+        assert!(!events.contains("STATUS"));
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let line = format!("SUMMARY:{}", "å".repeat(40));
+        let folded = fold_line(&line);
+
+        let lines: Vec<_> = folded.split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn feed_urls() {
+        let token = generate_token();
+        assert_eq!(token.len(), TOKEN_LENGTH);
+        assert_eq!(token_from_path(&feed_url(&token)), Some(token.as_str()));
+        assert_eq!(token_from_path("/api/calendar/../users.ics"), None);
+        assert_eq!(token_from_path("/api/calendar/.ics"), None);
+        assert_ne!(token_hash(&token), token_hash(&generate_token()));
+    }
+}
@@ -1,9 +1,24 @@
+use crate::{
+    app::{AppError, AppResult},
+    auth::Claims,
+};
+
+pub use audit::AuditController;
 pub use list::ListItemController;
 pub use server::ServerController;
 pub use traffic::TrafficController;
 pub use user::UserController;
 
+mod audit;
 mod list;
 mod server;
 mod traffic;
-mod user;
\ No newline at end of file
+mod user;
+
+/// The id of the user that the token was issued to, SAS tokens are not issued to a user.
+fn subject(claims: &Option<Claims>) -> AppResult<&str> {
+    claims
+        .as_ref()
+        .and_then(Claims::user_id)
+        .ok_or_else(AppError::not_permitted)
+}
@@ -0,0 +1,81 @@
+use crate::app::{AppError, AppResult, ParamsError};
+use database::{AuditDb, AuditEvent as DbAuditEvent, AuditEventFilter};
+use model::{
+    audit::{get_audit_events, AuditEvent},
+    JsonRpcRequest,
+};
+use std::{convert::TryFrom, str::FromStr, sync::Arc};
+use uuid::Uuid;
+
+pub struct AuditController {
+    db: Arc<AuditDb>,
+}
+
+impl AuditController {
+    pub fn new(db: Arc<AuditDb>) -> Self {
+        Self { db }
+    }
+
+    pub async fn get_audit_events(
+        &self,
+        request: JsonRpcRequest,
+    ) -> AppResult<get_audit_events::MethodResult> {
+        use get_audit_events::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+
+        let filter = AuditEventFilter {
+            actor_id: params.actor_id.as_deref(),
+            method: params.method.as_deref(),
+            entity_type: params.entity_type.as_deref(),
+            entity_id: params.entity_id.as_deref(),
+        };
+
+        let events = self
+            .db
+            .get_audit_events(&filter, params.limit as i64, params.offset as i64)
+            .await?;
+
+        let events: Vec<AuditEvent> = events
+            .into_iter()
+            .map(|e| AuditEventWrapper::try_from(e).map(|w| w.0))
+            .collect::<Result<_, _>>()?;
+
+        Ok(MethodResult::new(events))
+    }
+}
+
+impl ParamsError for get_audit_events::InvalidParams {}
+
+struct AuditEventWrapper(AuditEvent);
+
+impl TryFrom<DbAuditEvent> for AuditEventWrapper {
+    type Error = AppError;
+
+    fn try_from(db_event: DbAuditEvent) -> Result<Self, Self::Error> {
+        let id = Uuid::from_str(&db_event.id)
+            .map_err(|e| AppError::internal_error().with_context(&e))?;
+        let before = db_event
+            .before
+            .map(|before| serde_json::from_str(&before))
+            .transpose()?;
+        let after = db_event
+            .after
+            .map(|after| serde_json::from_str(&after))
+            .transpose()?;
+        let created = crate::chrono_timestamp(
+            db_event.created.unix_timestamp(),
+            db_event.created.nanosecond(),
+        );
+
+        Ok(AuditEventWrapper(AuditEvent::new(
+            id,
+            db_event.actor_id,
+            db_event.method,
+            db_event.entity_type,
+            db_event.entity_id,
+            before,
+            after,
+            created,
+        )))
+    }
+}
@@ -1,51 +1,255 @@
+use super::subject;
 use crate::{
     app::{AppResult, ParamsError},
-    AppError,
+    attachments::{AttachmentError, AttachmentStore},
+    audit::{self, AuditEntity, Auditor},
+    auth::Claims,
+    calendar::{self, CalendarComponent},
+    list_events::{ListEvents, Subscription},
+    list_format, AppError,
 };
-use database::{Database, InsertionResult, ListItem as DbListItem};
-use model::{list::*, JsonRpcError, JsonRpcRequest};
+use database::{
+    Database, DuplicateItemStrategy, InsertionResult, ListItem as DbListItem,
+    ListItemAttachment as DbListItemAttachment, ListItemChange as DbListItemChange,
+    ListItemDetails as DbListItemDetails, ListItemMove, ListItemPlacement,
+    ListItemVersion as DbListItemVersion, ListShare as DbListShare, ListTemplate as DbListTemplate,
+    ListTemplateItem as DbListTemplateItem, NewListItem as DbNewListItem,
+    NewListItemAttachment as DbNewListItemAttachment, TemplateInstantiation,
+};
+use hyper::Body;
+use model::{
+    chrono::{NaiveDate, Utc},
+    list::*,
+    JsonRpcError, JsonRpcRequest, Method,
+};
+use serde_json::json;
 use std::{collections::HashSet, convert::TryFrom, str::FromStr, sync::Arc};
 use uuid::Uuid;
 
 pub struct ListItemController {
     db: Arc<Database<DbListItem>>,
+    auditor: Arc<Auditor>,
+    attachments: Arc<AttachmentStore>,
+    list_events: ListEvents,
 }
 
 impl ListItemController {
-    pub fn new(list_item_db: Arc<Database<DbListItem>>) -> Self {
-        Self { db: list_item_db }
+    pub fn new(
+        list_item_db: Arc<Database<DbListItem>>,
+        auditor: Arc<Auditor>,
+        attachments: Arc<AttachmentStore>,
+        list_events: ListEvents,
+    ) -> Self {
+        Self {
+            db: list_item_db,
+            auditor,
+            attachments,
+            list_events,
+        }
     }
 
     pub async fn add_list_item(
         &self,
         request: JsonRpcRequest,
+        claims: &Option<Claims>,
     ) -> AppResult<add_list_item::MethodResult> {
         use add_list_item::{MethodResult, Params};
         let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let owner_id = params.owner_id.as_deref().unwrap_or(user_id);
+
+        self.check_permission(user_id, owner_id, &params.list_type, ListPermission::Write)
+            .await?;
 
         let new_item_id = params.id.unwrap_or_else(Uuid::new_v4);
         let list_type = params.list_type;
         let item_name = params.item_name;
+        let due_date = params.details.due_date.map(|date| date.to_string());
 
         let result = self
             .db
-            .insert_list_item(&new_item_id.to_string(), &list_type, &item_name)
+            .insert_list_item(
+                &new_item_id.to_string(),
+                owner_id,
+                &list_type,
+                &item_name,
+                db_details(&params.details, due_date.as_deref()),
+                Some(user_id),
+            )
             .await?;
 
         match result {
-            InsertionResult::Inserted => Ok(MethodResult::new(true, Some(new_item_id))),
+            InsertionResult::Inserted => {
+                let item =
+                    ListItem::new(new_item_id, list_type, item_name, 1, params.details, None);
+                self.auditor
+                    .record(
+                        claims,
+                        Method::AddListItem,
+                        AuditEntity::ListItem(new_item_id.to_string()),
+                        None,
+                        audit::to_json(&item),
+                    )
+                    .await;
+                Ok(MethodResult::new(true, Some(new_item_id)))
+            }
             InsertionResult::AlreadyExists => Ok(MethodResult::new(false, None)),
         }
     }
 
+    pub async fn quick_add_list_item(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<quick_add_list_item::MethodResult> {
+        use quick_add_list_item::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let owner_id = params.owner_id.as_deref().unwrap_or(user_id);
+        let list_type = params.list_type().to_owned();
+
+        self.check_permission(user_id, owner_id, &list_type, ListPermission::Write)
+            .await?;
+
+        if params.dry_run {
+            return Ok(MethodResult::preview(params.parsed));
+        }
+
+        let new_item_id = Uuid::new_v4();
+        let details = params.parsed.details();
+        let due_date = details.due_date.map(|date| date.to_string());
+
+        let result = self
+            .db
+            .insert_list_item(
+                &new_item_id.to_string(),
+                owner_id,
+                &list_type,
+                &params.parsed.item_name,
+                db_details(&details, due_date.as_deref()),
+                Some(user_id),
+            )
+            .await?;
+        if result != InsertionResult::Inserted {
+            return Err(AppError::internal_error()
+                .with_context(&"a quick added list item with a new id already existed"));
+        }
+
+        let item = ListItem::new(
+            new_item_id,
+            list_type,
+            params.parsed.item_name.clone(),
+            1,
+            details,
+            None,
+        );
+        self.auditor
+            .record(
+                claims,
+                Method::QuickAddListItem,
+                AuditEntity::ListItem(new_item_id.to_string()),
+                None,
+                audit::to_json(&item),
+            )
+            .await;
+
+        Ok(MethodResult::added(params.parsed, item))
+    }
+
+    pub async fn add_list_items(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<add_list_items::MethodResult> {
+        use add_list_items::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let owner_id = params.owner_id.as_deref().unwrap_or(user_id);
+
+        self.check_permission(user_id, owner_id, &params.list_type, ListPermission::Write)
+            .await?;
+
+        let ids: Vec<Uuid> = params
+            .items
+            .iter()
+            .map(|item| item.id.unwrap_or_else(Uuid::new_v4))
+            .collect();
+        let db_ids: Vec<String> = ids.iter().map(Uuid::to_string).collect();
+        let due_dates: Vec<Option<String>> = params
+            .items
+            .iter()
+            .map(|item| item.details.due_date.map(|date| date.to_string()))
+            .collect();
+        let new_items: Vec<DbNewListItem> = params
+            .items
+            .iter()
+            .zip(&db_ids)
+            .zip(&due_dates)
+            .map(|((item, id), due_date)| DbNewListItem {
+                id,
+                item_name: &item.item_name,
+                details: db_details(&item.details, due_date.as_deref()),
+                completed: false,
+            })
+            .collect();
+
+        let outcomes = self
+            .db
+            .insert_list_items(owner_id, &params.list_type, &new_items, Some(user_id))
+            .await?
+            .into_iter()
+            .map(|result| match result {
+                InsertionResult::Inserted => Ok(()),
+                InsertionResult::AlreadyExists => Err(BulkItemError::AlreadyExists),
+            })
+            .collect();
+        let results = bulk_item_results(&ids, outcomes);
+
+        if results.iter().all(|result| result.success) {
+            info!(
+                "added {} list items to '{}' of '{}'",
+                ids.len(),
+                params.list_type,
+                owner_id
+            );
+            for (id, item) in ids.iter().zip(params.items) {
+                let item = ListItem::new(
+                    *id,
+                    params.list_type.clone(),
+                    item.item_name,
+                    1,
+                    item.details,
+                    None,
+                );
+                self.auditor
+                    .record(
+                        claims,
+                        Method::AddListItems,
+                        AuditEntity::ListItem(id.to_string()),
+                        None,
+                        audit::to_json(&item),
+                    )
+                    .await;
+            }
+        }
+
+        Ok(MethodResult::new(results))
+    }
+
     pub async fn get_list_items(
         &self,
         request: JsonRpcRequest,
+        claims: &Option<Claims>,
     ) -> AppResult<get_list_items::MethodResult> {
         use get_list_items::{MethodResult, Params};
         let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let owner_id = params.owner_id.as_deref().unwrap_or(user_id);
 
-        let list_items = self.db.get_list_items(&params.list_type).await?;
+        self.check_permission(user_id, owner_id, &params.list_type, ListPermission::Read)
+            .await?;
+
+        let list_items = self.db.get_list_items(owner_id, &params.list_type).await?;
 
         let list_items: Vec<ListItem> = list_items
             .into_iter()
@@ -55,54 +259,1536 @@ impl ListItemController {
         Ok(MethodResult::new(list_items))
     }
 
+    /// The changes of the list type of the owner (the caller by default) as server-sent events,
+    /// if the caller is allowed to read it.
+    pub async fn subscribe_list_events(
+        &self,
+        claims: &Option<Claims>,
+        owner_id: Option<&str>,
+        list_type: &str,
+    ) -> AppResult<Body> {
+        let user_id = subject(claims)?;
+        let subscription = Subscription {
+            user_id: user_id.to_owned(),
+            owner_id: owner_id.unwrap_or(user_id).to_owned(),
+            list_type: list_type.to_owned(),
+        };
+
+        self.subscribe(subscription).await
+    }
+
+    /// The changes of the list type that `query` is the signature of a subscription url of.
+    pub async fn subscribe_signed_list_events(&self, query: &str) -> AppResult<Body> {
+        let subscription = self
+            .list_events
+            .verify_subscription(query, crate::current_timestamp_s())
+            .map_err(|e| AppError::not_permitted().with_context(&e))?;
+
+        self.subscribe(subscription).await
+    }
+
+    /// A short-lived url to subscribe to the changes of a list type with,
+    /// for clients that can't set the `Authorization` header of the subscription.
+    pub async fn get_list_events_url(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<get_list_events_url::MethodResult> {
+        use get_list_events_url::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let owner_id = params.owner_id.as_deref().unwrap_or(user_id);
+
+        self.check_permission(user_id, owner_id, &params.list_type, ListPermission::Read)
+            .await?;
+
+        let subscription = Subscription {
+            user_id: user_id.to_owned(),
+            owner_id: owner_id.to_owned(),
+            list_type: params.list_type,
+        };
+        match self
+            .list_events
+            .subscription_url(&subscription, crate::current_timestamp_s())
+        {
+            Some((url, expiry)) => Ok(MethodResult::new(url, crate::chrono_timestamp(expiry, 0))),
+            None => Err(AppError::not_implemented()
+                .with_context(&"no shared access key to sign subscription urls with")),
+        }
+    }
+
+    /// Checks that the subscriber is still allowed to read the list type, which may have changed
+    /// since a subscription url was signed.
+    async fn subscribe(&self, subscription: Subscription) -> AppResult<Body> {
+        self.check_permission(
+            &subscription.user_id,
+            &subscription.owner_id,
+            &subscription.list_type,
+            ListPermission::Read,
+        )
+        .await?;
+
+        Ok(self
+            .list_events
+            .subscribe(subscription.owner_id, subscription.list_type))
+    }
+
+    pub async fn get_list_changes(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<get_list_changes::MethodResult> {
+        use get_list_changes::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let owner_id = params.owner_id.as_deref().unwrap_or(user_id);
+
+        let list_types = self.readable_list_types(user_id, owner_id).await?;
+
+        // one more change than requested is fetched, to tell if there are more
+        let limit = params.limit as usize;
+        let mut db_changes = self
+            .db
+            .get_list_item_changes(
+                owner_id,
+                list_types.as_deref(),
+                params.since_revision,
+                limit as i64 + 1,
+            )
+            .await?;
+        let has_more = db_changes.len() > limit;
+        db_changes.truncate(limit);
+
+        let changes = db_changes
+            .into_iter()
+            .map(|db_change| list_item_change(db_change, params.since_revision))
+            .collect::<Result<_, _>>()?;
+
+        Ok(MethodResult::new(changes, params.since_revision, has_more))
+    }
+
+    pub async fn search_list_items(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<search_list_items::MethodResult> {
+        use search_list_items::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let owner_id = params.owner_id.as_deref().unwrap_or(user_id);
+
+        let list_types = match params.list_type {
+            Some(list_type) => {
+                self.check_permission(user_id, owner_id, &list_type, ListPermission::Read)
+                    .await?;
+                Some(vec![list_type])
+            }
+            None => self.readable_list_types(user_id, owner_id).await?,
+        };
+
+        // one more hit than requested is fetched, to tell if there are more
+        let limit = params.limit as usize;
+        let mut db_hits = self
+            .db
+            .search_list_items(
+                owner_id,
+                list_types.as_deref(),
+                &params.query,
+                limit as i64 + 1,
+                params.offset as i64,
+            )
+            .await?;
+        let has_more = db_hits.len() > limit;
+        db_hits.truncate(limit);
+
+        let hits = db_hits
+            .into_iter()
+            .map(|db_hit| {
+                let list_item = ListItemWrapper::try_from(db_hit.list_item)?.0;
+                let highlight =
+                    SearchHighlight::new(db_hit.item_name_highlight, db_hit.notes_highlight);
+                Ok(ListItemSearchHit::new(list_item, db_hit.rank, highlight))
+            })
+            .collect::<AppResult<_>>()?;
+
+        Ok(MethodResult::new(hits, has_more))
+    }
+
+    pub async fn export_list(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<export_list::MethodResult> {
+        use export_list::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let owner_id = params.owner_id.as_deref().unwrap_or(user_id);
+
+        self.check_permission(user_id, owner_id, &params.list_type, ListPermission::Read)
+            .await?;
+
+        let list_items: Vec<ListItem> = self
+            .db
+            .get_list_items(owner_id, &params.list_type)
+            .await?
+            .into_iter()
+            .map(|li| ListItemWrapper::try_from(li).map(|w| w.0))
+            .collect::<Result<_, _>>()?;
+
+        let content = list_format::export(params.format, &params.list_type, &list_items)
+            .map_err(|e| AppError::internal_error().with_context(&e))?;
+
+        Ok(MethodResult::new(params.format, content))
+    }
+
+    pub async fn import_list(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<import_list::MethodResult> {
+        use import_list::{ImportLine, ImportLineStatus, MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let owner_id = params.owner_id.as_deref().unwrap_or(user_id);
+
+        self.check_permission(user_id, owner_id, &params.list_type, ListPermission::Write)
+            .await?;
+
+        let mut item_names: HashSet<String> = self
+            .db
+            .get_list_items(owner_id, &params.list_type)
+            .await?
+            .into_iter()
+            .map(|li| li.item_name.to_lowercase())
+            .collect();
+
+        let mut lines = Vec::new();
+        let mut records = Vec::new();
+        for parsed in list_format::parse(params.format, &params.content) {
+            match parsed.record {
+                Ok(record) if item_names.insert(record.item_name.to_lowercase()) => {
+                    lines.push(ImportLine::ok(parsed.line, record.item_name.clone()));
+                    records.push(record);
+                }
+                Ok(record) => lines.push(ImportLine::duplicate(parsed.line, record.item_name)),
+                Err(error) => lines.push(ImportLine::invalid(parsed.line, error)),
+            }
+        }
+
+        if params.dry_run {
+            return Ok(MethodResult::new(true, 0, lines));
+        }
+        if lines
+            .iter()
+            .any(|line| line.status == ImportLineStatus::Invalid)
+        {
+            return Err(invalid_import_error(lines));
+        }
+
+        let ids: Vec<String> = records.iter().map(|_| Uuid::new_v4().to_string()).collect();
+        let details: Vec<ListItemDetails> = records.iter().map(|r| r.details()).collect();
+        let due_dates: Vec<Option<String>> = details
+            .iter()
+            .map(|details| details.due_date.map(|date| date.to_string()))
+            .collect();
+        let new_items: Vec<DbNewListItem> = records
+            .iter()
+            .zip(&ids)
+            .zip(details.iter().zip(&due_dates))
+            .map(|((record, id), (details, due_date))| DbNewListItem {
+                id,
+                item_name: &record.item_name,
+                details: db_details(details, due_date.as_deref()),
+                completed: record.completed,
+            })
+            .collect();
+
+        let results = self
+            .db
+            .insert_list_items(owner_id, &params.list_type, &new_items, Some(user_id))
+            .await?;
+        if results.iter().any(|r| *r != InsertionResult::Inserted) {
+            return Err(AppError::internal_error()
+                .with_context(&"an imported list item with a new id already existed"));
+        }
+
+        info!(
+            "imported {} list items to '{}' of '{}'",
+            new_items.len(),
+            params.list_type,
+            owner_id
+        );
+        self.auditor
+            .record(
+                claims,
+                Method::ImportList,
+                AuditEntity::ListType(params.list_type.clone()),
+                None,
+                Some(json!({
+                    "list_type": params.list_type,
+                    "format": params.format,
+                    "imported_items": new_items.len(),
+                })),
+            )
+            .await;
+
+        Ok(MethodResult::new(false, new_items.len(), lines))
+    }
+
+    pub async fn update_list_item(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<update_list_item::MethodResult> {
+        use update_list_item::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let id = params.id.to_string();
+
+        let db_list_item = match self.get_writable_list_item(user_id, &id).await? {
+            Some(db_list_item) => db_list_item,
+            None => return Ok(MethodResult::missing()),
+        };
+
+        if db_list_item.version != params.version {
+            return Err(AppError::version_conflict(db_list_item.version));
+        }
+
+        let due_date = params.details.due_date.map(|date| date.to_string());
+        let updated = match self
+            .db
+            .update_list_item(
+                &id,
+                params.version,
+                &params.item_name,
+                db_details(&params.details, due_date.as_deref()),
+                Some(user_id),
+            )
+            .await?
+        {
+            Some(updated) => ListItemWrapper::try_from(updated)?.0,
+            // changed or deleted after it was read above
+            None => {
+                return match self.db.get_list_item(&id).await? {
+                    Some(current) => Err(AppError::version_conflict(current.version)),
+                    None => Ok(MethodResult::missing()),
+                }
+            }
+        };
+
+        let before = ListItemWrapper::try_from(db_list_item)?.0;
+        self.auditor
+            .record(
+                claims,
+                Method::UpdateListItem,
+                AuditEntity::ListItem(id),
+                audit::to_json(&before),
+                audit::to_json(&updated),
+            )
+            .await;
+
+        Ok(MethodResult::updated(updated))
+    }
+
     pub async fn delete_list_item(
         &self,
         request: JsonRpcRequest,
+        claims: &Option<Claims>,
     ) -> AppResult<delete_list_item::MethodResult> {
         use delete_list_item::{MethodResult, Params};
         let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
 
         let id = params.id.to_string();
 
-        info!("deleting list item with id '{}'", id);
+        let db_list_item = match self.get_writable_list_item(user_id, &id).await? {
+            Some(db_list_item) => db_list_item,
+            None => return Ok(MethodResult::new(false)),
+        };
+
+        info!("moving list item with id '{}' to the trash", id);
 
-        let result = self.db.delete_list_item(&id).await?;
+        let before = ListItemWrapper::try_from(db_list_item)?.0;
+
+        let result = self.db.delete_list_item(&id, Some(user_id)).await?;
+
+        if result {
+            self.auditor
+                .record(
+                    claims,
+                    Method::DeleteListItem,
+                    AuditEntity::ListItem(id),
+                    audit::to_json(&before),
+                    None,
+                )
+                .await;
+        }
 
         Ok(MethodResult::new(result))
     }
 
+    pub async fn delete_list_items(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<delete_list_items::MethodResult> {
+        use delete_list_items::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let list_items = self.get_writable_list_items(user_id, &params.ids).await?;
+        if list_items.iter().any(Result::is_err) {
+            let outcomes = list_items
+                .into_iter()
+                .map(|item| item.map(|_| ()))
+                .collect();
+            return Ok(MethodResult::new(bulk_item_results(&params.ids, outcomes)));
+        }
+
+        info!("moving {} list items to the trash", params.ids.len());
+
+        let db_ids: Vec<String> = params.ids.iter().map(Uuid::to_string).collect();
+        let outcomes = self
+            .db
+            .delete_list_items(&db_ids, Some(user_id))
+            .await?
+            .into_iter()
+            .map(|deleted| {
+                if deleted {
+                    Ok(())
+                } else {
+                    Err(BulkItemError::DoesNotExist)
+                }
+            })
+            .collect();
+        let results = bulk_item_results(&params.ids, outcomes);
+
+        if results.iter().all(|result| result.success) {
+            for (id, before) in db_ids.into_iter().zip(list_items.into_iter().flatten()) {
+                self.auditor
+                    .record(
+                        claims,
+                        Method::DeleteListItems,
+                        AuditEntity::ListItem(id),
+                        audit::to_json(&before),
+                        None,
+                    )
+                    .await;
+            }
+        }
+
+        Ok(MethodResult::new(results))
+    }
+
+    pub async fn get_deleted_list_items(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<get_deleted_list_items::MethodResult> {
+        use get_deleted_list_items::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let owner_id = params.owner_id.as_deref().unwrap_or(user_id);
+
+        self.check_permission(user_id, owner_id, &params.list_type, ListPermission::Read)
+            .await?;
+
+        let list_items = self
+            .db
+            .get_deleted_list_items(owner_id, &params.list_type)
+            .await?
+            .into_iter()
+            .map(deleted_list_item)
+            .collect::<Result<_, _>>()?;
+
+        Ok(MethodResult::new(list_items))
+    }
+
+    pub async fn restore_list_item(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<restore_list_item::MethodResult> {
+        use restore_list_item::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let id = params.id.to_string();
+
+        let db_list_item = match self.db.get_deleted_list_item(&id).await? {
+            Some(db_list_item) => db_list_item,
+            None => return Ok(MethodResult::missing()),
+        };
+        self.check_item_permission(user_id, &db_list_item, ListPermission::Write)
+            .await?;
+        let before = deleted_list_item(db_list_item)?;
+
+        let restored = match self.db.restore_list_item(&id, Some(user_id)).await? {
+            Some(restored) => ListItemWrapper::try_from(restored)?.0,
+            None => return Ok(MethodResult::missing()),
+        };
+
+        self.auditor
+            .record(
+                claims,
+                Method::RestoreListItem,
+                AuditEntity::ListItem(id),
+                audit::to_json(&before),
+                audit::to_json(&restored),
+            )
+            .await;
+
+        Ok(MethodResult::restored(restored))
+    }
+
+    pub async fn get_list_item_history(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<get_list_item_history::MethodResult> {
+        use get_list_item_history::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let id = params.id.to_string();
+
+        // the history of items in the trash can also be read
+        let db_list_item = match self.db.get_list_item(&id).await? {
+            Some(db_list_item) => db_list_item,
+            None => match self.db.get_deleted_list_item(&id).await? {
+                Some(db_list_item) => db_list_item,
+                None => return Ok(MethodResult::missing()),
+            },
+        };
+        self.check_item_permission(user_id, &db_list_item, ListPermission::Read)
+            .await?;
+
+        let history = self
+            .db
+            .get_list_item_history(&id)
+            .await?
+            .into_iter()
+            .map(list_item_version)
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(MethodResult::found(history))
+    }
+
+    pub async fn revert_list_item(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<revert_list_item::MethodResult> {
+        use revert_list_item::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let id = params.id.to_string();
+
+        let before = match self.get_writable_list_item(user_id, &id).await? {
+            Some(db_list_item) => ListItemWrapper::try_from(db_list_item)?.0,
+            None => return Ok(MethodResult::missing()),
+        };
+
+        let reverted = match self
+            .db
+            .revert_list_item(&id, params.version, Some(user_id))
+            .await?
+        {
+            Some(reverted) => ListItemWrapper::try_from(reverted)?.0,
+            None => return Ok(MethodResult::missing()),
+        };
+
+        self.auditor
+            .record(
+                claims,
+                Method::RevertListItem,
+                AuditEntity::ListItem(id),
+                audit::to_json(&before),
+                audit::to_json(&reverted),
+            )
+            .await;
+
+        Ok(MethodResult::reverted(reverted))
+    }
+
+    /// Stores `body` as an attachment of a list item, which requires write access to the item.
+    ///
+    /// `file_name` and `content_type` are expected to have been validated by the caller.
+    pub async fn add_list_item_attachment(
+        &self,
+        claims: &Option<Claims>,
+        list_item_id: Uuid,
+        file_name: &str,
+        content_type: &str,
+        body: Body,
+    ) -> AppResult<Attachment> {
+        let user_id = subject(claims)?;
+        let list_item_id = list_item_id.to_string();
+
+        if self
+            .get_writable_list_item(user_id, &list_item_id)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::item_does_not_exist());
+        }
+
+        let id = Uuid::new_v4();
+        let size_bytes = match self.attachments.write(&id, body).await {
+            Ok(size_bytes) => size_bytes,
+            Err(AttachmentError::TooLarge) => {
+                return Err(AppError::invalid_params().with_message(&format!(
+                    "attachments can't be larger than {} bytes",
+                    self.attachments.max_bytes()
+                )))
+            }
+            Err(e) => return Err(AppError::internal_error().with_context(&e)),
+        };
+
+        let new_attachment = DbNewListItemAttachment {
+            id: &id.to_string(),
+            list_item_id: &list_item_id,
+            file_name,
+            content_type,
+            size_bytes: size_bytes as i64,
+            uploaded_by: Some(user_id),
+        };
+        let db_attachment = match self.db.insert_list_item_attachment(&new_attachment).await {
+            Ok(Some(db_attachment)) => db_attachment,
+            // the item was deleted during the upload
+            result => {
+                self.attachments.remove(&id).await?;
+                result?;
+                return Err(AppError::item_does_not_exist());
+            }
+        };
+        let attachment = self.attachment(db_attachment)?;
+
+        self.auditor
+            .record(
+                claims,
+                Method::AddListItemAttachment,
+                AuditEntity::ListItem(list_item_id),
+                None,
+                audit::to_json(&json!({ "attachment": &attachment })),
+            )
+            .await;
+
+        Ok(attachment)
+    }
+
+    pub async fn get_list_item_attachments(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<get_list_item_attachments::MethodResult> {
+        use get_list_item_attachments::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let id = params.id.to_string();
+
+        let db_list_item = match self.db.get_list_item(&id).await? {
+            Some(db_list_item) => db_list_item,
+            None => return Ok(MethodResult::missing()),
+        };
+        self.check_item_permission(user_id, &db_list_item, ListPermission::Read)
+            .await?;
+
+        let attachments = self
+            .db
+            .get_list_item_attachments(&id)
+            .await?
+            .into_iter()
+            .map(|db_attachment| self.attachment(db_attachment))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(MethodResult::found(attachments))
+    }
+
+    pub async fn delete_list_item_attachment(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<delete_list_item_attachment::MethodResult> {
+        use delete_list_item_attachment::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let db_attachment = match self
+            .db
+            .get_list_item_attachment(&params.id.to_string())
+            .await?
+        {
+            Some(db_attachment) => db_attachment,
+            None => return Ok(MethodResult::missing()),
+        };
+        if self
+            .get_writable_list_item(user_id, &db_attachment.list_item_id)
+            .await?
+            .is_none()
+        {
+            return Ok(MethodResult::missing());
+        }
+
+        if !self
+            .db
+            .delete_list_item_attachment(&db_attachment.id)
+            .await?
+        {
+            return Ok(MethodResult::missing());
+        }
+        if let Err(e) = self.attachments.remove(&params.id).await {
+            error!(
+                "failed to remove the file of attachment '{}' with error: '{}'",
+                params.id, e
+            );
+        }
+
+        let list_item_id = db_attachment.list_item_id.clone();
+        let before = self.attachment(db_attachment)?;
+        self.auditor
+            .record(
+                claims,
+                Method::DeleteListItemAttachment,
+                AuditEntity::ListItem(list_item_id),
+                audit::to_json(&json!({ "attachment": &before })),
+                None,
+            )
+            .await;
+
+        Ok(MethodResult::deleted())
+    }
+
+    /// The attachment `id` and its file, if `query` is the signature of a download url of it.
+    ///
+    /// Attachments of items in the trash can't be downloaded until the item is restored.
+    pub async fn download_attachment(
+        &self,
+        id: Uuid,
+        query: &str,
+    ) -> AppResult<Option<(Attachment, tokio::fs::File)>> {
+        if let Err(e) = self
+            .attachments
+            .verify_download(&id, query, crate::current_timestamp_s())
+        {
+            return Err(AppError::not_permitted().with_context(&e));
+        }
+
+        let db_attachment = match self.db.get_list_item_attachment(&id.to_string()).await? {
+            Some(db_attachment) => db_attachment,
+            None => return Ok(None),
+        };
+        let file = self.attachments.open(&id).await?;
+
+        Ok(Some((self.attachment(db_attachment)?, file)))
+    }
+
+    pub async fn regenerate_calendar_feed(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<regenerate_calendar_feed::MethodResult> {
+        use regenerate_calendar_feed::{MethodResult, Params};
+        let _params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let token = calendar::generate_token();
+        self.db
+            .set_calendar_feed_token(user_id, &calendar::token_hash(&token))
+            .await?;
+
+        self.auditor
+            .record(
+                claims,
+                Method::RegenerateCalendarFeed,
+                AuditEntity::User(user_id.to_owned()),
+                None,
+                Some(json!({ "calendar_feed_regenerated": true })),
+            )
+            .await;
+
+        Ok(MethodResult::new(calendar::feed_url(&token)))
+    }
+
+    /// The calendar feed of the user whose feed token is `token`, `None` if there is no such user.
+    pub async fn calendar_feed(
+        &self,
+        token: &str,
+        component: CalendarComponent,
+    ) -> AppResult<Option<String>> {
+        let user_id = match self
+            .db
+            .get_calendar_feed_user(&calendar::token_hash(token))
+            .await?
+        {
+            Some(user_id) => user_id,
+            None => return Ok(None),
+        };
+
+        let list_items = self
+            .db
+            .get_due_list_items(&user_id)
+            .await?
+            .into_iter()
+            .map(|li| ListItemWrapper::try_from(li).map(|w| w.0))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(Some(calendar::render(&list_items, component, Utc::now())))
+    }
+
+    pub async fn complete_list_item(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<complete_list_item::MethodResult> {
+        use complete_list_item::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+
+        Ok(match self.set_completed(params.id, true, claims).await? {
+            Some(list_item) => MethodResult::completed(list_item),
+            None => MethodResult::missing(),
+        })
+    }
+
+    pub async fn uncomplete_list_item(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<uncomplete_list_item::MethodResult> {
+        use uncomplete_list_item::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+
+        Ok(match self.set_completed(params.id, false, claims).await? {
+            Some(list_item) => MethodResult::uncompleted(list_item),
+            None => MethodResult::missing(),
+        })
+    }
+
+    pub async fn set_list_item_recurrence(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<set_list_item_recurrence::MethodResult> {
+        use set_list_item_recurrence::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let id = params.id.to_string();
+
+        let before = match self.get_writable_list_item(user_id, &id).await? {
+            Some(db_list_item) => ListItemWrapper::try_from(db_list_item)?.0,
+            None => return Ok(MethodResult::missing()),
+        };
+
+        let recurrence = params
+            .recurrence
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| AppError::internal_error().with_context(&e))?;
+        let updated = match self
+            .db
+            .set_list_item_recurrence(&id, recurrence.as_deref(), Some(user_id))
+            .await?
+        {
+            Some(updated) => ListItemWrapper::try_from(updated)?.0,
+            None => return Ok(MethodResult::missing()),
+        };
+
+        self.auditor
+            .record(
+                claims,
+                Method::SetListItemRecurrence,
+                AuditEntity::ListItem(id),
+                audit::to_json(&before),
+                audit::to_json(&updated),
+            )
+            .await;
+
+        Ok(MethodResult::updated(updated))
+    }
+
+    pub async fn move_list_item(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<move_list_item::MethodResult> {
+        use move_list_item::{MethodResult, Params, Placement};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let id = params.id.to_string();
+        let (target, placement) = params.target();
+
+        if self.get_writable_list_item(user_id, &id).await?.is_none() {
+            return Ok(MethodResult::new(false));
+        }
+
+        let placement = match placement {
+            Placement::Before => ListItemPlacement::Before,
+            Placement::After => ListItemPlacement::After,
+        };
+        let moved = self
+            .db
+            .move_list_item(&id, &target.to_string(), placement)
+            .await?;
+
+        if moved {
+            self.auditor
+                .record(
+                    claims,
+                    Method::MoveListItem,
+                    AuditEntity::ListItem(id),
+                    None,
+                    Some(json!({ "before": params.before, "after": params.after })),
+                )
+                .await;
+        }
+
+        Ok(MethodResult::new(moved))
+    }
+
+    pub async fn move_list_items(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<move_list_items::MethodResult> {
+        use move_list_item::Placement;
+        use move_list_items::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let ids: Vec<Uuid> = params.moves.iter().map(|m| m.id).collect();
+
+        let list_items = self.get_writable_list_items(user_id, &ids).await?;
+        if list_items.iter().any(Result::is_err) {
+            let outcomes = list_items
+                .into_iter()
+                .map(|item| item.map(|_| ()))
+                .collect();
+            return Ok(MethodResult::new(bulk_item_results(&ids, outcomes)));
+        }
+
+        let db_moves: Vec<(String, String, ListItemPlacement)> = params
+            .moves
+            .iter()
+            .map(|m| {
+                let (target, placement) = m.target();
+                let placement = match placement {
+                    Placement::Before => ListItemPlacement::Before,
+                    Placement::After => ListItemPlacement::After,
+                };
+                (m.id.to_string(), target.to_string(), placement)
+            })
+            .collect();
+        let moves: Vec<ListItemMove> = db_moves
+            .iter()
+            .map(|(id, target_id, placement)| ListItemMove {
+                id,
+                target_id,
+                placement: *placement,
+            })
+            .collect();
+
+        let outcomes = self
+            .db
+            .move_list_items(&moves)
+            .await?
+            .into_iter()
+            .map(|moved| {
+                if moved {
+                    Ok(())
+                } else {
+                    Err(BulkItemError::InvalidTarget)
+                }
+            })
+            .collect();
+        let results = bulk_item_results(&ids, outcomes);
+
+        if results.iter().all(|result| result.success) {
+            for m in &params.moves {
+                self.auditor
+                    .record(
+                        claims,
+                        Method::MoveListItems,
+                        AuditEntity::ListItem(m.id.to_string()),
+                        None,
+                        Some(json!({ "before": m.before, "after": m.after })),
+                    )
+                    .await;
+            }
+        }
+
+        Ok(MethodResult::new(results))
+    }
+
     pub async fn get_list_types(
         &self,
         request: JsonRpcRequest,
+        claims: &Option<Claims>,
     ) -> AppResult<get_list_types::MethodResult> {
         use get_list_types::{MethodResult, Params};
         let _params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
 
-        let list_types = self.db.get_list_types().await?;
+        let list_types = self.db.get_list_types(user_id).await?;
+        let shared_list_types = self
+            .db
+            .get_shared_list_types(user_id)
+            .await?
+            .into_iter()
+            .map(|share| SharedListTypeWrapper::try_from(share).map(|w| w.0))
+            .collect::<Result<_, _>>()?;
 
-        Ok(MethodResult::new(list_types))
+        Ok(MethodResult::new(list_types, shared_list_types))
     }
 
     pub async fn rename_list_type(
         &self,
         request: JsonRpcRequest,
+        claims: &Option<Claims>,
     ) -> AppResult<rename_list_type::MethodResult> {
         use rename_list_type::{MethodResult, Params};
         let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
 
-        let existing_list_types: HashSet<_> = self.db.get_list_types().await?.into_iter().collect();
-
-        if !existing_list_types.contains(&params.old_name) {
+        // only the owner can rename a list type, so only the caller's own list types are considered
+        if !self.owns_list_type(user_id, &params.old_name).await? {
             return Ok(MethodResult::new(false));
         }
 
         let updated_rows = self
             .db
-            .rename_list_type(&params.old_name, &params.new_name)
+            .rename_list_type(user_id, &params.old_name, &params.new_name, Some(user_id))
             .await?;
 
+        if updated_rows > 0 {
+            self.auditor
+                .record(
+                    claims,
+                    Method::RenameListType,
+                    AuditEntity::ListType(params.old_name.clone()),
+                    Some(json!({ "list_type": params.old_name })),
+                    Some(json!({ "list_type": params.new_name, "updated_items": updated_rows })),
+                )
+                .await;
+        }
+
         Ok(MethodResult::new(updated_rows > 0))
     }
+
+    pub async fn delete_list_type(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<delete_list_type::MethodResult> {
+        use delete_list_type::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        // only the owner can delete a list type, so only the caller's own list types are considered
+        if !self.owns_list_type(user_id, &params.list_type).await? {
+            return Ok(MethodResult::missing());
+        }
+
+        info!(
+            "moving all items of '{}' of '{}' to the trash",
+            params.list_type, user_id
+        );
+
+        let deleted = self
+            .db
+            .delete_list_type(user_id, &params.list_type, Some(user_id))
+            .await?;
+
+        self.auditor
+            .record(
+                claims,
+                Method::DeleteListType,
+                AuditEntity::ListType(params.list_type.clone()),
+                Some(json!({ "list_type": params.list_type })),
+                Some(json!({ "deleted_items": deleted })),
+            )
+            .await;
+
+        Ok(MethodResult::deleted(deleted))
+    }
+
+    pub async fn merge_list_types(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<merge_list_types::MethodResult> {
+        use merge_list_types::{DuplicateStrategy, MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        // a list type that doesn't exist yet can be the target, which works like a rename
+        if !self.owns_list_type(user_id, &params.source).await? {
+            return Ok(MethodResult::missing());
+        }
+
+        let strategy = match params.on_duplicate {
+            DuplicateStrategy::KeepBoth => DuplicateItemStrategy::KeepBoth,
+            DuplicateStrategy::KeepTarget => DuplicateItemStrategy::KeepTarget,
+            DuplicateStrategy::KeepSource => DuplicateItemStrategy::KeepSource,
+        };
+        let merge = self
+            .db
+            .merge_list_types(
+                user_id,
+                &params.source,
+                &params.target,
+                strategy,
+                Some(user_id),
+            )
+            .await?;
+
+        self.auditor
+            .record(
+                claims,
+                Method::MergeListTypes,
+                AuditEntity::ListType(params.source.clone()),
+                Some(json!({ "list_type": params.source })),
+                Some(json!({
+                    "list_type": params.target,
+                    "moved_items": merge.moved,
+                    "discarded_items": merge.discarded,
+                })),
+            )
+            .await;
+
+        Ok(MethodResult::merged(merge.moved, merge.discarded))
+    }
+
+    pub async fn save_list_as_template(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<save_list_as_template::MethodResult> {
+        use save_list_as_template::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+        let list_owner_id = params.owner_id.as_deref().unwrap_or(user_id);
+
+        self.check_permission(
+            user_id,
+            list_owner_id,
+            &params.list_type,
+            ListPermission::Read,
+        )
+        .await?;
+
+        let (db_template, db_items) = match self
+            .db
+            .save_list_template(
+                user_id,
+                &params.name,
+                list_owner_id,
+                &params.list_type,
+                params.overwrite,
+            )
+            .await?
+        {
+            Some(saved) => saved,
+            None => return Ok(MethodResult::already_exists()),
+        };
+        let template = list_template(db_template, db_items);
+
+        self.auditor
+            .record(
+                claims,
+                Method::SaveListAsTemplate,
+                AuditEntity::ListTemplate(params.name.clone()),
+                Some(json!({ "owner_id": list_owner_id, "list_type": params.list_type })),
+                audit::to_json(&template),
+            )
+            .await;
+
+        Ok(MethodResult::saved(template))
+    }
+
+    pub async fn get_templates(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<get_templates::MethodResult> {
+        use get_templates::{MethodResult, Params};
+        let _params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let db_templates = self.db.get_list_templates(user_id).await?;
+        let mut db_items = self.db.get_list_template_items(user_id).await?;
+
+        // the items are ordered by template name, like the templates
+        let templates = db_templates
+            .into_iter()
+            .map(|db_template| {
+                let count = db_items
+                    .iter()
+                    .take_while(|item| item.template_name == db_template.name)
+                    .count();
+                let items = db_items.drain(..count).collect();
+                list_template(db_template, items)
+            })
+            .collect();
+
+        Ok(MethodResult::new(templates))
+    }
+
+    pub async fn instantiate_template(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<instantiate_template::MethodResult> {
+        use instantiate_template::{InstantiationError, MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let db_list_items = match self
+            .db
+            .instantiate_list_template(user_id, &params.name, &params.list_type, Some(user_id))
+            .await?
+        {
+            TemplateInstantiation::Created(db_list_items) => db_list_items,
+            TemplateInstantiation::MissingTemplate => {
+                return Ok(MethodResult::failed(InstantiationError::MissingTemplate))
+            }
+            TemplateInstantiation::ListTypeExists => {
+                return Ok(MethodResult::failed(InstantiationError::ListTypeExists))
+            }
+        };
+        let list_items = db_list_items
+            .into_iter()
+            .map(|db_list_item| Ok(ListItemWrapper::try_from(db_list_item)?.0))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        self.auditor
+            .record(
+                claims,
+                Method::InstantiateTemplate,
+                AuditEntity::ListType(params.list_type.clone()),
+                None,
+                Some(json!({ "template": params.name, "created_items": list_items.len() })),
+            )
+            .await;
+
+        Ok(MethodResult::created(list_items))
+    }
+
+    pub async fn share_list_type(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<share_list_type::MethodResult> {
+        use share_list_type::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        if params.user_id == user_id || !self.owns_list_type(user_id, &params.list_type).await? {
+            return Ok(MethodResult::new(false));
+        }
+
+        let before = self
+            .db
+            .get_list_share(user_id, &params.list_type, &params.user_id)
+            .await?;
+
+        let shared = self
+            .db
+            .share_list_type(
+                user_id,
+                &params.list_type,
+                &params.user_id,
+                &params.permission.to_string(),
+            )
+            .await?;
+        if !shared {
+            return Err(AppError::user_does_not_exist());
+        }
+
+        info!(
+            "{} shared list type '{}' with {} ({})",
+            user_id, params.list_type, params.user_id, params.permission
+        );
+        self.auditor
+            .record(
+                claims,
+                Method::ShareListType,
+                AuditEntity::ListType(params.list_type.clone()),
+                before.map(
+                    |share| json!({ "user_id": share.user_id, "permission": share.permission }),
+                ),
+                Some(json!({ "user_id": params.user_id, "permission": params.permission })),
+            )
+            .await;
+
+        Ok(MethodResult::new(true))
+    }
+
+    pub async fn unshare_list_type(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<unshare_list_type::MethodResult> {
+        use unshare_list_type::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let before = match self
+            .db
+            .get_list_share(user_id, &params.list_type, &params.user_id)
+            .await?
+        {
+            Some(share) => share,
+            None => return Ok(MethodResult::new(false)),
+        };
+
+        let result = self
+            .db
+            .unshare_list_type(user_id, &params.list_type, &params.user_id)
+            .await?;
+
+        if result {
+            self.auditor
+                .record(
+                    claims,
+                    Method::UnshareListType,
+                    AuditEntity::ListType(params.list_type.clone()),
+                    Some(json!({ "user_id": before.user_id, "permission": before.permission })),
+                    None,
+                )
+                .await;
+        }
+
+        Ok(MethodResult::new(result))
+    }
+
+    /// Returns the updated item, or `None` if it doesn't exist.
+    async fn set_completed(
+        &self,
+        id: Uuid,
+        completed: bool,
+        claims: &Option<Claims>,
+    ) -> AppResult<Option<ListItem>> {
+        let user_id = subject(claims)?;
+        let id = id.to_string();
+
+        let before = match self.get_writable_list_item(user_id, &id).await? {
+            Some(db_list_item) => ListItemWrapper::try_from(db_list_item)?.0,
+            None => return Ok(None),
+        };
+
+        let updated = match self
+            .db
+            .set_list_item_completed(&id, completed, Some(user_id))
+            .await?
+        {
+            Some(updated) => ListItemWrapper::try_from(updated)?.0,
+            None => return Ok(None),
+        };
+
+        let method = if completed {
+            Method::CompleteListItem
+        } else {
+            Method::UncompleteListItem
+        };
+        self.auditor
+            .record(
+                claims,
+                method,
+                AuditEntity::ListItem(id),
+                audit::to_json(&before),
+                audit::to_json(&updated),
+            )
+            .await;
+
+        Ok(Some(updated))
+    }
+
+    /// An attachment with a freshly signed download url.
+    fn attachment(&self, db_attachment: DbListItemAttachment) -> AppResult<Attachment> {
+        let id = Uuid::from_str(&db_attachment.id)
+            .map_err(|e| AppError::internal_error().with_context(&e))?;
+        let list_item_id = Uuid::from_str(&db_attachment.list_item_id)
+            .map_err(|e| AppError::internal_error().with_context(&e))?;
+        let created = crate::chrono_timestamp(
+            db_attachment.created.unix_timestamp(),
+            db_attachment.created.nanosecond(),
+        );
+
+        let attachment = Attachment::new(
+            id,
+            list_item_id,
+            db_attachment.file_name,
+            db_attachment.content_type,
+            db_attachment.size_bytes as u64,
+            created,
+        );
+
+        Ok(
+            match self
+                .attachments
+                .download_url(&id, crate::current_timestamp_s())
+            {
+                Some((url, expiry)) => {
+                    attachment.with_download_url(url, crate::chrono_timestamp(expiry, 0))
+                }
+                None => attachment,
+            },
+        )
+    }
+
+    /// Returns the item if it exists, and fails if `user_id` isn't allowed to change it.
+    async fn get_writable_list_item(
+        &self,
+        user_id: &str,
+        id: &str,
+    ) -> AppResult<Option<DbListItem>> {
+        let db_list_item = match self.db.get_list_item(id).await? {
+            Some(db_list_item) => db_list_item,
+            None => return Ok(None),
+        };
+
+        self.check_item_permission(user_id, &db_list_item, ListPermission::Write)
+            .await?;
+
+        Ok(Some(db_list_item))
+    }
+
+    async fn check_item_permission(
+        &self,
+        user_id: &str,
+        db_list_item: &DbListItem,
+        required: ListPermission,
+    ) -> AppResult<()> {
+        if self
+            .has_item_permission(user_id, db_list_item, required)
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(AppError::not_permitted())
+        }
+    }
+
+    async fn has_item_permission(
+        &self,
+        user_id: &str,
+        db_list_item: &DbListItem,
+        required: ListPermission,
+    ) -> AppResult<bool> {
+        match db_list_item.owner_id.as_deref() {
+            Some(owner_id) => {
+                self.has_permission(user_id, owner_id, &db_list_item.list_type, required)
+                    .await
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// The writable list items with the given ids, or why each of them can't be changed.
+    async fn get_writable_list_items(
+        &self,
+        user_id: &str,
+        ids: &[Uuid],
+    ) -> AppResult<Vec<Result<ListItem, BulkItemError>>> {
+        let mut list_items = Vec::with_capacity(ids.len());
+        for id in ids {
+            let list_item = match self.db.get_list_item(&id.to_string()).await? {
+                Some(db_list_item)
+                    if self
+                        .has_item_permission(user_id, &db_list_item, ListPermission::Write)
+                        .await? =>
+                {
+                    Ok(ListItemWrapper::try_from(db_list_item)?.0)
+                }
+                Some(_) => Err(BulkItemError::NotPermitted),
+                None => Err(BulkItemError::DoesNotExist),
+            };
+            list_items.push(list_item);
+        }
+
+        Ok(list_items)
+    }
+
+    /// `None` if `user_id` can read all list types of `owner_id`,
+    /// otherwise the list types that are shared with `user_id`.
+    async fn readable_list_types(
+        &self,
+        user_id: &str,
+        owner_id: &str,
+    ) -> AppResult<Option<Vec<String>>> {
+        if owner_id == user_id {
+            return Ok(None);
+        }
+
+        let shared: Vec<_> = self
+            .db
+            .get_shared_list_types(user_id)
+            .await?
+            .into_iter()
+            .filter(|share| share.owner_id == owner_id)
+            .map(|share| share.list_type)
+            .collect();
+        if shared.is_empty() {
+            return Err(AppError::not_permitted());
+        }
+
+        Ok(Some(shared))
+    }
+
+    async fn owns_list_type(&self, user_id: &str, list_type: &str) -> AppResult<bool> {
+        let list_types: HashSet<_> = self.db.get_list_types(user_id).await?.into_iter().collect();
+        Ok(list_types.contains(list_type))
+    }
+
+    /// Owners have full access to their list types,
+    /// other users only have the access that the owner has shared with them.
+    async fn check_permission(
+        &self,
+        user_id: &str,
+        owner_id: &str,
+        list_type: &str,
+        required: ListPermission,
+    ) -> AppResult<()> {
+        if self
+            .has_permission(user_id, owner_id, list_type, required)
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(AppError::not_permitted())
+        }
+    }
+
+    async fn has_permission(
+        &self,
+        user_id: &str,
+        owner_id: &str,
+        list_type: &str,
+        required: ListPermission,
+    ) -> AppResult<bool> {
+        if user_id == owner_id {
+            return Ok(true);
+        }
+
+        let shared = match self.db.get_list_share(owner_id, list_type, user_id).await? {
+            Some(share) => Some(SharedListTypeWrapper::try_from(share)?.0.permission),
+            None => None,
+        };
+
+        Ok(permits(user_id, owner_id, shared, required))
+    }
+}
+
+/// Owners may do anything with their list types, other users what the share allows if there is one.
+fn permits(
+    user_id: &str,
+    owner_id: &str,
+    shared: Option<ListPermission>,
+    required: ListPermission,
+) -> bool {
+    user_id == owner_id || shared.is_some_and(|permission| permission.allows(required))
 }
 
 struct ListItemWrapper(ListItem);
@@ -113,17 +1799,297 @@ impl TryFrom<DbListItem> for ListItemWrapper {
     fn try_from(db_list_item: DbListItem) -> Result<Self, Self::Error> {
         let id = Uuid::from_str(&db_list_item.id)
             .map_err(|e| AppError::from(JsonRpcError::internal_error()).with_context(&e))?;
+        let due_date = db_list_item
+            .due_date
+            .as_deref()
+            .map(NaiveDate::from_str)
+            .transpose()
+            .map_err(|e| AppError::internal_error().with_context(&e))?;
+        let completed_at = db_list_item
+            .completed_at
+            .map(|t| crate::chrono_timestamp(t.unix_timestamp(), t.nanosecond()));
+        let recurrence = db_list_item
+            .recurrence
+            .as_deref()
+            .map(serde_json::from_str::<Recurrence>)
+            .transpose()
+            .map_err(|e| AppError::internal_error().with_context(&e))?;
 
-        Ok(ListItemWrapper(ListItem::new(
-            id,
-            db_list_item.list_type,
-            db_list_item.item_name,
+        Ok(ListItemWrapper(
+            ListItem::new(
+                id,
+                db_list_item.list_type,
+                db_list_item.item_name,
+                db_list_item.version,
+                ListItemDetails::new(
+                    db_list_item.quantity,
+                    db_list_item.unit,
+                    db_list_item.notes,
+                    due_date,
+                ),
+                completed_at,
+            )
+            .with_recurrence(recurrence),
+        ))
+    }
+}
+
+/// Items that were inserted after `since_revision` are reported as inserted,
+/// and items in the trash as deleted.
+fn list_item_change(db_change: DbListItemChange, since_revision: i64) -> AppResult<ListItemChange> {
+    let parse_id = |id: &str| {
+        Uuid::from_str(id)
+            .map_err(|e| AppError::from(JsonRpcError::internal_error()).with_context(&e))
+    };
+
+    Ok(match db_change {
+        DbListItemChange::Purged(tombstone) => ListItemChange::Deleted {
+            revision: tombstone.revision,
+            id: parse_id(&tombstone.id)?,
+            list_type: tombstone.list_type,
+        },
+        DbListItemChange::Changed(db_list_item) if db_list_item.deleted_at.is_some() => {
+            ListItemChange::Deleted {
+                revision: db_list_item.revision,
+                id: parse_id(&db_list_item.id)?,
+                list_type: db_list_item.list_type,
+            }
+        }
+        DbListItemChange::Changed(db_list_item) => {
+            let revision = db_list_item.revision;
+            let inserted = db_list_item
+                .created_revision
+                .is_some_and(|created_revision| created_revision > since_revision);
+            let list_item = ListItemWrapper::try_from(*db_list_item)?.0;
+            if inserted {
+                ListItemChange::Inserted {
+                    revision,
+                    list_item,
+                }
+            } else {
+                ListItemChange::Updated {
+                    revision,
+                    list_item,
+                }
+            }
+        }
+    })
+}
+
+/// `db_items` are the items of the template, in order.
+fn list_template(db_template: DbListTemplate, db_items: Vec<DbListTemplateItem>) -> ListTemplate {
+    let items = db_items
+        .into_iter()
+        .map(|item| TemplateItem::new(item.item_name, item.quantity, item.unit, item.notes))
+        .collect();
+
+    ListTemplate::new(
+        db_template.name,
+        items,
+        crate::chrono_timestamp(
+            db_template.created.unix_timestamp(),
+            db_template.created.nanosecond(),
+        ),
+        crate::chrono_timestamp(
+            db_template.updated.unix_timestamp(),
+            db_template.updated.nanosecond(),
+        ),
+    )
+}
+
+fn list_item_version(db_version: DbListItemVersion) -> AppResult<ListItemVersion> {
+    let id = Uuid::from_str(&db_version.list_item_id)
+        .map_err(|e| AppError::internal_error().with_context(&e))?;
+    let due_date = db_version
+        .due_date
+        .as_deref()
+        .map(NaiveDate::from_str)
+        .transpose()
+        .map_err(|e| AppError::internal_error().with_context(&e))?;
+    let recurrence = db_version
+        .recurrence
+        .as_deref()
+        .map(serde_json::from_str::<Recurrence>)
+        .transpose()
+        .map_err(|e| AppError::internal_error().with_context(&e))?;
+    let completed_at = db_version
+        .completed_at
+        .map(|t| crate::chrono_timestamp(t.unix_timestamp(), t.nanosecond()));
+    let changed_at = crate::chrono_timestamp(
+        db_version.changed_at.unix_timestamp(),
+        db_version.changed_at.nanosecond(),
+    );
+    let deleted_at = db_version
+        .deleted_at
+        .map(|t| crate::chrono_timestamp(t.unix_timestamp(), t.nanosecond()));
+
+    let list_item = ListItem::new(
+        id,
+        db_version.list_type,
+        db_version.item_name,
+        db_version.version,
+        ListItemDetails::new(
+            db_version.quantity,
+            db_version.unit,
+            db_version.notes,
+            due_date,
+        ),
+        completed_at,
+    )
+    .with_recurrence(recurrence);
+
+    Ok(ListItemVersion::new(
+        list_item,
+        db_version.actor_id,
+        changed_at,
+        deleted_at,
+    ))
+}
+
+fn deleted_list_item(db_list_item: DbListItem) -> AppResult<DeletedListItem> {
+    let deleted_at = db_list_item
+        .deleted_at
+        .map(|t| crate::chrono_timestamp(t.unix_timestamp(), t.nanosecond()))
+        .ok_or_else(|| {
+            AppError::internal_error().with_context(&format!(
+                "list item '{}' is not in the trash",
+                db_list_item.id
+            ))
+        })?;
+
+    Ok(DeletedListItem::new(
+        ListItemWrapper::try_from(db_list_item)?.0,
+        deleted_at,
+    ))
+}
+
+/// Bulk methods are applied either for all of their items or for none of them,
+/// so if any of `outcomes` failed the successful ones are reported as not applied.
+fn bulk_item_results(
+    ids: &[Uuid],
+    outcomes: Vec<Result<(), BulkItemError>>,
+) -> Vec<BulkItemResult> {
+    let applied = outcomes.iter().all(Result::is_ok);
+
+    ids.iter()
+        .zip(outcomes)
+        .map(|(id, outcome)| match outcome {
+            Ok(()) if applied => BulkItemResult::succeeded(*id),
+            Ok(()) => BulkItemResult::failed(*id, BulkItemError::NotApplied),
+            Err(error) => BulkItemResult::failed(*id, error),
+        })
+        .collect()
+}
+
+/// Nothing is imported if any line is invalid, the outcome of every line is reported as `lines`.
+fn invalid_import_error(lines: Vec<import_list::ImportLine>) -> AppError {
+    AppError::from(
+        JsonRpcError::invalid_params()
+            .with_message("invalid lines in the imported content, nothing was imported")
+            .with_data(json!({ "lines": lines })),
+    )
+}
+
+/// `due_date` is the date of `details` formatted as `YYYY-MM-DD`.
+fn db_details<'a>(
+    details: &'a ListItemDetails,
+    due_date: Option<&'a str>,
+) -> DbListItemDetails<'a> {
+    DbListItemDetails {
+        quantity: details.quantity,
+        unit: details.unit.as_deref(),
+        notes: details.notes.as_deref(),
+        due_date,
+    }
+}
+
+struct SharedListTypeWrapper(SharedListType);
+
+impl TryFrom<DbListShare> for SharedListTypeWrapper {
+    type Error = AppError;
+
+    fn try_from(db_list_share: DbListShare) -> Result<Self, Self::Error> {
+        let permission = ListPermission::from_str(&db_list_share.permission).map_err(|_| {
+            AppError::internal_error().with_context(&format!(
+                "invalid list permission '{}'",
+                db_list_share.permission
+            ))
+        })?;
+
+        Ok(SharedListTypeWrapper(SharedListType::new(
+            db_list_share.owner_id,
+            db_list_share.list_type,
+            permission,
         )))
     }
 }
 
 impl ParamsError for add_list_item::InvalidParams {}
+impl ParamsError for add_list_items::InvalidParams {}
 impl ParamsError for get_list_items::InvalidParams {}
+impl ParamsError for get_list_changes::InvalidParams {}
+impl ParamsError for search_list_items::InvalidParams {}
+impl ParamsError for set_list_item_recurrence::InvalidParams {}
+impl ParamsError for get_list_item_history::InvalidParams {}
+impl ParamsError for revert_list_item::InvalidParams {}
+impl ParamsError for get_list_item_attachments::InvalidParams {}
+impl ParamsError for get_list_events_url::InvalidParams {}
+impl ParamsError for delete_list_item_attachment::InvalidParams {}
+impl ParamsError for regenerate_calendar_feed::InvalidParams {}
+impl ParamsError for save_list_as_template::InvalidParams {}
+impl ParamsError for get_templates::InvalidParams {}
+impl ParamsError for instantiate_template::InvalidParams {}
+impl ParamsError for quick_add_list_item::InvalidParams {}
+impl ParamsError for export_list::InvalidParams {}
+impl ParamsError for import_list::InvalidParams {}
+impl ParamsError for update_list_item::InvalidParams {}
 impl ParamsError for delete_list_item::InvalidParams {}
+impl ParamsError for delete_list_items::InvalidParams {}
+impl ParamsError for get_deleted_list_items::InvalidParams {}
+impl ParamsError for restore_list_item::InvalidParams {}
+impl ParamsError for complete_list_item::InvalidParams {}
+impl ParamsError for uncomplete_list_item::InvalidParams {}
+impl ParamsError for move_list_item::InvalidParams {}
+impl ParamsError for move_list_items::InvalidParams {}
 impl ParamsError for get_list_types::InvalidParams {}
 impl ParamsError for rename_list_type::InvalidParams {}
+impl ParamsError for delete_list_type::InvalidParams {}
+impl ParamsError for merge_list_types::InvalidParams {}
+impl ParamsError for share_list_type::InvalidParams {}
+impl ParamsError for unshare_list_type::InvalidParams {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::error_codes;
+
+    #[test]
+    fn invalid_imports_report_every_line() {
+        use import_list::ImportLine;
+
+        let lines = vec![
+            ImportLine::ok(1, "milk".to_owned()),
+            ImportLine::invalid(2, "missing item name".to_owned()),
+        ];
+        let error = invalid_import_error(lines).rpc_error;
+        assert_eq!(error.code, error_codes::standard::INVALID_PARAMS);
+
+        let data = error.data.unwrap();
+        assert_eq!(data["lines"][0]["status"], "ok");
+        assert_eq!(data["lines"][1]["status"], "invalid");
+        assert_eq!(data["lines"][1]["error"], "missing item name");
+    }
+
+    #[test]
+    fn owners_and_shares_permit() {
+        use ListPermission::{Read, Write};
+
+        for required in [Read, Write] {
+            assert!(permits("owner", "owner", None, required));
+            assert!(permits("user", "owner", Some(Write), required));
+            assert!(!permits("user", "owner", None, required));
+        }
+        assert!(permits("user", "owner", Some(Read), Read));
+        assert!(!permits("user", "owner", Some(Read), Write));
+    }
+}
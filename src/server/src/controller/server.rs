@@ -1,9 +1,13 @@
-use crate::app::{AppResult, ParamsError};
-use hmac::{Hmac, Mac, NewMac};
-use model::{sas, server, JsonRpcRequest};
+use crate::{
+    app::{AppResult, ParamsError},
+    sas::SasToken,
+};
+use model::{
+    sas::{self, verify_sas_key},
+    server, JsonRpcRequest,
+};
 use server::sleep;
-use sha2::Sha256;
-use std::{convert::TryFrom, time};
+use std::{convert::TryFrom, str::FromStr, time};
 
 pub struct ServerController {}
 
@@ -27,9 +31,12 @@ impl ServerController {
         use sas::{MethodResult, Params};
         let params = Params::try_from(request)?;
 
-        let token = Self::generate(
-            crate::current_timestamp_s(),
-            params.weeks_expiry,
+        const SECONDS_IN_ONE_WEEK: i64 = 60 * 60 * 24 * 7;
+        let expiry =
+            crate::current_timestamp_s() + params.weeks_expiry as i64 * SECONDS_IN_ONE_WEEK;
+
+        let token = crate::sas::generate(
+            expiry,
             &params.resource_uri,
             &params.key_value,
             &params.key_name,
@@ -38,31 +45,34 @@ impl ServerController {
         Ok(MethodResult::new(token))
     }
 
-    fn generate(
-        now: i64,
-        weeks_expiry: u32,
-        resource_uri: &str,
-        key_value: &str,
-        key_name: &str,
-    ) -> AppResult<String> {
-        type HmacSha256 = Hmac<Sha256>;
-        const SECONDS_IN_ONE_WEEK: i64 = 60 * 60 * 24 * 7;
-        let expiry = now + weeks_expiry as i64 * SECONDS_IN_ONE_WEEK;
-        let sign = format!("{}\n{}", urlencoding::encode(&resource_uri), expiry);
-        let mut hmac = HmacSha256::new_from_slice(key_value.as_bytes())?;
-        hmac.update(sign.as_bytes());
-        let result = hmac.finalize().into_bytes();
-        let signature = base64::encode(result);
-        let token = format!(
-            "SharedAccessSignature sr={}&sig={}&se={}&skn={}",
-            urlencoding::encode(&resource_uri),
-            urlencoding::encode(&signature),
-            expiry,
-            key_name
-        );
-        Ok(token)
+    pub async fn verify_sas_key(
+        &self,
+        request: JsonRpcRequest,
+    ) -> AppResult<verify_sas_key::MethodResult> {
+        use verify_sas_key::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+
+        let result = SasToken::from_str(&params.sas_key).and_then(|token| {
+            token
+                .verify(
+                    &params.key_value,
+                    crate::current_timestamp_s(),
+                    params.resource_uri.as_deref(),
+                )
+                .map(|_| token)
+        });
+
+        Ok(match result {
+            Ok(token) => MethodResult::valid(
+                token.resource_uri(),
+                token.expiry(),
+                token.key_name().to_owned(),
+            ),
+            Err(e) => MethodResult::invalid(e.to_string()),
+        })
     }
 }
 
 impl ParamsError for sleep::InvalidParams {}
 impl ParamsError for sas::InvalidParams {}
+impl ParamsError for verify_sas_key::InvalidParams {}
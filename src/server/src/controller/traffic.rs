@@ -1,22 +1,53 @@
-use std::convert::TryFrom;
+use std::{
+    convert::TryFrom,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use gtfs::consts::{STATION_REDIS_KEY, STOP_GEO_REDIS_KEY};
 use isahc::{AsyncReadResponseExt, HttpClient};
 use model::{
-    traffic::{get_departures, Departure},
+    chrono::NaiveDateTime,
+    traffic::{
+        get_arrivals, get_departures, get_nearby_stops, search_stops, Arrival, Departure,
+        NearbyStop, Stop, TransportMode,
+    },
     JsonRpcRequest,
 };
+use redis::async_pool::{
+    mobc_redis::redis::{cmd, AsyncCommands},
+    AsyncRedisPool,
+};
 use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::{
+    app::{AppError, AppResult, ParamsError},
+    stop_search::StopIndex,
+};
+
+const RESROBOT_DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
-use crate::app::{AppResult, ParamsError};
+/// How long the stations are searched in memory before they are read from Redis again.
+const STOP_INDEX_TTL: Duration = Duration::from_secs(10 * 60);
 
 pub struct TrafficController {
     http_client: HttpClient,
     key: String,
+    /// The GTFS data that `gtfs::Populate` loads.
+    redis_pool: AsyncRedisPool,
+    /// The stations of the GTFS data and when they were read.
+    stop_index: RwLock<Option<(Instant, Arc<StopIndex>)>>,
 }
 
 impl TrafficController {
-    pub fn new(http_client: HttpClient, key: String) -> Self {
-        Self { http_client, key }
+    pub fn new(http_client: HttpClient, key: String, redis_pool: AsyncRedisPool) -> Self {
+        Self {
+            http_client,
+            key,
+            redis_pool,
+            stop_index: RwLock::new(None),
+        }
     }
 
     pub async fn get_departures(
@@ -26,24 +57,145 @@ impl TrafficController {
         use get_departures::{MethodResult, Params};
         let params = Params::try_from(request)?;
 
-        let response = self.get_departures_by_id(params.stop_id).await?;
+        let response = self.get_board(Board::Departures, &params).await?;
 
-        let departures = response
+        let mut departures = response
             .departure
             .into_iter()
-            .map(|d| Departure::from(d))
-            .collect();
+            .map(Departure::try_from)
+            .collect::<AppResult<Vec<_>>>()?;
+        departures.sort_by_key(Departure::expected);
+        departures.truncate(params.count as usize);
 
         Ok(MethodResult::new(departures))
     }
 
-    async fn get_departures_by_id(&self, stop_id: String) -> AppResult<ResRobotDepartureResponse> {
-        let request = isahc::Request::builder()
-            .method("GET")
-            .uri(format!("https://api.resrobot.se/v2.1/departureBoard?id={}&format=json&accessId={}&duration=30", stop_id, self.key))
-            .body(())?;
+    pub async fn get_arrivals(
+        &self,
+        request: JsonRpcRequest,
+    ) -> AppResult<get_arrivals::MethodResult> {
+        use get_arrivals::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+
+        let response = self.get_board(Board::Arrivals, &params).await?;
+
+        let mut arrivals = response
+            .arrival
+            .into_iter()
+            .map(Arrival::try_from)
+            .collect::<AppResult<Vec<_>>>()?;
+        arrivals.sort_by_key(Arrival::expected);
+        arrivals.truncate(params.count as usize);
+
+        Ok(MethodResult::new(arrivals))
+    }
+
+    pub async fn search_stops(
+        &self,
+        request: JsonRpcRequest,
+    ) -> AppResult<search_stops::MethodResult> {
+        use search_stops::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+
+        let stop_index = self.stop_index().await?;
+
+        Ok(MethodResult::new(
+            stop_index.search(&params.query, params.limit),
+        ))
+    }
+
+    /// The stations that `gtfs::Populate` stored, read from Redis at most once per `STOP_INDEX_TTL`.
+    async fn stop_index(&self) -> AppResult<Arc<StopIndex>> {
+        if let Some((read_at, stop_index)) = &*self.stop_index.read().await {
+            if read_at.elapsed() < STOP_INDEX_TTL {
+                return Ok(stop_index.clone());
+            }
+        }
 
-        let response: ResRobotDepartureResponse =
+        let mut conn = self.redis_pool.get_connection().await?;
+        let stations: Option<String> = conn.get(STATION_REDIS_KEY).await?;
+        let stops = match stations {
+            Some(stations) => stops_from_stations(&stations)?,
+            None => {
+                warn!("no stations in Redis under '{}'", STATION_REDIS_KEY);
+                Vec::new()
+            }
+        };
+
+        let stop_index = Arc::new(StopIndex::new(stops));
+        *self.stop_index.write().await = Some((Instant::now(), stop_index.clone()));
+        Ok(stop_index)
+    }
+
+    /// Uses GEOSEARCH, which needs Redis 6.2 or newer.
+    pub async fn get_nearby_stops(
+        &self,
+        request: JsonRpcRequest,
+    ) -> AppResult<get_nearby_stops::MethodResult> {
+        use get_nearby_stops::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+
+        let mut conn = self.redis_pool.get_connection().await?;
+        let nearby: Vec<(String, f64)> = cmd("GEOSEARCH")
+            .arg(STOP_GEO_REDIS_KEY)
+            .arg("FROMLONLAT")
+            .arg(params.lon)
+            .arg(params.lat)
+            .arg("BYRADIUS")
+            .arg(params.distance_m)
+            .arg("m")
+            .arg("ASC")
+            .arg("COUNT")
+            .arg(params.count)
+            .arg("WITHDIST")
+            .query_async(&mut *conn)
+            .await?;
+        if nearby.is_empty() {
+            return Ok(MethodResult::new(Vec::new()));
+        }
+
+        // stations that were added after the index was read are left out until it's read again
+        let stop_index = self.stop_index().await?;
+        let stops = nearby
+            .into_iter()
+            .filter_map(|(stop_id, distance_m)| {
+                let stop = stop_index.get(&stop_id)?;
+                Some(NearbyStop::new(stop.clone(), distance_m))
+            })
+            .collect();
+
+        Ok(MethodResult::new(stops))
+    }
+
+    /// Departures and arrivals are requested with the same parameters.
+    async fn get_board(
+        &self,
+        board: Board,
+        params: &get_departures::Params,
+    ) -> AppResult<ResRobotBoardResponse> {
+        let board = match board {
+            Board::Departures => "departureBoard",
+            Board::Arrivals => "arrivalBoard",
+        };
+        let mut uri = format!(
+            "https://api.resrobot.se/v2.1/{}?id={}&format=json&accessId={}&duration={}&maxJourneys={}",
+            board,
+            urlencoding::encode(&params.stop_id),
+            self.key,
+            params.duration_m,
+            params.count
+        );
+        if let Some(start) = params.start {
+            uri.push_str(&format!(
+                "&date={}&time={}",
+                start.format("%Y-%m-%d"),
+                start.format("%H:%M")
+            ));
+        }
+
+        let request = isahc::Request::builder().method("GET").uri(uri).body(())?;
+
+        let response: ResRobotBoardResponse =
             self.http_client.send_async(request).await?.json().await?;
 
         Ok(response)
@@ -52,20 +204,272 @@ impl TrafficController {
 
 impl ParamsError for get_departures::InvalidParams {}
 
+impl ParamsError for search_stops::InvalidParams {}
+
+impl ParamsError for get_nearby_stops::InvalidParams {}
+
+/// The stations stored by `gtfs::Populate`, a station that can't be read is left out.
+fn stops_from_stations(stations: &str) -> AppResult<Vec<Stop>> {
+    let stations: Vec<serde_json::Value> = serde_json::from_str(stations)?;
+    let stops = stations
+        .into_iter()
+        .filter_map(
+            |station| match serde_json::from_value::<gtfs::Station>(station) {
+                Ok(station) => Some(Stop::new(
+                    station.stop_id,
+                    station.stop_name,
+                    station.lat,
+                    station.lon,
+                )),
+                Err(e) => {
+                    warn!("skipping invalid station with error: '{}'", e);
+                    None
+                }
+            },
+        )
+        .collect();
+
+    Ok(stops)
+}
+
+enum Board {
+    Departures,
+    Arrivals,
+}
+
 #[derive(Serialize, Deserialize)]
-struct ResRobotDepartureResponse {
-    #[serde(alias = "Departure")]
-    departure: Vec<ResRobotDeparture>,
+struct ResRobotBoardResponse {
+    /// Left out when there are no departures.
+    #[serde(alias = "Departure", default)]
+    departure: Vec<ResRobotJourney>,
+    /// Left out when there are no arrivals.
+    #[serde(alias = "Arrival", default)]
+    arrival: Vec<ResRobotJourney>,
 }
 
+/// A departure or an arrival, departures have a `direction` and arrivals an `origin`.
 #[derive(Serialize, Deserialize)]
-struct ResRobotDeparture {
+#[serde(rename_all = "camelCase")]
+struct ResRobotJourney {
+    date: String,
     time: String,
-    direction: String,
+    rt_date: Option<String>,
+    rt_time: Option<String>,
+    direction: Option<String>,
+    origin: Option<String>,
+    track: Option<String>,
+    rt_track: Option<String>,
+    #[serde(default)]
+    cancelled: bool,
+    #[serde(rename = "ProductAtStop")]
+    product: Option<ResRobotProduct>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResRobotProduct {
+    display_number: Option<String>,
+    num: Option<String>,
+    cat_code: Option<String>,
+}
+
+/// What departures and arrivals have in common.
+struct Journey {
+    line: Option<String>,
+    transport_mode: TransportMode,
+    track: Option<String>,
+    scheduled: NaiveDateTime,
+    realtime: Option<NaiveDateTime>,
+    cancelled: bool,
+}
+
+impl TryFrom<&ResRobotJourney> for Journey {
+    type Error = AppError;
+
+    fn try_from(rrj: &ResRobotJourney) -> Result<Self, Self::Error> {
+        let scheduled = date_time(&rrj.date, &rrj.time)?;
+        let realtime = match (&rrj.rt_date, &rrj.rt_time) {
+            (rt_date, Some(rt_time)) => {
+                Some(date_time(rt_date.as_deref().unwrap_or(&rrj.date), rt_time)?)
+            }
+            (_, None) => None,
+        };
+
+        let line = rrj.product.as_ref().and_then(|product| {
+            product
+                .display_number
+                .clone()
+                .or_else(|| product.num.clone())
+        });
+        let transport_mode = rrj
+            .product
+            .as_ref()
+            .and_then(|product| product.cat_code.as_deref())
+            .map(transport_mode)
+            .unwrap_or(TransportMode::Other);
+
+        Ok(Journey {
+            line,
+            transport_mode,
+            track: rrj.rt_track.clone().or_else(|| rrj.track.clone()),
+            scheduled,
+            realtime,
+            cancelled: rrj.cancelled,
+        })
+    }
+}
+
+impl TryFrom<ResRobotJourney> for Departure {
+    type Error = AppError;
+
+    fn try_from(rrj: ResRobotJourney) -> Result<Self, Self::Error> {
+        let journey = Journey::try_from(&rrj)?;
+
+        Ok(Departure::new(
+            journey.line,
+            journey.transport_mode,
+            rrj.direction.unwrap_or_default(),
+            journey.track,
+            journey.scheduled,
+            journey.realtime,
+            journey.cancelled,
+        ))
+    }
+}
+
+impl TryFrom<ResRobotJourney> for Arrival {
+    type Error = AppError;
+
+    fn try_from(rrj: ResRobotJourney) -> Result<Self, Self::Error> {
+        let journey = Journey::try_from(&rrj)?;
+
+        Ok(Arrival::new(
+            journey.line,
+            journey.transport_mode,
+            rrj.origin.unwrap_or_default(),
+            journey.track,
+            journey.scheduled,
+            journey.realtime,
+            journey.cancelled,
+        ))
+    }
+}
+
+fn date_time(date: &str, time: &str) -> AppResult<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(&format!("{} {}", date, time), RESROBOT_DATE_TIME_FORMAT)
+        .map_err(|e| AppError::internal_error().with_context(&e))
 }
 
-impl From<ResRobotDeparture> for Departure {
-    fn from(rrd: ResRobotDeparture) -> Self {
-        Departure::new(rrd.time, rrd.direction)
+/// The product categories of ResRobot.
+fn transport_mode(cat_code: &str) -> TransportMode {
+    match cat_code {
+        "1" => TransportMode::HighSpeedTrain,
+        "2" => TransportMode::RegionalTrain,
+        "3" => TransportMode::ExpressBus,
+        "4" => TransportMode::LocalTrain,
+        "5" => TransportMode::Metro,
+        "6" => TransportMode::Tram,
+        "7" => TransportMode::Bus,
+        "8" => TransportMode::Ferry,
+        "9" => TransportMode::Taxi,
+        _ => TransportMode::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn departures_from_resrobot() {
+        let response: ResRobotBoardResponse = serde_json::from_str(
+            r#"{
+                "Departure": [
+                    {
+                        "name": "Länstrafik - Buss 4",
+                        "date": "2022-03-02",
+                        "time": "23:58:00",
+                        "rtDate": "2022-03-03",
+                        "rtTime": "00:03:00",
+                        "direction": "Radiohuset (Stockholm kn)",
+                        "track": "A",
+                        "ProductAtStop": { "displayNumber": "4", "num": "4", "catCode": "7" }
+                    },
+                    {
+                        "date": "2022-03-02",
+                        "time": "23:59:00",
+                        "direction": "Södertälje centrum",
+                        "track": "2",
+                        "rtTrack": "3",
+                        "cancelled": true,
+                        "ProductAtStop": { "num": "2845", "catCode": "4" }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let departures: Vec<_> = response
+            .departure
+            .into_iter()
+            .map(|d| Departure::try_from(d).unwrap())
+            .collect();
+
+        let bus = &departures[0];
+        assert_eq!(bus.line.as_deref(), Some("4"));
+        assert_eq!(bus.transport_mode, TransportMode::Bus);
+        assert_eq!(bus.scheduled.to_string(), "2022-03-02 23:58:00");
+        assert_eq!(bus.expected().to_string(), "2022-03-03 00:03:00");
+        assert!(!bus.cancelled);
+
+        let train = &departures[1];
+        assert_eq!(train.line.as_deref(), Some("2845"));
+        assert_eq!(train.transport_mode, TransportMode::LocalTrain);
+        assert_eq!(train.track.as_deref(), Some("3"));
+        assert_eq!(train.realtime, None);
+        assert!(train.cancelled);
+
+        let empty: ResRobotBoardResponse = serde_json::from_str("{}").unwrap();
+        assert!(empty.departure.is_empty());
+    }
+
+    #[test]
+    fn invalid_stations_are_skipped() {
+        let stops = stops_from_stations(
+            r#"[
+                { "stop_id": "740000001", "stop_name": "Slussen", "lat": 59.3195, "lon": 18.0722 },
+                { "stop_id": "740000002", "stop_name": "Gullmarsplan" },
+                { "stop_id": "740000003", "stop_name": "Skanstull", "lat": 59.3079, "lon": 18.0763 }
+            ]"#,
+        )
+        .unwrap();
+
+        let ids: Vec<_> = stops.iter().map(|stop| stop.id.as_str()).collect();
+        assert_eq!(ids, ["740000001", "740000003"]);
+        assert!(stops_from_stations("not json").is_err());
+    }
+
+    #[test]
+    fn arrivals_from_resrobot() {
+        let response: ResRobotBoardResponse = serde_json::from_str(
+            r#"{
+                "Arrival": [
+                    {
+                        "date": "2022-03-02",
+                        "time": "07:12:00",
+                        "rtTime": "07:15:00",
+                        "origin": "Uppsala Centralstation",
+                        "ProductAtStop": { "displayNumber": "40", "catCode": "4" }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert!(response.departure.is_empty());
+
+        let arrival = Arrival::try_from(response.arrival.into_iter().next().unwrap()).unwrap();
+        assert_eq!(arrival.origin, "Uppsala Centralstation");
+        assert_eq!(arrival.line.as_deref(), Some("40"));
+        assert_eq!(arrival.expected().to_string(), "2022-03-02 07:15:00");
     }
 }
@@ -1,30 +1,50 @@
+use super::subject;
 use crate::{
     app::{AppError, AppResult, ParamsError},
-    auth::{Role, TokenHandler},
+    audit::{AuditEntity, Auditor},
+    auth::{Claims, Role, TokenHandler},
+    login_throttle::{self, LoginThrottlePolicy},
+    totp::{self, Totp},
 };
-use database::{InsertionResult, User as DbUser, UserDatabase};
+use database::{AuthEventKind, InsertionResult, User as DbUser, UserDatabase};
 use model::{
-    user::{add_user, get_token, get_user, User},
-    JsonRpcError, JsonRpcRequest,
+    user::{add_user, confirm_totp, enroll_totp, get_token, get_user, unlock_user, User},
+    JsonRpcError, JsonRpcRequest, Method,
 };
-use std::{convert::TryFrom, sync::Arc};
+use serde_json::json;
+use std::{convert::TryFrom, net::IpAddr, sync::Arc};
 use time::{ext::NumericalDuration, OffsetDateTime};
 use uuid::Uuid;
 
+const TOTP_ISSUER: &str = "webserver";
+const RECOVERY_CODE_COUNT: usize = 10;
+
 pub struct UserController {
     user_db: Arc<UserDatabase>,
     token_handler: TokenHandler,
+    throttle_policy: LoginThrottlePolicy,
+    auditor: Arc<Auditor>,
 }
 
 impl UserController {
-    pub fn new(user_db: Arc<UserDatabase>, token_handler: TokenHandler) -> Self {
+    pub fn new(
+        user_db: Arc<UserDatabase>,
+        token_handler: TokenHandler,
+        auditor: Arc<Auditor>,
+    ) -> Self {
         Self {
             user_db,
             token_handler,
+            throttle_policy: LoginThrottlePolicy::new(),
+            auditor,
         }
     }
 
-    pub async fn add_user(&self, request: JsonRpcRequest) -> AppResult<add_user::MethodResult> {
+    pub async fn add_user(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<add_user::MethodResult> {
         use add_user::{MethodResult, Params};
         let params = Params::try_from(request)?;
 
@@ -36,36 +56,224 @@ impl UserController {
             .await?;
 
         match result {
-            InsertionResult::Inserted => Ok(MethodResult::success(id)),
+            InsertionResult::Inserted => {
+                self.auditor
+                    .record(
+                        claims,
+                        Method::AddUser,
+                        AuditEntity::User(id.clone()),
+                        None,
+                        Some(json!({ "id": id, "username": params.username })),
+                    )
+                    .await;
+                Ok(MethodResult::success(id))
+            }
             InsertionResult::AlreadyExists => Ok(MethodResult::failure()),
         }
     }
 
-    pub async fn get_token(&self, request: JsonRpcRequest) -> AppResult<get_token::MethodResult> {
+    pub async fn get_token(
+        &self,
+        request: JsonRpcRequest,
+        client_ip: Option<IpAddr>,
+    ) -> AppResult<get_token::MethodResult> {
         use get_token::{MethodResult, Params};
         let params = Params::try_from(request)?;
-        if let Some(user) = self
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let username_key = login_throttle::username_key(&params.username);
+        let ip_keys: Vec<String> = client_ip.map(login_throttle::ip_key).into_iter().collect();
+        let mut throttle_keys = vec![username_key.clone()];
+        throttle_keys.extend(ip_keys.iter().cloned());
+        let client_ip = client_ip.map(|ip| ip.to_string());
+
+        // the attempt is registered as failed before it is made, and taken back if it succeeds
+        let blocked_until = self
+            .user_db
+            .register_login_attempt(
+                &throttle_keys,
+                now,
+                self.throttle_policy.reset_before(now),
+                |throttle| {
+                    self.throttle_policy.blocked_until(
+                        throttle.failed_attempts as u32,
+                        throttle.last_failure.unix_timestamp(),
+                    )
+                },
+            )
+            .await?;
+        if let Some(blocked_until) = blocked_until {
+            warn!(
+                "login for '{}' from {:?} is throttled until {}",
+                params.username, client_ip, blocked_until
+            );
+            self.record_auth_event(
+                AuthEventKind::LoginThrottled,
+                &params.username,
+                None,
+                client_ip.as_deref(),
+            )
+            .await;
+            return Err(AppError::too_many_login_attempts(blocked_until - now));
+        }
+
+        let user = match self
             .user_db
             .validate_user(&params.username, &params.password)
             .await?
         {
-            info!("{} successfully logged in", user.id);
-            let roles = self.user_db.get_roles_for_user(&user.id).await?;
-            let roles = roles
-                .into_iter()
-                .filter_map(|r| Role::from_sql_value(&r).ok())
-                .collect();
-            let exp = OffsetDateTime::now_utc().checked_add(1.hours()).ok_or(
-                AppError::internal_error()
-                    .with_context(&"failed to add 1 hour to current timestamp".to_string()),
-            )?;
-            let token = self.token_handler.generate_token(exp, roles);
-            Ok(MethodResult::new(token))
-        } else {
-            Err(AppError::from(
-                JsonRpcError::internal_error().with_message("invalid username or password"),
-            ))
+            Some(user) => user,
+            None => {
+                self.record_auth_event(
+                    AuthEventKind::LoginFailed,
+                    &params.username,
+                    None,
+                    client_ip.as_deref(),
+                )
+                .await;
+                return Err(AppError::from(
+                    JsonRpcError::internal_error().with_message("invalid username or password"),
+                ));
+            }
+        };
+
+        if let Some(totp) = self.user_db.get_totp(&user.id).await? {
+            if totp.confirmed {
+                let code = match params.totp_code.as_ref() {
+                    Some(code) => code,
+                    None => {
+                        // the password was right, the client only has to ask for the code
+                        self.user_db
+                            .unregister_login_attempt(&throttle_keys)
+                            .await?;
+                        return Err(AppError::totp_required());
+                    }
+                };
+
+                if !self
+                    .verify_second_factor(&user.id, totp.secret, code, now)
+                    .await?
+                {
+                    self.record_auth_event(
+                        AuthEventKind::LoginFailed,
+                        &params.username,
+                        None,
+                        client_ip.as_deref(),
+                    )
+                    .await;
+                    return Err(AppError::totp_required().with_message("invalid two-factor code"));
+                }
+            }
         }
+
+        info!("{} successfully logged in", user.id);
+        self.user_db.clear_login_throttle(&username_key).await?;
+        // the failures of other logins from the ip are kept on purpose, or a valid account
+        // could be used to clear the failed guesses made against other accounts from the same ip
+        self.user_db.unregister_login_attempt(&ip_keys).await?;
+        self.record_auth_event(
+            AuthEventKind::LoginSucceeded,
+            &params.username,
+            Some(&user.id),
+            client_ip.as_deref(),
+        )
+        .await;
+        let roles = self.user_db.get_roles_for_user(&user.id).await?;
+        let roles = roles
+            .into_iter()
+            .filter_map(|r| Role::from_sql_value(&r).ok())
+            .collect();
+        let exp = OffsetDateTime::now_utc().checked_add(1.hours()).ok_or(
+            AppError::internal_error()
+                .with_context(&"failed to add 1 hour to current timestamp".to_string()),
+        )?;
+        let token = self.token_handler.generate_token(user.id, exp, roles);
+        Ok(MethodResult::new(token))
+    }
+
+    pub async fn enroll_totp(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<enroll_totp::MethodResult> {
+        use enroll_totp::{MethodResult, Params};
+        let _params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let user = self
+            .user_db
+            .get_user_by_id(user_id)
+            .await?
+            .ok_or_else(AppError::not_permitted)?;
+
+        let totp = Totp::new(Totp::generate_secret());
+        if !self
+            .user_db
+            .set_pending_totp(&user.id, totp.secret())
+            .await?
+        {
+            return Err(AppError::invalid_request()
+                .with_message("two-factor authentication is already enabled"));
+        }
+
+        info!("{} started two-factor enrollment", user.id);
+        self.auditor
+            .record(
+                claims,
+                Method::EnrollTotp,
+                AuditEntity::User(user.id.clone()),
+                None,
+                Some(json!({ "totp_pending": true })),
+            )
+            .await;
+
+        Ok(MethodResult::new(
+            totp.secret_base32(),
+            totp.provisioning_uri(TOTP_ISSUER, &user.username),
+        ))
+    }
+
+    pub async fn confirm_totp(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<confirm_totp::MethodResult> {
+        use confirm_totp::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+        let user_id = subject(claims)?;
+
+        let pending = match self.user_db.get_totp(user_id).await? {
+            Some(totp) if !totp.confirmed => totp,
+            _ => return Ok(MethodResult::failure()),
+        };
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let step = match Totp::new(pending.secret).verify(&params.code, now) {
+            Some(step) => step,
+            None => return Ok(MethodResult::failure()),
+        };
+
+        let recovery_codes = totp::generate_recovery_codes(RECOVERY_CODE_COUNT);
+        if !self
+            .user_db
+            .confirm_totp(user_id, step, &recovery_codes)
+            .await?
+        {
+            return Ok(MethodResult::failure());
+        }
+
+        info!("{} enabled two-factor authentication", user_id);
+        self.auditor
+            .record(
+                claims,
+                Method::ConfirmTotp,
+                AuditEntity::User(user_id.to_owned()),
+                Some(json!({ "totp_enabled": false })),
+                Some(json!({ "totp_enabled": true })),
+            )
+            .await;
+
+        Ok(MethodResult::success(recovery_codes))
     }
 
     pub async fn get_user(&self, request: JsonRpcRequest) -> AppResult<get_user::MethodResult> {
@@ -79,11 +287,95 @@ impl UserController {
             None => MethodResult::missing(),
         })
     }
+
+    pub async fn unlock_user(
+        &self,
+        request: JsonRpcRequest,
+        claims: &Option<Claims>,
+    ) -> AppResult<unlock_user::MethodResult> {
+        use unlock_user::{MethodResult, Params};
+        let params = Params::try_from(request)?;
+
+        let unlocked = self
+            .user_db
+            .clear_login_throttle(&login_throttle::username_key(&params.username))
+            .await?;
+
+        if unlocked {
+            info!("unlocked user '{}'", params.username);
+            self.record_auth_event(AuthEventKind::Unlocked, &params.username, None, None)
+                .await;
+            // failed logins are throttled for usernames that don't exist too, those have no user
+            if let Some(user) = self.user_db.get_user_by_username(&params.username).await? {
+                self.auditor
+                    .record(
+                        claims,
+                        Method::UnlockUser,
+                        AuditEntity::User(user.id),
+                        Some(json!({ "locked": true })),
+                        Some(json!({ "locked": false })),
+                    )
+                    .await;
+            }
+        }
+
+        Ok(MethodResult::new(unlocked))
+    }
+
+    /// Checks `code` either as a TOTP code or as an unused recovery code.
+    ///
+    /// A TOTP code can only be used once, and so can a recovery code.
+    async fn verify_second_factor(
+        &self,
+        user_id: &str,
+        secret: Vec<u8>,
+        code: &str,
+        now: i64,
+    ) -> AppResult<bool> {
+        let code = code.trim();
+        if totp::is_totp_code(code) {
+            match Totp::new(secret).verify(code, now) {
+                Some(step) => Ok(self.user_db.use_totp_step(user_id, step).await?),
+                None => Ok(false),
+            }
+        } else {
+            let recovery_code = totp::normalize_recovery_code(code);
+            let used = self
+                .user_db
+                .use_recovery_code(user_id, &recovery_code)
+                .await?;
+            if used {
+                warn!("{} logged in using a recovery code", user_id);
+            }
+            Ok(used)
+        }
+    }
+
+    /// Failing to record an auth event should not fail the login itself, so errors are only logged.
+    async fn record_auth_event(
+        &self,
+        kind: AuthEventKind,
+        username: &str,
+        user_id: Option<&str>,
+        ip_address: Option<&str>,
+    ) {
+        let id = Uuid::new_v4().to_string();
+        if let Err(e) = self
+            .user_db
+            .insert_auth_event(&id, kind, username, user_id, ip_address)
+            .await
+        {
+            error!("failed to insert auth event with error: '{:?}'", e);
+        }
+    }
 }
 
 impl ParamsError for add_user::InvalidParams {}
 impl ParamsError for get_token::InvalidParams {}
 impl ParamsError for get_user::InvalidParams {}
+impl ParamsError for unlock_user::InvalidParams {}
+impl ParamsError for enroll_totp::InvalidParams {}
+impl ParamsError for confirm_totp::InvalidParams {}
 
 /// Used in order to convert from `database::User` to `model::User` (orphan rule).
 struct UserWrapper(User);
@@ -93,3 +385,72 @@ impl From<DbUser> for UserWrapper {
         UserWrapper(User::new(value.id, value.username))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::Database;
+    use std::net::Ipv6Addr;
+
+    /// Skipped unless `TEST_DATABASE_URL` is set, like the tests of the database crate.
+    async fn controller() -> Option<UserController> {
+        let url = match std::env::var("TEST_DATABASE_URL") {
+            Ok(url) => url,
+            Err(_) => {
+                eprintln!("TEST_DATABASE_URL is not set, skipping");
+                return None;
+            }
+        };
+        let user_db = Arc::new(Database::new(url.clone()).await.unwrap());
+        let audit_db = Arc::new(Database::new(url).await.unwrap());
+
+        Some(UserController::new(
+            user_db,
+            TokenHandler::new("jwt secret".to_owned()),
+            Arc::new(Auditor::new(audit_db)),
+        ))
+    }
+
+    fn get_token_request(username: &str, password: &str) -> JsonRpcRequest {
+        JsonRpcRequest::new(
+            "get_token".to_owned(),
+            json!({ "username": username, "password": password }),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn logins_keep_the_failures_of_other_logins_from_the_same_ip() {
+        let controller = match controller().await {
+            Some(controller) => controller,
+            None => return,
+        };
+        let ip = IpAddr::V6(Ipv6Addr::from(Uuid::new_v4().as_u128()));
+        let username = Uuid::new_v4().to_string();
+        controller
+            .user_db
+            .insert_user(&Uuid::new_v4().to_string(), &username, "password")
+            .await
+            .unwrap();
+
+        let other_username = Uuid::new_v4().to_string();
+        for _ in 0..2 {
+            let request = get_token_request(&other_username, "guess");
+            assert!(controller.get_token(request, Some(ip)).await.is_err());
+        }
+        let request = get_token_request(&username, "password");
+        assert!(controller.get_token(request, Some(ip)).await.is_ok());
+
+        let throttles = controller
+            .user_db
+            .get_login_throttles(&[
+                login_throttle::username_key(&username),
+                login_throttle::ip_key(ip),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(throttles.len(), 1);
+        assert_eq!(throttles[0].throttle_key, login_throttle::ip_key(ip));
+        assert_eq!(throttles[0].failed_attempts, 2);
+    }
+}
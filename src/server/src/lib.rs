@@ -1,19 +1,49 @@
 #![allow(clippy::new_without_default)]
 
 use app::{App, AppError};
+use attachments::ATTACHMENTS_URI;
 use auth::{Claims, TokenHandler};
+use calendar::{CalendarComponent, CALENDAR_URI};
 use futures::future;
 use hyper::{body::Buf, Body, Request, Response};
-use model::{JsonRpcError, JsonRpcRequest, JsonRpcResponse};
+use list_events::LIST_EVENTS_URI;
+use model::{
+    chrono::{DateTime, TimeZone, Utc},
+    error_codes,
+    list::Attachment,
+    JsonRpcError, JsonRpcRequest, JsonRpcResponse,
+};
+use sas::SasVerifier;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
-use std::{convert::TryInto, fmt::Debug, sync::Arc};
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    fmt::Debug,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+};
 use time::OffsetDateTime;
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
 
 pub mod app;
+pub mod attachments;
+pub mod audit;
 pub mod auth;
+pub mod calendar;
 pub mod controller;
 pub mod influx;
+pub mod list_events;
+pub mod list_format;
+pub mod login_throttle;
+pub mod recurrence;
+pub mod sas;
+pub mod stop_search;
+pub mod totp;
+pub mod trash;
 
 #[macro_use]
 extern crate log;
@@ -22,36 +52,76 @@ extern crate log;
 pub struct AppSettings {
     pub port: u16,
     pub database_addr: String,
+    /// The Redis that the GTFS data is loaded into, 6.2 or newer.
+    pub redis_addr: String,
     pub jwt_secret: String,
     pub publish_request_log: bool,
     pub influx_addr: Option<String>,
     pub influx_token: Option<String>,
     pub influx_org: Option<String>,
     pub resrobot_api_key: String,
+    pub trust_proxy_headers: bool,
+    pub sas_key_name: Option<String>,
+    pub sas_key_value: Option<String>,
+    /// Days that deleted list items are kept in the trash before they are purged.
+    pub trash_retention_days: u32,
+    /// The directory that the files of list item attachments are stored in.
+    pub attachment_root: PathBuf,
+    /// The largest file that can be attached to a list item, in bytes.
+    pub attachment_max_bytes: u64,
 }
 
 const API_URI: &'static str = "/api";
 const PING_URI: &'static str = "/api/ping";
-const URIS: [&'static str; 2] = [API_URI, PING_URI];
+const URIS: [&'static str; 5] = [
+    API_URI,
+    PING_URI,
+    LIST_EVENTS_URI,
+    ATTACHMENTS_URI,
+    CALENDAR_URI,
+];
 
 pub async fn entry_point(
     webserver: Arc<Webserver>,
     request: Request<Body>,
+    remote_addr: SocketAddr,
 ) -> Result<Response<Body>, hyper::Error> {
-    Ok(webserver.handle_request(request).await)
+    Ok(webserver.handle_request(request, remote_addr).await)
 }
 
 pub struct Webserver {
     app: Arc<App>,
     tokens: TokenHandler,
+    trust_proxy_headers: bool,
+    sas_verifier: Option<SasVerifier>,
+    attachment_max_bytes: u64,
 }
 
 impl Webserver {
-    pub fn new(app: Arc<App>, tokens: TokenHandler) -> Self {
-        Self { app, tokens }
+    pub fn new(app: Arc<App>, tokens: TokenHandler, settings: &AppSettings) -> Self {
+        let sas_verifier = match (&settings.sas_key_name, &settings.sas_key_value) {
+            (Some(key_name), Some(key_value)) => Some(SasVerifier::new(
+                vec![(key_name.clone(), key_value.clone())]
+                    .into_iter()
+                    .collect(),
+            )),
+            _ => None,
+        };
+
+        Self {
+            app,
+            tokens,
+            trust_proxy_headers: settings.trust_proxy_headers,
+            sas_verifier,
+            attachment_max_bytes: settings.attachment_max_bytes,
+        }
     }
 
-    pub async fn handle_request(&self, request: Request<Body>) -> Response<Body> {
+    pub async fn handle_request(
+        &self,
+        request: Request<Body>,
+        remote_addr: SocketAddr,
+    ) -> Response<Body> {
         let route = request.uri().to_string();
         let without_trailing_slash = route.trim_end_matches("/");
         // route without trailing slash for easier matching
@@ -60,12 +130,35 @@ impl Webserver {
             without_trailing_slash,
             URIS
         );
+        // the list events route takes query parameters, so only its path is matched
+        let is_list_events = request.uri().path().trim_end_matches("/") == LIST_EVENTS_URI;
+        // as do the attachment routes, downloads also have the attachment id in their path
+        let is_attachments = request.uri().path().trim_end_matches("/") == ATTACHMENTS_URI;
+        let attachment_id = request
+            .uri()
+            .path()
+            .strip_prefix(ATTACHMENTS_URI)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .map(|id| id.trim_end_matches('/').to_owned());
+        let calendar_token = calendar::token_from_path(request.uri().path()).map(str::to_owned);
         match (request.method(), without_trailing_slash) {
             (_, PING_URI) => ping_pong_response(),
             (&hyper::Method::POST, API_URI) => {
-                let response_body = self.api_route(request).await;
+                let response_body = self.api_route(request, remote_addr).await;
                 return crate::generic_json_response(response_body, 200);
             }
+            (&hyper::Method::GET, _) if is_list_events => self.list_events_route(request).await,
+            (&hyper::Method::POST, _) if is_attachments => {
+                self.upload_attachment_route(request).await
+            }
+            (&hyper::Method::GET, _) if attachment_id.is_some() => {
+                let id = attachment_id.unwrap_or_default();
+                self.download_attachment_route(&request, &id).await
+            }
+            (&hyper::Method::GET, _) if calendar_token.is_some() => {
+                let token = calendar_token.unwrap_or_default();
+                self.calendar_route(&request, &token).await
+            }
             _invalid => {
                 error!("invalid http method or route request: '{:?}'", request);
                 return crate::generic_json_response(not_found(), 200);
@@ -73,14 +166,19 @@ impl Webserver {
         }
     }
 
-    async fn api_route(&self, request: Request<Body>) -> Vec<JsonRpcResponse> {
+    async fn api_route(
+        &self,
+        request: Request<Body>,
+        remote_addr: SocketAddr,
+    ) -> Vec<JsonRpcResponse> {
         let claims = self.get_auth_claims(&request);
+        let client_ip = self.get_client_ip(&request, remote_addr);
 
         match Self::get_body_as_json(request).await {
             Ok(JsonValue::Array(values)) => {
                 let results: Vec<_> = values
                     .into_iter()
-                    .map(|v| self.parse_and_handle_single(v, &claims))
+                    .map(|v| self.parse_and_handle_single(v, &claims, client_ip))
                     .collect();
 
                 let results: Vec<_> = future::join_all(results)
@@ -110,26 +208,260 @@ impl Webserver {
         }
     }
 
+    /// Server-sent events for the changes of a list type, e.g. `/api/list_events?list_type=groceries`.
+    ///
+    /// Since browsers can't set headers on an `EventSource`, the signed url of
+    /// `get_list_events_url` can be subscribed to without them.
+    async fn list_events_route(&self, request: Request<Body>) -> Response<Body> {
+        let raw_query = request.uri().query().unwrap_or_default();
+        let query = query_params(raw_query);
+
+        let subscribed = if query.contains_key("sig") {
+            self.app.subscribe_signed_list_events(raw_query).await
+        } else {
+            let list_type = match query.get("list_type") {
+                Some(list_type) if !list_type.trim().is_empty() => list_type.trim(),
+                _ => {
+                    let error = JsonRpcError::invalid_params().with_message("missing 'list_type'");
+                    let response = vec![JsonRpcResponse::error(error, None)];
+                    return crate::generic_json_response(response, 400);
+                }
+            };
+            let owner_id = query.get("owner_id").map(String::as_str);
+            let claims = self.get_auth_claims(&request);
+
+            self.app
+                .subscribe_list_events(&claims, owner_id, list_type)
+                .await
+        };
+
+        match subscribed {
+            Ok(body) => Response::builder()
+                .status(200)
+                .header("Content-Type", "text/event-stream")
+                .header("Cache-Control", "no-cache")
+                .body(body)
+                .unwrap(),
+            Err(error) => {
+                error!("failed to subscribe to list events: '{:?}'", error.context);
+                let status = error_status(&error);
+                let response = vec![JsonRpcResponse::error(error.rpc_error, None)];
+                crate::generic_json_response(response, status)
+            }
+        }
+    }
+
+    /// Upload a file as an attachment of a list item,
+    /// e.g. `/api/attachments?list_item_id=<uuid>&file_name=receipt.pdf` with the file as the body.
+    ///
+    /// The type of the file is taken from the `Content-Type` header.
+    async fn upload_attachment_route(&self, request: Request<Body>) -> Response<Body> {
+        let query = query_params(request.uri().query().unwrap_or_default());
+        let list_item_id = query
+            .get("list_item_id")
+            .and_then(|id| Uuid::from_str(id).ok());
+        let file_name = query
+            .get("file_name")
+            .and_then(|file_name| attachments::sanitize_file_name(file_name));
+        let (list_item_id, file_name) = match (list_item_id, file_name) {
+            (Some(list_item_id), Some(file_name)) => (list_item_id, file_name),
+            _ => {
+                let error = JsonRpcError::invalid_params()
+                    .with_message("'list_item_id' and 'file_name' are required");
+                let response = vec![JsonRpcResponse::error(error, None)];
+                return crate::generic_json_response(response, 400);
+            }
+        };
+
+        let content_type = request
+            .headers()
+            .get("Content-Type")
+            .and_then(|header| header.to_str().ok())
+            .map(|content_type| content_type.trim().to_lowercase())
+            .unwrap_or_default();
+        if !Attachment::is_allowed_content_type(&content_type) {
+            let error = JsonRpcError::invalid_params().with_message(format!(
+                "the content type should be one of {:?}",
+                Attachment::ALLOWED_CONTENT_TYPES
+            ));
+            let response = vec![JsonRpcResponse::error(error, None)];
+            return crate::generic_json_response(response, 415);
+        }
+
+        // larger files are rejected up front if their size is known,
+        // otherwise while they are being stored
+        let content_length = request
+            .headers()
+            .get("Content-Length")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|length| length.parse::<u64>().ok());
+        if content_length > Some(self.attachment_max_bytes) {
+            let error = JsonRpcError::invalid_params().with_message(format!(
+                "attachments can't be larger than {} bytes",
+                self.attachment_max_bytes
+            ));
+            let response = vec![JsonRpcResponse::error(error, None)];
+            return crate::generic_json_response(response, 413);
+        }
+
+        let claims = self.get_auth_claims(&request);
+        match self
+            .app
+            .upload_attachment(
+                &claims,
+                list_item_id,
+                &file_name,
+                &content_type,
+                request.into_body(),
+            )
+            .await
+        {
+            Ok(attachment) => {
+                let response = vec![JsonRpcResponse::success(attachment, None)];
+                crate::generic_json_response(response, 200)
+            }
+            Err(error) => {
+                error!("failed to upload attachment: '{:?}'", error.context);
+                let status = error_status(&error);
+                let response = vec![JsonRpcResponse::error(error.rpc_error, None)];
+                crate::generic_json_response(response, status)
+            }
+        }
+    }
+
+    /// Download the file of an attachment from a signed url, see `get_list_item_attachments`.
+    async fn download_attachment_route(&self, request: &Request<Body>, id: &str) -> Response<Body> {
+        let id = match Uuid::from_str(id) {
+            Ok(id) => id,
+            Err(_) => return crate::generic_json_response(not_found(), 404),
+        };
+        let query = request.uri().query().unwrap_or_default();
+
+        match self.app.download_attachment(id, query).await {
+            Ok(Some((attachment, file))) => Response::builder()
+                .status(200)
+                .header("Content-Type", attachment.content_type)
+                .header(
+                    "Content-Disposition",
+                    format!("attachment; filename=\"{}\"", attachment.file_name),
+                )
+                .header("X-Content-Type-Options", "nosniff")
+                .header("Cache-Control", "private, no-store")
+                .body(Body::wrap_stream(ReaderStream::new(file)))
+                .unwrap_or_else(|e| {
+                    error!(
+                        "failed to build the download of attachment '{}': '{}'",
+                        id, e
+                    );
+                    let response =
+                        vec![JsonRpcResponse::error(JsonRpcError::internal_error(), None)];
+                    crate::generic_json_response(response, 500)
+                }),
+            Ok(None) => crate::generic_json_response(not_found(), 404),
+            Err(error) => {
+                error!("failed to download attachment: '{:?}'", error.context);
+                let status = error_status(&error);
+                let response = vec![JsonRpcResponse::error(error.rpc_error, None)];
+                crate::generic_json_response(response, status)
+            }
+        }
+    }
+
+    /// The iCalendar feed of a user, e.g. `/api/calendar/<token>.ics?component=vevent`,
+    /// see `regenerate_calendar_feed`. Items are to-dos unless `component` is `vevent`.
+    async fn calendar_route(&self, request: &Request<Body>, token: &str) -> Response<Body> {
+        let query = query_params(request.uri().query().unwrap_or_default());
+        let component = match query.get("component") {
+            Some(component) => match CalendarComponent::from_str(component) {
+                Ok(component) => component,
+                Err(_) => {
+                    let error = JsonRpcError::invalid_params()
+                        .with_message("'component' should be 'vtodo' or 'vevent'");
+                    let response = vec![JsonRpcResponse::error(error, None)];
+                    return crate::generic_json_response(response, 400);
+                }
+            },
+            None => CalendarComponent::Todo,
+        };
+
+        match self.app.calendar_feed(token, component).await {
+            Ok(Some(feed)) => Response::builder()
+                .status(200)
+                .header("Content-Type", "text/calendar; charset=utf-8")
+                .header("Cache-Control", "private, no-cache")
+                .body(Body::from(feed))
+                .unwrap(),
+            Ok(None) => crate::generic_json_response(not_found(), 404),
+            Err(error) => {
+                error!("failed to generate calendar feed: '{:?}'", error.context);
+                let response = vec![JsonRpcResponse::error(error.rpc_error, None)];
+                crate::generic_json_response(response, 500)
+            }
+        }
+    }
+
+    /// Claims from either a `Bearer` JWT or a `SharedAccessSignature` token in the `Authorization` header.
     fn get_auth_claims(&self, request: &Request<Body>) -> Option<Claims> {
-        let header = request.headers().get("Authorization")?;
-        let token = header.to_str().ok()?.trim_start_matches("Bearer ");
-        self.tokens.parse_token(token).ok()
+        let header = request.headers().get("Authorization")?.to_str().ok()?;
+
+        if header.starts_with("SharedAccessSignature ") {
+            let verifier = self.sas_verifier.as_ref()?;
+            // the resource uri is checked per method, since a batch can contain several methods
+            match verifier.verify(header, current_timestamp_s(), None) {
+                Ok(token) => Some(Claims::sas(
+                    token.key_name(),
+                    token.expiry(),
+                    token.resource_uri(),
+                )),
+                Err(e) => {
+                    error!("failed to validate SAS token with error: '{}'", e);
+                    None
+                }
+            }
+        } else {
+            let token = header.trim_start_matches("Bearer ");
+            self.tokens.parse_token(token).ok()
+        }
+    }
+
+    /// The address of the client, taken from `X-Forwarded-For` if the server runs behind a trusted proxy.
+    fn get_client_ip(&self, request: &Request<Body>, remote_addr: SocketAddr) -> IpAddr {
+        if self.trust_proxy_headers {
+            let forwarded_ip = request
+                .headers()
+                .get("X-Forwarded-For")
+                .and_then(|header| header.to_str().ok())
+                .and_then(forwarded_client_ip);
+            if let Some(ip) = forwarded_ip {
+                return ip;
+            }
+        }
+
+        remote_addr.ip()
     }
 
     async fn parse_and_handle_single(
         &self,
         request: JsonValue,
         claims: &Option<Claims>,
+        client_ip: IpAddr,
     ) -> Result<Option<JsonRpcResponse>, AppError> {
         match serde_json::from_value::<JsonRpcRequest>(request) {
             Ok(request) => {
                 if request.is_notification() {
                     let claims_clone = claims.clone();
                     let app = self.app.clone();
-                    tokio::spawn(async move { app.handle_single(request, &claims_clone).await });
+                    tokio::spawn(async move {
+                        app.handle_single(request, &claims_clone, Some(client_ip))
+                            .await
+                    });
                     Ok(None)
                 } else {
-                    Ok(Some(self.app.handle_single(request, claims).await))
+                    Ok(Some(
+                        self.app
+                            .handle_single(request, claims, Some(client_ip))
+                            .await,
+                    ))
                 }
             }
             Err(serde_error) => {
@@ -163,6 +495,28 @@ where
         .unwrap()
 }
 
+/// The url decoded parameters of a query string, the last one wins if a name is repeated.
+fn query_params(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .filter_map(|(name, value)| {
+            let value = urlencoding::decode(&value.replace('+', " ")).ok()?;
+            Some((name.to_owned(), value))
+        })
+        .collect()
+}
+
+/// The status of a failed request outside of JSON RPC, like an attachment download.
+fn error_status(error: &AppError) -> u16 {
+    match error.rpc_error.code {
+        error_codes::standard::INVALID_PARAMS => 400,
+        error_codes::application::NOT_PERMITTED => 403,
+        error_codes::application::ITEM_DOES_NOT_EXIST => 404,
+        _ => 500,
+    }
+}
+
 fn ping_pong_response() -> Response<Body> {
     Response::builder()
         .status(200)
@@ -170,6 +524,12 @@ fn ping_pong_response() -> Response<Body> {
         .unwrap()
 }
 
+/// The address that the trusted proxy appended to an `X-Forwarded-For` header,
+/// the entries before it are sent by the client and can be anything.
+fn forwarded_client_ip(header: &str) -> Option<IpAddr> {
+    header.rsplit(',').next()?.trim().parse().ok()
+}
+
 fn not_found() -> Vec<JsonRpcResponse> {
     let error = JsonRpcError::invalid_request().with_message("invalid route");
     let response = JsonRpcResponse::error(error, None);
@@ -183,7 +543,7 @@ pub fn get_required_env_var(var_name: &str) -> String {
 }
 
 pub fn current_timestamp_s() -> i64 {
-    OffsetDateTime::now_utc().unix_timestamp() / 1000
+    OffsetDateTime::now_utc().unix_timestamp()
 }
 
 pub fn current_timestamp_ms() -> i64 {
@@ -191,3 +551,58 @@ pub fn current_timestamp_ms() -> i64 {
         .try_into()
         .unwrap()
 }
+
+/// Convert a unix timestamp to the timestamp type used in `model`.
+///
+/// Timestamps come from the database, so one that is out of range is logged as an error
+/// and converted to the unix epoch.
+pub fn chrono_timestamp(unix_s: i64, nanos: u32) -> DateTime<Utc> {
+    match Utc.timestamp_opt(unix_s, nanos).single() {
+        Some(timestamp) => timestamp,
+        None => {
+            error!(
+                "invalid timestamp '{}' s and '{}' ns, using the unix epoch",
+                unix_s, nanos
+            );
+            Utc.timestamp_opt(0, 0).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwarded_client_ip_is_the_last_entry() {
+        assert_eq!(
+            forwarded_client_ip("1.2.3.4, 10.0.0.1, 203.0.113.7"),
+            "203.0.113.7".parse().ok()
+        );
+        assert_eq!(
+            forwarded_client_ip("2001:db8::1"),
+            "2001:db8::1".parse().ok()
+        );
+        assert_eq!(forwarded_client_ip("203.0.113.7, unknown"), None);
+        assert_eq!(forwarded_client_ip(""), None);
+    }
+
+    #[test]
+    fn error_statuses() {
+        assert_eq!(error_status(&AppError::invalid_params()), 400);
+        assert_eq!(error_status(&AppError::item_does_not_exist()), 404);
+        assert_eq!(error_status(&AppError::not_permitted()), 403);
+        assert_eq!(error_status(&AppError::internal_error()), 500);
+        assert_eq!(error_status(&AppError::database_error()), 500);
+    }
+
+    #[test]
+    fn chrono_timestamps() {
+        let timestamp = chrono_timestamp(1_646_000_000, 500_000_000);
+        assert_eq!(timestamp.timestamp(), 1_646_000_000);
+        assert_eq!(timestamp.timestamp_subsec_millis(), 500);
+
+        assert_eq!(chrono_timestamp(i64::MAX, 0).timestamp(), 0);
+        assert_eq!(chrono_timestamp(0, 2_000_000_000).timestamp(), 0);
+    }
+}
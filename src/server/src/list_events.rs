@@ -0,0 +1,298 @@
+use crate::sas::{self, SasError, SasToken};
+use database::{Database, ListItem, ListItemNotification};
+use hyper::{body::Bytes, Body};
+use serde_json::json;
+use std::{str::FromStr, sync::Arc, time::Duration};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+pub const LIST_EVENTS_URI: &str = "/api/list_events";
+
+/// How long a signed subscription url can be connected to.
+const SUBSCRIPTION_URL_VALIDITY_S: i64 = 60;
+
+const CHANNEL_CAPACITY: usize = 1024;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+enum ListEvent {
+    Changed(ListItemNotification),
+    /// Notifications may have been missed, so subscribers should sync with `get_list_changes`.
+    Missed,
+}
+
+/// A user subscribing to the changes of a list type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subscription {
+    pub user_id: String,
+    pub owner_id: String,
+    pub list_type: String,
+}
+
+impl Subscription {
+    /// The resource that the signed url of the subscription is signed for.
+    fn resource_uri(&self) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            LIST_EVENTS_URI,
+            urlencoding::encode(&self.user_id),
+            urlencoding::encode(&self.owner_id),
+            urlencoding::encode(&self.list_type)
+        )
+    }
+
+    fn from_resource_uri(resource_uri: &str) -> Option<Self> {
+        let segments = resource_uri
+            .strip_prefix(LIST_EVENTS_URI)?
+            .strip_prefix('/')?
+            .split('/')
+            .map(|segment| urlencoding::decode(segment).ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        match segments.as_slice() {
+            [user_id, owner_id, list_type] => Some(Self {
+                user_id: user_id.clone(),
+                owner_id: owner_id.clone(),
+                list_type: list_type.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Fans out the list item changes of the database to the clients subscribed to this server,
+/// changes made through other servers sharing the database are included.
+#[derive(Clone)]
+pub struct ListEvents {
+    sender: broadcast::Sender<ListEvent>,
+    /// The name and value of the shared access key that subscription urls are signed with.
+    signing_key: Option<(String, String)>,
+}
+
+impl ListEvents {
+    pub fn new(signing_key: Option<(String, String)>) -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            signing_key,
+        }
+    }
+
+    /// A url for the subscription that can be connected to until the returned unix timestamp,
+    /// relative to the server. `None` if the server has no key to sign it with.
+    ///
+    /// The subscription is signed into the url, so it can't be used for any other list type.
+    pub fn subscription_url(&self, subscription: &Subscription, now: i64) -> Option<(String, i64)> {
+        let (key_name, key_value) = self.signing_key.as_ref()?;
+        let expiry = now + SUBSCRIPTION_URL_VALIDITY_S;
+
+        // the fields of the token are used as the query of the url
+        let token =
+            sas::generate(expiry, &subscription.resource_uri(), key_value, key_name).ok()?;
+        let fields = token.split_once(' ')?.1;
+
+        Some((format!("{}?{}", LIST_EVENTS_URI, fields), expiry))
+    }
+
+    /// The subscription that the `query` of a subscription url is a valid signature for.
+    pub fn verify_subscription(&self, query: &str, now: i64) -> Result<Subscription, SasError> {
+        let (key_name, key_value) = self.signing_key.as_ref().ok_or(SasError::InvalidKey)?;
+        let token = SasToken::from_str(&format!("SharedAccessSignature {}", query))?;
+        if token.key_name() != key_name {
+            return Err(SasError::UnknownKeyName);
+        }
+        token.verify(key_value, now, None)?;
+
+        Subscription::from_resource_uri(&token.resource_uri()).ok_or(SasError::ResourceMismatch)
+    }
+
+    /// Forwards the notifications of the database to the subscribers,
+    /// reconnecting after `RECONNECT_DELAY` if listening fails.
+    pub async fn listen(self, db: Arc<Database<ListItem>>) {
+        loop {
+            let mut listener = match db.listen().await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("failed to listen for list item changes with error: '{}'", e);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                    continue;
+                }
+            };
+
+            loop {
+                let event = match listener.recv().await {
+                    Ok(Some(notification)) => ListEvent::Changed(notification),
+                    Ok(None) => {
+                        warn!("lost connection while listening for list item changes");
+                        ListEvent::Missed
+                    }
+                    Err(e) => {
+                        error!("failed to receive list item change with error: '{}'", e);
+                        let _ = self.sender.send(ListEvent::Missed);
+                        break;
+                    }
+                };
+                // there may be no subscribers at the moment
+                let _ = self.sender.send(event);
+            }
+
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    /// A `text/event-stream` body with the changes of the list type,
+    /// which ends when the client disconnects.
+    ///
+    /// Every change is sent as a `list_item_change` event with the id and revision of the item,
+    /// and a `resync` event is sent if changes may have been missed.
+    pub fn subscribe(&self, owner_id: String, list_type: String) -> Body {
+        let mut receiver = self.sender.subscribe();
+        let (mut sender, body) = Body::channel();
+
+        tokio::spawn(async move {
+            let mut keep_alive = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+            loop {
+                let message = tokio::select! {
+                    event = receiver.recv() => match event {
+                        Ok(ListEvent::Changed(notification)) => {
+                            if !is_subscribed(&notification, &owner_id, &list_type) {
+                                continue;
+                            }
+                            change_event(&notification)
+                        }
+                        Ok(ListEvent::Missed) | Err(RecvError::Lagged(_)) => {
+                            resync_event()
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = keep_alive.tick() => ": keep-alive\n\n".to_owned(),
+                };
+
+                if sender.send_data(Bytes::from(message)).await.is_err() {
+                    trace!("list event subscriber of '{}' disconnected", list_type);
+                    break;
+                }
+            }
+        });
+
+        body
+    }
+}
+
+fn is_subscribed(notification: &ListItemNotification, owner_id: &str, list_type: &str) -> bool {
+    notification.owner_id.as_deref() == Some(owner_id) && notification.list_type == list_type
+}
+
+fn change_event(notification: &ListItemNotification) -> String {
+    let data = json!({
+        "id": notification.id,
+        "owner_id": notification.owner_id,
+        "list_type": notification.list_type,
+        "revision": notification.revision,
+    });
+    format!("event: list_item_change\ndata: {}\n\n", data)
+}
+
+fn resync_event() -> String {
+    "event: resync\ndata: {}\n\n".to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_600_000_000;
+
+    fn list_events() -> ListEvents {
+        ListEvents::new(Some((
+            "test_key".to_owned(),
+            "c2VjcmV0IGtleSB2YWx1ZQ==".to_owned(),
+        )))
+    }
+
+    fn subscription(list_type: &str) -> Subscription {
+        Subscription {
+            user_id: "user".to_owned(),
+            owner_id: "owner/with?odd&chars".to_owned(),
+            list_type: list_type.to_owned(),
+        }
+    }
+
+    fn notification(owner_id: Option<&str>, list_type: &str) -> ListItemNotification {
+        serde_json::from_value(json!({
+            "id": "1",
+            "owner_id": owner_id,
+            "list_type": list_type,
+            "revision": 7,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn subscription_urls_are_signed_for_one_subscription() {
+        let list_events = list_events();
+        let subscription = subscription("Groceries");
+
+        let (url, expiry) = list_events.subscription_url(&subscription, NOW).unwrap();
+        assert_eq!(expiry, NOW + SUBSCRIPTION_URL_VALIDITY_S);
+        let (path, query) = url.split_once('?').unwrap();
+        assert_eq!(path, LIST_EVENTS_URI);
+
+        assert_eq!(
+            list_events.verify_subscription(query, NOW),
+            Ok(subscription.clone())
+        );
+        assert_eq!(
+            list_events.verify_subscription(query, expiry),
+            Err(SasError::Expired)
+        );
+
+        // signatures are case sensitive, unlike the resources of other tokens
+        let tampered = query.replace("Groceries", "groceries");
+        assert_ne!(tampered, query);
+        assert_eq!(
+            list_events.verify_subscription(&tampered, NOW),
+            Err(SasError::InvalidSignature)
+        );
+
+        let unsigned = ListEvents::new(None);
+        assert_eq!(unsigned.subscription_url(&subscription, NOW), None);
+        assert_eq!(
+            unsigned.verify_subscription(query, NOW),
+            Err(SasError::InvalidKey)
+        );
+    }
+
+    #[test]
+    fn only_changes_of_the_subscribed_list_type_are_sent() {
+        assert!(is_subscribed(
+            &notification(Some("owner"), "groceries"),
+            "owner",
+            "groceries"
+        ));
+        assert!(!is_subscribed(
+            &notification(Some("owner"), "Groceries"),
+            "owner",
+            "groceries"
+        ));
+        assert!(!is_subscribed(
+            &notification(Some("other"), "groceries"),
+            "owner",
+            "groceries"
+        ));
+        assert!(!is_subscribed(
+            &notification(None, "groceries"),
+            "owner",
+            "groceries"
+        ));
+    }
+
+    #[test]
+    fn events() {
+        assert_eq!(
+            change_event(&notification(Some("owner"), "groceries")),
+            "event: list_item_change\ndata: {\"id\":\"1\",\"list_type\":\"groceries\",\"owner_id\":\"owner\",\"revision\":7}\n\n"
+        );
+        assert_eq!(resync_event(), "event: resync\ndata: {}\n\n");
+    }
+}
@@ -0,0 +1,304 @@
+use model::{
+    chrono::NaiveDate,
+    list::{ListFormat, ListItem, ListItemDetails},
+};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+const CSV_HEADER: [&str; 6] = [
+    "item_name",
+    "quantity",
+    "unit",
+    "notes",
+    "due_date",
+    "completed",
+];
+
+/// A list item as it is exported, and as it is read when importing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ListRecord {
+    pub item_name: String,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub notes: Option<String>,
+    pub due_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub completed: bool,
+}
+
+impl ListRecord {
+    pub fn details(&self) -> ListItemDetails {
+        ListItemDetails::new(
+            self.quantity,
+            self.unit.clone(),
+            self.notes.clone(),
+            self.due_date,
+        )
+    }
+}
+
+impl From<&ListItem> for ListRecord {
+    fn from(list_item: &ListItem) -> Self {
+        Self {
+            item_name: list_item.item_name.clone(),
+            quantity: list_item.details.quantity,
+            unit: list_item.details.unit.clone(),
+            notes: list_item.details.notes.clone(),
+            due_date: list_item.details.due_date,
+            completed: list_item.completed,
+        }
+    }
+}
+
+/// One parsed line of an imported list, or why it couldn't be parsed.
+///
+/// For JSON, `line` is the position of the item in the array (starting at 1).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedRecord {
+    pub line: usize,
+    pub record: Result<ListRecord, String>,
+}
+
+impl ParsedRecord {
+    fn new(line: usize, record: Result<ListRecord, String>) -> Self {
+        Self {
+            line,
+            record: record.and_then(validate),
+        }
+    }
+}
+
+/// Markdown checklists only contain the item names and whether they are completed.
+pub fn export(
+    format: ListFormat,
+    list_type: &str,
+    list_items: &[ListItem],
+) -> Result<String, Box<dyn Error>> {
+    let records = list_items.iter().map(ListRecord::from);
+
+    match format {
+        ListFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(vec![]);
+            writer.write_record(CSV_HEADER)?;
+            for record in records {
+                writer.serialize(record)?;
+            }
+            Ok(String::from_utf8(writer.into_inner()?)?)
+        }
+        ListFormat::Json => Ok(serde_json::to_string_pretty(&records.collect::<Vec<_>>())?),
+        ListFormat::Markdown => {
+            let mut output = format!("# {}\n\n", list_type);
+            for record in records {
+                let check = if record.completed { "x" } else { " " };
+                output.push_str(&format!("- [{}] {}\n", check, record.item_name));
+            }
+            Ok(output)
+        }
+    }
+}
+
+/// Parses `content` line by line, so that every invalid line can be reported.
+///
+/// CSV needs a header with at least `item_name`, the other columns of an export are optional.
+pub fn parse(format: ListFormat, content: &str) -> Vec<ParsedRecord> {
+    match format {
+        ListFormat::Csv => parse_csv(content),
+        ListFormat::Json => parse_json(content),
+        ListFormat::Markdown => parse_markdown(content),
+    }
+}
+
+fn parse_csv(content: &str) -> Vec<ParsedRecord> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => return vec![ParsedRecord::new(1, Err(e.to_string()))],
+    };
+
+    reader
+        .records()
+        .map(|result| match result {
+            Ok(record) => {
+                let line = record.position().map_or(0, |p| p.line() as usize);
+                let parsed = record
+                    .deserialize::<ListRecord>(Some(&headers))
+                    .map_err(|e| e.to_string());
+                ParsedRecord::new(line, parsed)
+            }
+            Err(e) => {
+                let line = e.position().map_or(0, |p| p.line() as usize);
+                ParsedRecord::new(line, Err(e.to_string()))
+            }
+        })
+        .collect()
+}
+
+fn parse_json(content: &str) -> Vec<ParsedRecord> {
+    let values: Vec<serde_json::Value> = match serde_json::from_str(content) {
+        Ok(values) => values,
+        Err(e) => return vec![ParsedRecord::new(e.line(), Err(e.to_string()))],
+    };
+
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let parsed = serde_json::from_value(value).map_err(|e| e.to_string());
+            ParsedRecord::new(index + 1, parsed)
+        })
+        .collect()
+}
+
+/// Accepts `- [ ] item`, `- [x] item` and `- item` lines (also with `*` or `+`),
+/// blank lines and headings are skipped.
+fn parse_markdown(content: &str) -> Vec<ParsedRecord> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+
+            let parsed = match ["- ", "* ", "+ "]
+                .iter()
+                .find_map(|bullet| line.strip_prefix(bullet))
+            {
+                Some(item) => {
+                    let item = item.trim_start();
+                    let (completed, item_name) = if let Some(name) = item.strip_prefix("[ ]") {
+                        (false, name)
+                    } else if let Some(name) = item
+                        .strip_prefix("[x]")
+                        .or_else(|| item.strip_prefix("[X]"))
+                    {
+                        (true, name)
+                    } else {
+                        (false, item)
+                    };
+
+                    Ok(ListRecord {
+                        item_name: item_name.to_owned(),
+                        quantity: None,
+                        unit: None,
+                        notes: None,
+                        due_date: None,
+                        completed,
+                    })
+                }
+                None => Err("expected a checklist item like '- [ ] milk'".to_owned()),
+            };
+
+            Some(ParsedRecord::new(index + 1, parsed))
+        })
+        .collect()
+}
+
+fn validate(record: ListRecord) -> Result<ListRecord, String> {
+    let item_name = record.item_name.trim();
+    if item_name.is_empty() {
+        return Err("item_name can't be empty".to_owned());
+    }
+
+    if !record.details().has_valid_quantity() {
+        return Err("quantity must be a positive number".to_owned());
+    }
+
+    Ok(ListRecord {
+        item_name: item_name.to_owned(),
+        ..record
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn list_item(item_name: &str, details: ListItemDetails, completed: bool) -> ListItem {
+        let completed_at = if completed {
+            Some(model::chrono::Utc::now())
+        } else {
+            None
+        };
+        ListItem::new(
+            Uuid::new_v4(),
+            "groceries".to_owned(),
+            item_name.to_owned(),
+            1,
+            details,
+            completed_at,
+        )
+    }
+
+    fn records(parsed: Vec<ParsedRecord>) -> Vec<ListRecord> {
+        parsed
+            .into_iter()
+            .map(|parsed| parsed.record.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let items = vec![
+            list_item(
+                "milk",
+                ListItemDetails::new(
+                    Some(2.0),
+                    Some("l".to_owned()),
+                    Some("lactose free, organic".to_owned()),
+                    NaiveDate::from_ymd_opt(2022, 3, 4),
+                ),
+                false,
+            ),
+            list_item("bread", ListItemDetails::default(), true),
+        ];
+        let expected: Vec<ListRecord> = items.iter().map(ListRecord::from).collect();
+
+        for format in [ListFormat::Csv, ListFormat::Json] {
+            let exported = export(format, "groceries", &items).unwrap();
+            assert_eq!(records(parse(format, &exported)), expected);
+        }
+
+        let markdown = export(ListFormat::Markdown, "groceries", &items).unwrap();
+        assert_eq!(markdown, "# groceries\n\n- [ ] milk\n- [x] bread\n");
+        let names: Vec<_> = records(parse(ListFormat::Markdown, &markdown))
+            .into_iter()
+            .map(|record| (record.item_name, record.completed))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("milk".to_owned(), false), ("bread".to_owned(), true)]
+        );
+    }
+
+    #[test]
+    fn invalid_lines_are_reported_with_line_numbers() {
+        let csv = "item_name,quantity\nmilk,2\n ,1\neggs,-3\nbutter,many\n";
+        let errors: Vec<_> = parse(ListFormat::Csv, csv)
+            .into_iter()
+            .filter(|parsed| parsed.record.is_err())
+            .map(|parsed| parsed.line)
+            .collect();
+        assert_eq!(errors, vec![3, 4, 5]);
+
+        let markdown = "# list\n\n- [ ] milk\njust some text\n* eggs\n";
+        let parsed = parse(ListFormat::Markdown, markdown);
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[1].line, 4);
+        assert!(parsed[1].record.is_err());
+        assert_eq!(parsed[2].record.as_ref().unwrap().item_name, "eggs");
+
+        let json = r#"[{"item_name": "milk"}, {"quantity": 1}]"#;
+        let parsed = parse(ListFormat::Json, json);
+        assert!(parsed[0].record.is_ok());
+        assert_eq!(parsed[1].line, 2);
+        assert!(parsed[1].record.is_err());
+    }
+}
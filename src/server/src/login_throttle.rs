@@ -0,0 +1,110 @@
+use std::{cmp, net::IpAddr};
+
+const FREE_ATTEMPTS: u32 = 3;
+const BASE_DELAY_S: i64 = 1;
+const MAX_DELAY_S: i64 = 5 * 60;
+const LOCKOUT_THRESHOLD: u32 = 10;
+const LOCKOUT_S: i64 = 15 * 60;
+const RESET_AFTER_S: i64 = 24 * 60 * 60;
+
+/// Decides for how long logins are blocked after a number of consecutive failures.
+///
+/// The first few failures are free, after that the delay doubles for every failure (up to a cap),
+/// and once the lockout threshold is reached the key is locked for a longer period.
+#[derive(Clone, Debug)]
+pub struct LoginThrottlePolicy {
+    free_attempts: u32,
+    base_delay_s: i64,
+    max_delay_s: i64,
+    lockout_threshold: u32,
+    lockout_s: i64,
+    reset_after_s: i64,
+}
+
+impl LoginThrottlePolicy {
+    pub fn new() -> Self {
+        Self {
+            free_attempts: FREE_ATTEMPTS,
+            base_delay_s: BASE_DELAY_S,
+            max_delay_s: MAX_DELAY_S,
+            lockout_threshold: LOCKOUT_THRESHOLD,
+            lockout_s: LOCKOUT_S,
+            reset_after_s: RESET_AFTER_S,
+        }
+    }
+
+    /// Returns the unix timestamp (in seconds) until which logins are blocked, if they are blocked at all.
+    pub fn blocked_until(&self, failed_attempts: u32, last_failure_s: i64) -> Option<i64> {
+        if failed_attempts >= self.lockout_threshold {
+            Some(last_failure_s + self.lockout_s)
+        } else if failed_attempts >= self.free_attempts {
+            let exponent = cmp::min(failed_attempts - self.free_attempts, 32);
+            let delay = self
+                .base_delay_s
+                .checked_mul(1 << exponent)
+                .map_or(self.max_delay_s, |delay| cmp::min(delay, self.max_delay_s));
+            Some(last_failure_s + delay)
+        } else {
+            None
+        }
+    }
+
+    /// Failures older than this timestamp no longer count towards the throttle.
+    pub fn reset_before(&self, now_s: i64) -> i64 {
+        now_s - self.reset_after_s
+    }
+}
+
+/// The throttle key for failed logins of a username.
+pub fn username_key(username: &str) -> String {
+    format!("user:{}", username)
+}
+
+/// The throttle key for failed logins from an ip address.
+pub fn ip_key(ip: IpAddr) -> String {
+    format!("ip:{}", ip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_attempts_are_not_blocked() {
+        let policy = LoginThrottlePolicy::new();
+
+        for failed_attempts in 0..FREE_ATTEMPTS {
+            assert_eq!(policy.blocked_until(failed_attempts, 1000), None);
+        }
+    }
+
+    #[test]
+    fn delay_doubles_and_is_capped() {
+        let policy = LoginThrottlePolicy::new();
+
+        assert_eq!(policy.blocked_until(FREE_ATTEMPTS, 1000), Some(1001));
+        assert_eq!(policy.blocked_until(FREE_ATTEMPTS + 1, 1000), Some(1002));
+        assert_eq!(policy.blocked_until(FREE_ATTEMPTS + 2, 1000), Some(1004));
+        assert_eq!(policy.blocked_until(FREE_ATTEMPTS + 6, 1000), Some(1064));
+
+        let policy = LoginThrottlePolicy {
+            lockout_threshold: u32::MAX,
+            ..LoginThrottlePolicy::new()
+        };
+        assert_eq!(policy.blocked_until(100, 1000), Some(1000 + MAX_DELAY_S));
+    }
+
+    #[test]
+    fn lockout_after_threshold() {
+        let policy = LoginThrottlePolicy::new();
+
+        assert_eq!(
+            policy.blocked_until(LOCKOUT_THRESHOLD, 1000),
+            Some(1000 + LOCKOUT_S)
+        );
+        assert_eq!(
+            policy.blocked_until(LOCKOUT_THRESHOLD + 5, 1000),
+            Some(1000 + LOCKOUT_S)
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use hyper::{
+    server::conn::AddrStream,
     service::{make_service_fn, service_fn},
     Server,
 };
 use server::{app::App, auth::TokenHandler, get_required_env_var, AppSettings, Webserver};
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 use structopt::StructOpt;
 
 #[macro_use]
@@ -34,16 +35,17 @@ async fn main() {
 
     let app = Arc::new(App::new(opts.clone(), tokens.clone()).await);
 
-    let webserver = Arc::new(Webserver::new(app, tokens));
+    let webserver = Arc::new(Webserver::new(app, tokens, &opts));
 
     let addr = ([0, 0, 0, 0], opts.port).into();
 
-    let service = make_service_fn(|_| {
+    let service = make_service_fn(|conn: &AddrStream| {
         let webserver = webserver.clone();
-        async {
+        let remote_addr = conn.remote_addr();
+        async move {
             Ok::<_, hyper::Error>(service_fn(move |request| {
                 let webserver = webserver.clone();
-                server::entry_point(webserver, request)
+                server::entry_point(webserver, request, remote_addr)
             }))
         }
     });
@@ -60,6 +62,8 @@ pub struct Opts {
     port: u16,
     #[structopt(long, env = "WEBSERVER_DATABASE_ADDR")]
     database_addr: String,
+    #[structopt(long, env = "WEBSERVER_REDIS_ADDR")]
+    redis_addr: String,
     #[structopt(long, env = "WEBSERVER_JWT_SECRET")]
     jwt_secret: String,
     #[structopt(long, env = "WEBSERVER_PUBLISH_REQUEST_LOG")]
@@ -72,6 +76,27 @@ pub struct Opts {
     influx_org: Option<String>,
     #[structopt(long, env = "WEBSERVER_RESROBOT_API_KEY")]
     resrobot_api_key: String,
+    #[structopt(
+        long,
+        default_value = "false",
+        parse(try_from_str),
+        env = "WEBSERVER_TRUST_PROXY_HEADERS"
+    )]
+    trust_proxy_headers: bool,
+    #[structopt(long, env = "WEBSERVER_SAS_KEY_NAME")]
+    sas_key_name: Option<String>,
+    #[structopt(long, env = "WEBSERVER_SAS_KEY_VALUE")]
+    sas_key_value: Option<String>,
+    #[structopt(long, default_value = "30", env = "WEBSERVER_TRASH_RETENTION_DAYS")]
+    trash_retention_days: u32,
+    #[structopt(long, default_value = "attachments", env = "WEBSERVER_ATTACHMENT_ROOT")]
+    attachment_root: PathBuf,
+    #[structopt(
+        long,
+        default_value = "10485760",
+        env = "WEBSERVER_ATTACHMENT_MAX_BYTES"
+    )]
+    attachment_max_bytes: u64,
 }
 
 impl From<Opts> for AppSettings {
@@ -79,23 +104,37 @@ impl From<Opts> for AppSettings {
         Opts {
             port,
             database_addr,
+            redis_addr,
             jwt_secret,
             publish_request_log,
             influx_addr,
             influx_token,
             influx_org,
             resrobot_api_key,
+            trust_proxy_headers,
+            sas_key_name,
+            sas_key_value,
+            trash_retention_days,
+            attachment_root,
+            attachment_max_bytes,
         }: Opts,
     ) -> Self {
         AppSettings {
             port,
             database_addr,
+            redis_addr,
             jwt_secret,
             publish_request_log,
             influx_addr,
             influx_token,
             influx_org,
             resrobot_api_key,
+            trust_proxy_headers,
+            sas_key_name,
+            sas_key_value,
+            trash_retention_days,
+            attachment_root,
+            attachment_max_bytes,
         }
     }
 }
@@ -0,0 +1,143 @@
+use database::{Database, DatabaseResult, ListItem};
+use model::{
+    chrono::{DateTime, Utc},
+    list::Recurrence,
+};
+use std::{sync::Arc, time::Duration};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// The current time, injected so that recurrences can be tested at any time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Uncompletes completed list items once they recur.
+pub struct RecurrenceScheduler<C> {
+    db: Arc<Database<ListItem>>,
+    clock: C,
+}
+
+impl<C: Clock> RecurrenceScheduler<C> {
+    pub fn new(db: Arc<Database<ListItem>>, clock: C) -> Self {
+        Self { db, clock }
+    }
+
+    /// Uncompletes the items that have recurred, checking once every `CHECK_INTERVAL`.
+    pub async fn run_periodically(self) {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match self.reopen_recurred_items().await {
+                Ok(0) => (),
+                Ok(reopened) => info!("uncompleted {} recurring list items", reopened),
+                Err(e) => error!(
+                    "failed to uncomplete recurring list items with error: '{}'",
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Uncompletes the completed items that have recurred by now, and returns how many.
+    ///
+    /// Items that are changed while this runs are left as they are.
+    pub async fn reopen_recurred_items(&self) -> DatabaseResult<u64> {
+        let mut reopened = 0;
+        for db_list_item in self.db.get_completed_recurring_list_items().await? {
+            let completed_at = match db_list_item.completed_at {
+                Some(completed_at) => completed_at,
+                None => continue,
+            };
+            let recurrence = match db_list_item
+                .recurrence
+                .as_deref()
+                .map(serde_json::from_str::<Recurrence>)
+            {
+                Some(Ok(recurrence)) => recurrence,
+                Some(Err(e)) => {
+                    warn!(
+                        "invalid recurrence of list item '{}' with error: '{}'",
+                        db_list_item.id, e
+                    );
+                    continue;
+                }
+                None => continue,
+            };
+
+            let completed_at_utc =
+                crate::chrono_timestamp(completed_at.unix_timestamp(), completed_at.nanosecond());
+            if has_recurred(&recurrence, completed_at_utc, &self.clock)
+                && self
+                    .db
+                    .reopen_recurring_list_item(&db_list_item.id, completed_at)
+                    .await?
+            {
+                trace!("uncompleted recurring list item '{}'", db_list_item.id);
+                reopened += 1;
+            }
+        }
+
+        Ok(reopened)
+    }
+}
+
+/// An item recurs at the start of its next occurrence (in UTC) after the day it was completed.
+fn has_recurred(recurrence: &Recurrence, completed_at: DateTime<Utc>, clock: &impl Clock) -> bool {
+    let next_occurrence = recurrence.next_occurrence(completed_at.naive_utc().date());
+    clock.now().naive_utc().date() >= next_occurrence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::chrono::{TimeZone, Weekday};
+
+    struct FixedClock(DateTime<Utc>);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.0
+        }
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2022, 3, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn items_recur_at_the_start_of_their_next_occurrence() {
+        // wednesday evening
+        let completed_at = at(2, 20);
+
+        let daily = Recurrence::Daily;
+        assert!(!has_recurred(&daily, completed_at, &FixedClock(at(2, 23))));
+        assert!(has_recurred(&daily, completed_at, &FixedClock(at(3, 0))));
+
+        let weekly = Recurrence::Weekly {
+            weekdays: vec![Weekday::Mon],
+        };
+        assert!(!has_recurred(&weekly, completed_at, &FixedClock(at(6, 23))));
+        assert!(has_recurred(&weekly, completed_at, &FixedClock(at(7, 8))));
+
+        let after_completion = Recurrence::AfterCompletion { days: 3 };
+        assert!(!has_recurred(
+            &after_completion,
+            completed_at,
+            &FixedClock(at(4, 12))
+        ));
+        assert!(has_recurred(
+            &after_completion,
+            completed_at,
+            &FixedClock(at(5, 1))
+        ));
+    }
+}
@@ -0,0 +1,316 @@
+use hmac::{crypto_mac::InvalidKeyLength, Hmac, Mac, NewMac};
+use sha2::Sha256;
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SCHEME: &str = "SharedAccessSignature";
+
+/// The parts of a `SharedAccessSignature sr=<uri>&sig=<signature>&se=<expiry>&skn=<key name>` token.
+///
+/// `resource_uri` and `signature` are kept url encoded, exactly as they appear in the token,
+/// since the signature is calculated over the encoded resource uri.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SasToken {
+    resource_uri: String,
+    signature: String,
+    expiry: i64,
+    key_name: String,
+}
+
+impl SasToken {
+    /// The resource uri the token was signed for (url decoded).
+    pub fn resource_uri(&self) -> String {
+        urlencoding::decode(&self.resource_uri).unwrap_or_else(|_| self.resource_uri.clone())
+    }
+
+    /// Unix timestamp (in seconds) after which the token is no longer valid.
+    pub fn expiry(&self) -> i64 {
+        self.expiry
+    }
+
+    pub fn key_name(&self) -> &str {
+        &self.key_name
+    }
+
+    /// Check the signature and expiry of the token,
+    /// and that `requested_resource` (if any) is covered by the signed resource uri.
+    pub fn verify(
+        &self,
+        key_value: &str,
+        now: i64,
+        requested_resource: Option<&str>,
+    ) -> Result<(), SasError> {
+        let signature = urlencoding::decode(&self.signature)
+            .ok()
+            .and_then(|signature| base64::decode(signature).ok())
+            .ok_or(SasError::Malformed)?;
+
+        let mut hmac =
+            HmacSha256::new_from_slice(key_value.as_bytes()).map_err(|_| SasError::InvalidKey)?;
+        hmac.update(string_to_sign(&self.resource_uri, self.expiry).as_bytes());
+        hmac.verify(&signature)
+            .map_err(|_| SasError::InvalidSignature)?;
+
+        if self.expiry <= now {
+            return Err(SasError::Expired);
+        }
+
+        if let Some(requested_resource) = requested_resource {
+            if !resource_covers(&self.resource_uri(), requested_resource) {
+                return Err(SasError::ResourceMismatch);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for SasToken {
+    type Err = SasError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s
+            .trim()
+            .strip_prefix(SCHEME)
+            .ok_or(SasError::Malformed)?
+            .trim_start();
+
+        let mut resource_uri = None;
+        let mut signature = None;
+        let mut expiry = None;
+        let mut key_name = None;
+        for field in fields.split('&') {
+            let (name, value) = field.split_once('=').ok_or(SasError::Malformed)?;
+            match name {
+                "sr" => resource_uri = Some(value.to_owned()),
+                "sig" => signature = Some(value.to_owned()),
+                "se" => expiry = Some(value.parse().map_err(|_| SasError::Malformed)?),
+                "skn" => key_name = Some(value.to_owned()),
+                _unknown => return Err(SasError::Malformed),
+            }
+        }
+
+        Ok(Self {
+            resource_uri: resource_uri.ok_or(SasError::Malformed)?,
+            signature: signature.ok_or(SasError::Malformed)?,
+            expiry: expiry.ok_or(SasError::Malformed)?,
+            key_name: key_name.ok_or(SasError::Malformed)?,
+        })
+    }
+}
+
+/// Generate a token for `resource_uri` that expires at the unix timestamp `expiry`.
+pub fn generate(
+    expiry: i64,
+    resource_uri: &str,
+    key_value: &str,
+    key_name: &str,
+) -> Result<String, InvalidKeyLength> {
+    let encoded_uri = urlencoding::encode(resource_uri);
+    let mut hmac = HmacSha256::new_from_slice(key_value.as_bytes())?;
+    hmac.update(string_to_sign(&encoded_uri, expiry).as_bytes());
+    let result = hmac.finalize().into_bytes();
+    let signature = base64::encode(result);
+    let token = format!(
+        "{} sr={}&sig={}&se={}&skn={}",
+        SCHEME,
+        encoded_uri,
+        urlencoding::encode(&signature),
+        expiry,
+        key_name
+    );
+    Ok(token)
+}
+
+fn string_to_sign(encoded_resource_uri: &str, expiry: i64) -> String {
+    format!("{}\n{}", encoded_resource_uri, expiry)
+}
+
+/// A token signed for `/api` covers `/api` and `/api/get_list_items`, but not `/apis`.
+///
+/// Only the paths are compared, so a token signed for `https://example.com/api` covers `/api` too.
+pub(crate) fn resource_covers(signed: &str, requested: &str) -> bool {
+    let signed = resource_path(signed).trim_end_matches('/').to_lowercase();
+    let requested = resource_path(requested)
+        .trim_end_matches('/')
+        .to_lowercase();
+
+    match requested.strip_prefix(&signed) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// The path of a resource uri, which is the uri itself unless it has a scheme and host.
+fn resource_path(resource_uri: &str) -> &str {
+    match resource_uri.split_once("://") {
+        Some((_scheme, rest)) => rest.find('/').map_or("", |path_start| &rest[path_start..]),
+        None => resource_uri,
+    }
+}
+
+/// Verifies SAS tokens against the shared access keys known by the server.
+#[derive(Clone, Debug)]
+pub struct SasVerifier {
+    keys: HashMap<String, String>,
+}
+
+impl SasVerifier {
+    /// `keys` maps key names to key values.
+    pub fn new(keys: HashMap<String, String>) -> Self {
+        Self { keys }
+    }
+
+    pub fn verify(
+        &self,
+        token: &str,
+        now: i64,
+        requested_resource: Option<&str>,
+    ) -> Result<SasToken, SasError> {
+        let token = SasToken::from_str(token)?;
+        let key_value = self
+            .keys
+            .get(token.key_name())
+            .ok_or(SasError::UnknownKeyName)?;
+
+        token.verify(key_value, now, requested_resource)?;
+
+        Ok(token)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SasError {
+    Malformed,
+    InvalidKey,
+    UnknownKeyName,
+    InvalidSignature,
+    Expired,
+    ResourceMismatch,
+}
+
+impl Display for SasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            SasError::Malformed => "malformed token",
+            SasError::InvalidKey => "invalid key",
+            SasError::UnknownKeyName => "unknown key name",
+            SasError::InvalidSignature => "invalid signature",
+            SasError::Expired => "token has expired",
+            SasError::ResourceMismatch => "token is not valid for the requested resource",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+impl std::error::Error for SasError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_NAME: &str = "test_key";
+    const KEY_VALUE: &str = "c2VjcmV0IGtleSB2YWx1ZQ==";
+    const NOW: i64 = 1_600_000_000;
+
+    fn token_for(resource_uri: &str, expiry: i64) -> SasToken {
+        generate(expiry, resource_uri, KEY_VALUE, KEY_NAME)
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn generated_token_verifies() {
+        let token = token_for("https://example.com/api", NOW + 60);
+
+        assert_eq!(token.resource_uri(), "https://example.com/api");
+        assert_eq!(token.expiry(), NOW + 60);
+        assert_eq!(token.key_name(), KEY_NAME);
+        assert_eq!(token.verify(KEY_VALUE, NOW, None), Ok(()));
+    }
+
+    #[test]
+    fn wrong_key_or_tampering_is_rejected() {
+        let token = token_for("/api", NOW + 60);
+        assert_eq!(
+            token.verify("another key", NOW, None),
+            Err(SasError::InvalidSignature)
+        );
+
+        let tampered = SasToken {
+            expiry: NOW + 3600,
+            ..token
+        };
+        assert_eq!(
+            tampered.verify(KEY_VALUE, NOW, None),
+            Err(SasError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn expired_token_is_rejected() {
+        let token = token_for("/api", NOW);
+
+        assert_eq!(token.verify(KEY_VALUE, NOW, None), Err(SasError::Expired));
+    }
+
+    #[test]
+    fn resource_scope() {
+        let token = token_for("/api/get_list_items", NOW + 60);
+
+        assert_eq!(
+            token.verify(KEY_VALUE, NOW, Some("/api/get_list_items")),
+            Ok(())
+        );
+        assert_eq!(
+            token.verify(KEY_VALUE, NOW, Some("/api/delete_list_item")),
+            Err(SasError::ResourceMismatch)
+        );
+
+        assert!(resource_covers("/api", "/api/add_user"));
+        assert!(resource_covers("/api/", "/api"));
+        assert!(!resource_covers("/api", "/apis"));
+    }
+
+    #[test]
+    fn full_urls_are_compared_by_path() {
+        let token = token_for("https://example.com/api", NOW + 60);
+        assert_eq!(
+            token.verify(KEY_VALUE, NOW, Some("/api/get_list_items")),
+            Ok(())
+        );
+
+        assert!(resource_covers(
+            "https://example.com",
+            "/api/get_list_items"
+        ));
+        assert!(resource_covers("http://example.com/api/", "/api"));
+        assert!(!resource_covers(
+            "https://example.com/api/sleep",
+            "/api/add_user"
+        ));
+        assert!(!resource_covers("https://example.com/apis", "/api"));
+    }
+
+    #[test]
+    fn verifier_looks_up_key_by_name() {
+        let token = generate(NOW + 60, "/api", KEY_VALUE, KEY_NAME).unwrap();
+        let verifier = SasVerifier::new(
+            vec![(KEY_NAME.to_owned(), KEY_VALUE.to_owned())]
+                .into_iter()
+                .collect(),
+        );
+
+        assert!(verifier.verify(&token, NOW, Some("/api/sleep")).is_ok());
+        assert_eq!(
+            SasVerifier::new(HashMap::new()).verify(&token, NOW, None),
+            Err(SasError::UnknownKeyName)
+        );
+        assert_eq!(
+            verifier.verify("Bearer abc", NOW, None),
+            Err(SasError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use model::traffic::Stop;
+use std::collections::HashMap;
+
+/// How well the name of a stop matches a query, the best kind first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchKind {
+    Exact,
+    /// The name starts with the query.
+    NamePrefix,
+    /// A word of the name starts with the query.
+    WordPrefix,
+    Substring,
+    /// A word of the name starts with the query, give or take a few typos.
+    Fuzzy,
+}
+
+/// Stops with their names normalized once, so that they can be searched many times.
+pub struct StopIndex {
+    stops: Vec<(String, Stop)>,
+    /// The positions of the stops in `stops` by their ids.
+    positions: HashMap<String, usize>,
+}
+
+impl StopIndex {
+    pub fn new(stops: Vec<Stop>) -> Self {
+        let positions = stops
+            .iter()
+            .enumerate()
+            .map(|(position, stop)| (stop.id.clone(), position))
+            .collect();
+        let stops = stops
+            .into_iter()
+            .map(|stop| (normalize(&stop.name), stop))
+            .collect();
+
+        Self { stops, positions }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Stop> {
+        let position = *self.positions.get(id)?;
+        Some(&self.stops[position].1)
+    }
+
+    /// The stops whose names match `query`, the best matches first and at most `limit` of them.
+    ///
+    /// Case and accents are ignored, so "sodra" matches "Södra", and queries of four or more
+    /// characters may have one typo, eight or more two.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<Stop> {
+        let query = normalize(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<_> = self
+            .stops
+            .iter()
+            .filter_map(|(name, stop)| {
+                let (kind, typos) = rank(name, &query)?;
+                Some(((kind, typos, name.chars().count(), name), stop))
+            })
+            .collect();
+        matches.sort_by_key(|(key, _)| *key);
+
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, stop)| stop.clone())
+            .collect()
+    }
+}
+
+/// Ranks a normalized name against a normalized query, `None` if they don't match.
+fn rank(name: &str, query: &str) -> Option<(MatchKind, usize)> {
+    if name == query {
+        return Some((MatchKind::Exact, 0));
+    }
+    if name.starts_with(query) {
+        return Some((MatchKind::NamePrefix, 0));
+    }
+    if word_starts(name).any(|start| name[start..].starts_with(query)) {
+        return Some((MatchKind::WordPrefix, 0));
+    }
+    if name.contains(query) {
+        return Some((MatchKind::Substring, 0));
+    }
+
+    let query: Vec<char> = query.chars().collect();
+    let max_typos = max_typos(query.len());
+    if max_typos == 0 {
+        return None;
+    }
+
+    word_starts(name)
+        .map(|start| {
+            let candidate: Vec<char> = name[start..]
+                .chars()
+                .take(query.len() + max_typos)
+                .collect();
+            prefix_edit_distance(&query, &candidate)
+        })
+        .min()
+        .filter(|typos| *typos <= max_typos)
+        .map(|typos| (MatchKind::Fuzzy, typos))
+}
+
+fn max_typos(query_length: usize) -> usize {
+    match query_length {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// The byte indices that the words of a normalized name start at.
+fn word_starts(name: &str) -> impl Iterator<Item = usize> + '_ {
+    std::iter::once(0).chain(name.match_indices(' ').map(|(i, _)| i + 1))
+}
+
+/// The fewest edits that turn `query` into any prefix of `candidate`.
+fn prefix_edit_distance(query: &[char], candidate: &[char]) -> usize {
+    // distances from the query so far to every prefix of the candidate
+    let mut row: Vec<usize> = (0..=candidate.len()).collect();
+    for (i, q) in query.iter().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, c) in candidate.iter().enumerate() {
+            let substitution = previous + usize::from(q != c);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+
+    row.into_iter().min().unwrap_or_default()
+}
+
+/// Lowercase words without accents, separated by single spaces.
+fn normalize(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' => folded.push('a'),
+            'æ' => folded.push_str("ae"),
+            'ç' | 'č' => folded.push('c'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' => folded.push('e'),
+            'ì' | 'í' | 'î' | 'ï' => folded.push('i'),
+            'ñ' => folded.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => folded.push('o'),
+            'ß' => folded.push_str("ss"),
+            'š' => folded.push('s'),
+            'ù' | 'ú' | 'û' | 'ü' => folded.push('u'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'ž' => folded.push('z'),
+            c if c.is_alphanumeric() => folded.push(c),
+            _ => folded.push(' '),
+        }
+    }
+
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stops(names: &[&str]) -> Vec<Stop> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Stop::new(i.to_string(), name.to_string(), 59.3, 18.0))
+            .collect()
+    }
+
+    fn search(stops: Vec<Stop>, query: &str, limit: usize) -> Vec<Stop> {
+        StopIndex::new(stops).search(query, limit)
+    }
+
+    fn names(stops: Vec<Stop>) -> Vec<String> {
+        stops.into_iter().map(|stop| stop.name).collect()
+    }
+
+    #[test]
+    fn best_matches_first() {
+        let stops = stops(&[
+            "Stockholm Södra",
+            "Södertälje centrum",
+            "Södra station (Stockholm)",
+            "Södra",
+            "Liljeholmen",
+        ]);
+
+        assert_eq!(
+            names(search(stops.clone(), "sodra", 10)),
+            ["Södra", "Södra station (Stockholm)", "Stockholm Södra"]
+        );
+        assert_eq!(
+            names(search(stops.clone(), "STOCKHOLM", 1)),
+            ["Stockholm Södra"]
+        );
+        assert_eq!(names(search(stops, "holmen", 10)), ["Liljeholmen"]);
+    }
+
+    #[test]
+    fn typos_are_tolerated_in_longer_queries() {
+        let stops = stops(&["Stockholm Centralstation", "Göteborg Centralstation", "Ed"]);
+
+        assert_eq!(
+            names(search(stops.clone(), "stokholm", 10)),
+            ["Stockholm Centralstation"]
+        );
+        assert_eq!(
+            names(search(stops.clone(), "goteborgg central", 10)),
+            ["Göteborg Centralstation"]
+        );
+        assert_eq!(names(search(stops.clone(), "centrl", 10)).len(), 2);
+        assert!(search(stops.clone(), "ex", 10).is_empty());
+        assert!(search(stops, " - ", 10).is_empty());
+    }
+
+    #[test]
+    fn stops_by_id() {
+        let stop_index = StopIndex::new(stops(&["Slussen", "Skanstull"]));
+        assert_eq!(
+            stop_index.get("1").map(|stop| stop.name.as_str()),
+            Some("Skanstull")
+        );
+        assert!(stop_index.get("2").is_none());
+    }
+
+    #[test]
+    fn names_are_normalized() {
+        assert_eq!(normalize("  Åre  Björnänge, Café "), "are bjornange cafe");
+        assert_eq!(prefix_edit_distance(&['a', 'b'], &['a', 'b', 'c']), 0);
+        assert_eq!(prefix_edit_distance(&['a', 'x', 'c'], &['a', 'b', 'c']), 1);
+    }
+}
@@ -0,0 +1,177 @@
+use hmac::{Hmac, Mac, NewMac};
+use rand::{distributions::Alphanumeric, Rng, RngCore};
+use sha1::Sha1;
+
+const SECRET_LEN: usize = 20;
+const DIGITS: u32 = 6;
+const STEP_S: i64 = 30;
+/// How many steps before and after the current one are accepted, to allow for clock drift.
+const SKEW_STEPS: i64 = 1;
+const RECOVERY_CODE_HALF_LEN: usize = 5;
+
+/// Time-based one-time passwords as described in RFC 6238 (HMAC-SHA1, 6 digits, 30 second steps).
+///
+/// All time dependent functions take the current unix timestamp as a parameter,
+/// so that they can be tested with a fixed clock.
+pub struct Totp {
+    secret: Vec<u8>,
+}
+
+impl Totp {
+    pub fn new(secret: Vec<u8>) -> Self {
+        Self { secret }
+    }
+
+    pub fn generate_secret() -> Vec<u8> {
+        let mut secret = vec![0; SECRET_LEN];
+        rand::thread_rng().fill_bytes(&mut secret);
+        secret
+    }
+
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    pub fn secret_base32(&self) -> String {
+        base32::encode(base32::Alphabet::RFC4648 { padding: false }, &self.secret)
+    }
+
+    /// The `otpauth://` URI that authenticator apps use to set up the secret (usually scanned as a QR code).
+    pub fn provisioning_uri(&self, issuer: &str, account_name: &str) -> String {
+        format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            urlencoding::encode(issuer),
+            urlencoding::encode(account_name),
+            self.secret_base32(),
+            urlencoding::encode(issuer),
+            DIGITS,
+            STEP_S
+        )
+    }
+
+    pub fn step_at(now_s: i64) -> i64 {
+        now_s.div_euclid(STEP_S)
+    }
+
+    pub fn code_at(&self, now_s: i64) -> String {
+        self.code_for_step(Self::step_at(now_s))
+    }
+
+    /// Returns the step that `code` is valid for, if it is valid at `now_s`.
+    pub fn verify(&self, code: &str, now_s: i64) -> Option<i64> {
+        let code = code.trim();
+        if !is_totp_code(code) {
+            return None;
+        }
+
+        let current = Self::step_at(now_s);
+        (current - SKEW_STEPS..=current + SKEW_STEPS).find(|step| self.code_for_step(*step) == code)
+    }
+
+    fn code_for_step(&self, step: i64) -> String {
+        type HmacSha1 = Hmac<Sha1>;
+        let mut hmac =
+            HmacSha1::new_from_slice(&self.secret).expect("hmac accepts keys of any length");
+        hmac.update(&(step as u64).to_be_bytes());
+        let hash = hmac.finalize().into_bytes();
+
+        // dynamic truncation, RFC 4226 section 5.3
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            hash[offset] & 0x7f,
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]);
+
+        format!(
+            "{:0width$}",
+            binary % 10_u32.pow(DIGITS),
+            width = DIGITS as usize
+        )
+    }
+}
+
+pub fn is_totp_code(code: &str) -> bool {
+    code.len() == DIGITS as usize && code.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Generates single-use recovery codes on the form `xxxxx-xxxxx`.
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    (0..count)
+        .map(|_| {
+            let code: String = (&mut rng)
+                .sample_iter(&Alphanumeric)
+                .take(2 * RECOVERY_CODE_HALF_LEN)
+                .map(|c| char::from(c).to_ascii_lowercase())
+                .collect();
+            format!(
+                "{}-{}",
+                &code[..RECOVERY_CODE_HALF_LEN],
+                &code[RECOVERY_CODE_HALF_LEN..]
+            )
+        })
+        .collect()
+}
+
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.trim().to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn rfc6238_test_vectors() {
+        // the last 6 digits of the SHA1 test vectors in RFC 6238, appendix B
+        let totp = Totp::new(RFC_SECRET.to_vec());
+
+        assert_eq!(totp.code_at(59), "287082");
+        assert_eq!(totp.code_at(1_111_111_109), "081804");
+        assert_eq!(totp.code_at(1_111_111_111), "050471");
+        assert_eq!(totp.code_at(1_234_567_890), "005924");
+        assert_eq!(totp.code_at(2_000_000_000), "279037");
+    }
+
+    #[test]
+    fn verify_allows_one_step_of_skew() {
+        let totp = Totp::new(RFC_SECRET.to_vec());
+        let now = 1_234_567_890;
+        let code = totp.code_at(now);
+
+        assert_eq!(totp.verify(&code, now), Some(Totp::step_at(now)));
+        assert_eq!(totp.verify(&code, now - STEP_S), Some(Totp::step_at(now)));
+        assert_eq!(totp.verify(&code, now + STEP_S), Some(Totp::step_at(now)));
+        assert_eq!(totp.verify(&code, now + 2 * STEP_S), None);
+        assert_eq!(totp.verify("abcdef", now), None);
+        assert_eq!(totp.verify("1234567", now), None);
+    }
+
+    #[test]
+    fn provisioning_uri_contains_base32_secret() {
+        let totp = Totp::new(RFC_SECRET.to_vec());
+
+        assert_eq!(
+            totp.provisioning_uri("webserver", "user"),
+            "otpauth://totp/webserver:user?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=webserver&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+
+    #[test]
+    fn recovery_codes_are_unique_and_well_formed() {
+        let codes = generate_recovery_codes(10);
+
+        assert_eq!(codes.len(), 10);
+        for code in &codes {
+            assert_eq!(code.len(), 2 * RECOVERY_CODE_HALF_LEN + 1);
+            assert!(!is_totp_code(code));
+            assert_eq!(&normalize_recovery_code(&code.to_uppercase()), code);
+        }
+        let unique: std::collections::HashSet<_> = codes.iter().collect();
+        assert_eq!(unique.len(), codes.len());
+    }
+}
@@ -0,0 +1,71 @@
+use crate::attachments::AttachmentStore;
+use database::{Database, DatabaseResult, ListItem};
+use std::{str::FromStr, sync::Arc, time::Duration};
+use uuid::Uuid;
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Permanently deletes list items that have been in the trash for more than `retention_days`,
+/// along with the files of their attachments, checking once every `PURGE_INTERVAL`.
+pub async fn purge_periodically(
+    db: Arc<Database<ListItem>>,
+    attachments: Arc<AttachmentStore>,
+    retention_days: u32,
+) {
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match purge(&db, &attachments, retention_days).await {
+            Ok(0) => (),
+            Ok(purged) => info!("purged {} list items from the trash", purged),
+            Err(e) => error!("failed to purge the trash with error: '{}'", e),
+        }
+    }
+}
+
+async fn purge(
+    db: &Database<ListItem>,
+    attachments: &AttachmentStore,
+    retention_days: u32,
+) -> DatabaseResult<u64> {
+    let purged = db.purge_deleted_list_items(retention_days).await?;
+
+    for id in attachment_uuids(purged.attachment_ids) {
+        if let Err(e) = attachments.remove(&id).await {
+            warn!(
+                "failed to remove the file of attachment '{}' with error: '{}'",
+                id, e
+            );
+        }
+    }
+
+    Ok(purged.purged)
+}
+
+/// The files of attachments are named by their ids, so attachments with invalid ids have no file.
+fn attachment_uuids(ids: Vec<String>) -> Vec<Uuid> {
+    ids.into_iter()
+        .filter_map(|id| match Uuid::from_str(&id) {
+            Ok(uuid) => Some(uuid),
+            Err(_) => {
+                warn!("purged attachment with invalid id '{}'", id);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attachments_with_invalid_ids_are_skipped() {
+        let id = Uuid::from_u128(0x2b5f_4e36_2f6e_4b8a_9a4d_1f1a_5e9a_8c11);
+        assert_eq!(
+            attachment_uuids(vec![id.to_string(), "not-a-uuid".to_owned()]),
+            [id]
+        );
+        assert!(attachment_uuids(Vec::new()).is_empty());
+    }
+}